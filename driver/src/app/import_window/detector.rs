@@ -0,0 +1,409 @@
+//! The module that implements [`Detection`] and [`Sample`]
+
+use std::io::{self, Read};
+
+use rnalib::{
+	Compression, FastaReader, FastqReader, FlatFileFormat, FlatFileReader, ImportConfig,
+	IupacNucleotide, LoadError, NucleotideString, QualityEncoding, RecordHeader, SequenceFormat,
+	SourceFormat,
+};
+
+use super::{preprocessor::Readable, ImportSettings};
+
+/// A record parsed from the sample, or the error
+/// that stopped parsing.
+type SampleItem = Result<(RecordHeader, NucleotideString), LoadError>;
+
+/// Amount of bytes read from the beginning of the
+/// source, after decompression.
+const SAMPLE_LEN: u64 = 1 << 16;
+
+/// The format of a source, guessed from its beginning.
+pub struct Detection {
+	pub compression: Compression,
+	pub format: SequenceFormat,
+	/// Amount of leading lines that hold neither
+	/// a sequence nor a FASTA header
+	header_len: u32,
+	/// The only character of the sequence that is
+	/// not a nucleotide, if it is a punctuation mark
+	separator: Option<char>,
+	/// Whether the sequence holds other characters
+	/// that are not nucleotides
+	wrong_chars: bool,
+	encoding: QualityEncoding,
+}
+
+impl Detection {
+	/// Maximal amount of leading lines skipped
+	/// as the header of a raw sequence.
+	const MAX_HEADER_LEN: usize = 10;
+
+	/// Reads the beginning of the source described
+	/// by the settings and guesses its format.
+	pub fn run(settings: &ImportSettings) -> io::Result<Self> {
+		let (source, _) = Readable::decompress(settings)?;
+		let compression = source.get_compression();
+		let mut sample = Vec::new();
+		source.take(SAMPLE_LEN).read_to_end(&mut sample)?;
+
+		// The last line of a full sample may be cut off
+		let mut sample = &sample[..];
+		if sample.len() as u64 == SAMPLE_LEN {
+			if let Some(end) = sample.iter().rposition(|&x| x == b'\n') {
+				sample = &sample[..end + 1];
+			}
+		}
+		let lines: Vec<_> = sample.split(|&x| x == b'\n').collect();
+
+		let mut format = SequenceFormat::detect(sample);
+		let mut header_len = 0;
+		if format == SequenceFormat::Raw {
+			let start = lines.iter().position(|x| Self::is_sequence(x));
+			let start = start.unwrap_or(0).min(Self::MAX_HEADER_LEN + 1);
+			let header = &lines[..start];
+			if start <= Self::MAX_HEADER_LEN && header.iter().any(|x| !x.trim_ascii().is_empty()) {
+				header_len = start;
+				let rest = &lines[start..];
+				if rest.first().is_some_and(|x| x.starts_with(b">")) {
+					format = SequenceFormat::Fasta;
+				}
+			}
+		}
+
+		let mut wrong = Vec::new();
+		if matches!(format, SequenceFormat::Raw | SequenceFormat::Fasta) {
+			let body = lines[header_len..].iter().filter(|x| !x.starts_with(b">"));
+			for &byte in body.flat_map(|x| x.iter()) {
+				let valid =
+					byte.is_ascii_whitespace() || IupacNucleotide::parse_raw(byte).is_some();
+				if !valid && !wrong.contains(&byte) {
+					wrong.push(byte);
+				}
+			}
+		}
+		let separator = match wrong[..] {
+			[x] if x.is_ascii_punctuation() => Some(x as char),
+			_ => None,
+		};
+
+		let mut encoding = QualityEncoding::Phred33;
+		if format == SequenceFormat::Fastq {
+			let qualities = lines
+				.iter()
+				.filter(|x| !x.trim_ascii().is_empty())
+				.skip(3)
+				.step_by(4);
+			let bytes = qualities.flat_map(|x| x.trim_ascii().iter().copied());
+			let (min, max) = bytes.fold((u8::MAX, 0), |(a, b), x| (a.min(x), b.max(x)));
+			// Phred+33 scores seldom go past 'J', Phred+64 ones never fall below '@'
+			if min >= QualityEncoding::Phred64.get_offset() && max > b'J' {
+				encoding = QualityEncoding::Phred64;
+			}
+		}
+
+		Ok(Self {
+			compression,
+			format,
+			header_len: header_len as u32,
+			separator,
+			wrong_chars: !wrong.is_empty() && separator.is_none(),
+			encoding,
+		})
+	}
+
+	/// Returns the settings that fit the detected format,
+	/// with the rest of them left at their defaults.
+	pub fn get_settings(&self) -> ImportSettings {
+		let format = match self.format {
+			SequenceFormat::Raw | SequenceFormat::Fasta => SourceFormat::Sequence,
+			SequenceFormat::Fastq => SourceFormat::Fastq,
+			SequenceFormat::FlatFile(format) => SourceFormat::FlatFile(format),
+		};
+		let mut settings = ImportSettings {
+			config: ImportConfig {
+				format,
+				quality_encoding: self.encoding,
+				..ImportConfig::default()
+			},
+			..ImportSettings::default()
+		};
+		let preprocessing = &mut settings.config.preprocessing;
+		preprocessing.delete_header = self.header_len > 0;
+		if self.header_len > 0 {
+			preprocessing.header_len = self.header_len;
+		}
+		preprocessing.separator = self.separator.map(String::from).unwrap_or_default();
+		preprocessing.delete_wrong_chars = self.wrong_chars;
+		settings
+	}
+
+	/// Returns the name of the detected format, along
+	/// with the compression, if any.
+	pub fn describe(&self) -> String {
+		let format = match self.format {
+			SequenceFormat::Raw => "Surowa sekwencja",
+			SequenceFormat::Fasta => "FASTA",
+			SequenceFormat::Fastq => "FASTQ",
+			SequenceFormat::FlatFile(FlatFileFormat::GenBank) => "GenBank",
+			SequenceFormat::FlatFile(FlatFileFormat::Embl) => "EMBL",
+		};
+		match self.compression {
+			Compression::None => format.to_owned(),
+			Compression::Gzip => format!("{format} (gzip)"),
+			Compression::Zstd => format!("{format} (Zstandard)"),
+		}
+	}
+
+	/// A helper function that checks whether a line holds
+	/// a FASTA header or mostly nucleotides.
+	fn is_sequence(line: &[u8]) -> bool {
+		if line.starts_with(b">") {
+			return true;
+		}
+		let chars = line.iter().filter(|x| !x.is_ascii_whitespace());
+		let (valid, total) = chars.fold((0, 0), |(valid, total), &x| {
+			(
+				valid + IupacNucleotide::parse_raw(x).is_some() as usize,
+				total + 1,
+			)
+		});
+		total > 0 && valid * 10 >= total * 9
+	}
+}
+
+/// A single record parsed from the beginning of the source.
+pub struct SampleRecord {
+	pub header: RecordHeader,
+	/// Amount of nucleotides of the record
+	pub length: usize,
+	/// The first nucleotides of the record
+	pub preview: String,
+	/// Whether the record may continue past
+	/// the end of the sample
+	pub truncated: bool,
+}
+
+/// The first few records of the source, parsed
+/// exactly as they are imported.
+#[derive(Default)]
+pub struct Sample {
+	pub records: Vec<SampleRecord>,
+	/// The error that stopped parsing, if any
+	pub error: Option<LoadError>,
+}
+
+impl Sample {
+	/// Maximal amount of records kept.
+	pub const MAX_RECORDS: usize = 5;
+	/// Amount of nucleotides of every preview.
+	const PREVIEW_LEN: usize = 60;
+
+	/// Parses the beginning of the source described by
+	/// the settings, after pre-processing it if needed.
+	pub fn load(settings: &ImportSettings) -> io::Result<Self> {
+		if settings.config.format != SourceFormat::Sequence {
+			return Self::load_records(settings);
+		}
+		let mut bytes = Vec::new();
+		let (source, _) = Readable::preprocess(settings)?;
+		source.take(SAMPLE_LEN).read_to_end(&mut bytes)?;
+
+		let records = FastaReader::new(&bytes[..]);
+		let records = records.map(|x| x.map(|x| (x.get_header().clone(), x.into_sequence())));
+		let mut result = Self::collect(records, &bytes);
+		result.error = result.error.map(|x| settings.locate_error(x));
+		Ok(result)
+	}
+
+	/// A helper function that parses the beginning of an
+	/// annotated flat file or FASTQ file, which are not
+	/// pre-processed. FASTQ reads are filtered as well.
+	fn load_records(settings: &ImportSettings) -> io::Result<Self> {
+		let mut bytes = Vec::new();
+		let (source, _) = Readable::decompress(settings)?;
+		source.take(SAMPLE_LEN).read_to_end(&mut bytes)?;
+
+		Ok(match settings.config.format {
+			SourceFormat::FlatFile(format) => {
+				let records = FlatFileReader::new(&bytes[..], format);
+				let records =
+					records.map(|x| x.map(|x| (x.get_header().clone(), x.into_sequence())));
+				Self::collect(records, &bytes)
+			}
+			_ => {
+				let filter = &settings.config.quality_filter;
+				let records = FastqReader::new(&bytes[..], settings.config.quality_encoding);
+				let records = records.filter_map(|x| match x {
+					Ok(x) => filter
+						.apply(x)
+						.map(|x| Ok((x.get_header().clone(), x.into_sequence()))),
+					Err(err) => Some(Err(err)),
+				});
+				Self::collect(records, &bytes)
+			}
+		})
+	}
+
+	/// A helper function that keeps the first few records
+	/// parsed from given sample bytes.
+	///
+	/// If the sample does not hold the whole source, its last
+	/// record may be cut off, and errors found on its last line
+	/// are ignored.
+	fn collect(records: impl Iterator<Item = SampleItem>, bytes: &[u8]) -> Self {
+		let mut result = Self::default();
+		let mut count = 0;
+		for record in records {
+			let (header, sequence) = match record {
+				Ok(x) => x,
+				Err(err) => {
+					result.error = Some(err);
+					break;
+				}
+			};
+			count += 1;
+			if result.records.len() < Self::MAX_RECORDS {
+				result.records.push(SampleRecord {
+					header,
+					length: sequence.len(),
+					preview: sequence.slice(0, Self::PREVIEW_LEN).to_string(),
+					truncated: false,
+				});
+			}
+		}
+
+		if bytes.len() as u64 == SAMPLE_LEN {
+			let last_line = bytes.iter().rposition(|&x| x == b'\n').map_or(0, |x| x + 1);
+			let cut = match &result.error {
+				Some(LoadError::InvalidCharacter { offset, .. })
+				| Some(LoadError::InvalidRecord { offset, .. }) => *offset >= last_line as u64,
+				_ => false,
+			};
+			if cut {
+				result.error = None;
+			}
+			if result.error.is_none() && count == result.records.len() {
+				if let Some(last) = result.records.last_mut() {
+					last.truncated = true;
+				}
+			}
+		}
+		result
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{env, fs, io::Write, process};
+
+	use flate2::{write::GzEncoder, Compression as Level};
+
+	use super::*;
+
+	/// A helper function that detects the format of a given text.
+	fn detect(text: &str) -> Detection {
+		let settings = ImportSettings {
+			input_rna: text.to_owned(),
+			..Default::default()
+		};
+		Detection::run(&settings).unwrap()
+	}
+
+	/// A helper function that detects the format of a file
+	/// holding given bytes.
+	fn detect_file(name: &str, bytes: &[u8]) -> Detection {
+		let path = env::temp_dir().join(format!("detector-{}-{name}", process::id()));
+		fs::write(&path, bytes).unwrap();
+		let settings = ImportSettings {
+			from_file: true,
+			path: path.to_string_lossy().into_owned(),
+			..Default::default()
+		};
+		let detection = Detection::run(&settings);
+		fs::remove_file(&path).unwrap();
+		detection.unwrap()
+	}
+
+	#[test]
+	fn raw_sequences_are_detected() {
+		let detection = detect("ACGUACGU\nGGCCAAUU\n");
+		assert!(detection.format == SequenceFormat::Raw);
+		assert_eq!(detection.describe(), "Surowa sekwencja");
+		let settings = detection.get_settings();
+		assert!(settings.config.format == SourceFormat::Sequence);
+		assert!(settings.config.preprocessing == Default::default());
+
+		let settings = detect("ACGU,ACGU,\nGGCC,\n").get_settings();
+		assert_eq!(settings.config.preprocessing.separator, ",");
+		assert!(!settings.config.preprocessing.delete_wrong_chars);
+		let settings = detect("ACGU1ACGU\nGG#CC\n").get_settings();
+		assert!(settings.config.preprocessing.separator.is_empty());
+		assert!(settings.config.preprocessing.delete_wrong_chars);
+	}
+
+	#[test]
+	fn ambiguous_first_lines_are_headers() {
+		// Not a FASTQ read, as the third line does not start with '+'
+		let detection = detect("@ run 3\nACGUACGU\nGGCCAAUU\n");
+		assert!(detection.format == SequenceFormat::Raw);
+		let settings = detection.get_settings();
+		assert!(settings.config.preprocessing.delete_header);
+		assert_eq!(settings.config.preprocessing.header_len, 1);
+		assert!(!settings.config.preprocessing.delete_wrong_chars);
+
+		// FASTA records after a few lines of other text
+		let detection = detect("Exported on Monday\n\n>seq1\nACGU\n");
+		assert!(detection.format == SequenceFormat::Fasta);
+		assert_eq!(detection.get_settings().config.preprocessing.header_len, 2);
+	}
+
+	#[test]
+	fn record_formats_are_detected() {
+		let detection = detect(">seq1 first\nACGU\n>seq2\nGGCC\n");
+		assert!(detection.format == SequenceFormat::Fasta);
+		assert_eq!(detection.describe(), "FASTA");
+		let settings = detection.get_settings();
+		assert!(settings.config.format == SourceFormat::Sequence);
+		assert!(!settings.config.preprocessing.delete_header);
+
+		let detection = detect("@r1\nACGU\n+\nIIII\n@r2\nGG\n+\n#5\n");
+		assert_eq!(detection.describe(), "FASTQ");
+		let settings = detection.get_settings();
+		assert!(settings.config.format == SourceFormat::Fastq);
+		assert!(settings.config.quality_encoding == QualityEncoding::Phred33);
+		let settings = detect("@r1\nACGU\n+\nhhhh\n@r2\nGG\n+\nBB\n").get_settings();
+		assert!(settings.config.quality_encoding == QualityEncoding::Phred64);
+
+		let genbank = "LOCUS       X 8 bp RNA\nORIGIN\n        1 acguacgu\n//\n";
+		let settings = detect(genbank).get_settings();
+		assert!(settings.config.format == SourceFormat::FlatFile(FlatFileFormat::GenBank));
+		let embl = "ID   X; SV 1; linear; RNA\nSQ   Sequence 8 BP;\n     acguacgu 8\n//\n";
+		let settings = detect(embl).get_settings();
+		assert!(settings.config.format == SourceFormat::FlatFile(FlatFileFormat::Embl));
+	}
+
+	#[test]
+	fn compressed_files_are_detected() {
+		let fasta = ">seq1\nACGUACGU\n";
+		let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+		encoder.write_all(fasta.as_bytes()).unwrap();
+		let detection = detect_file("fasta.gz", &encoder.finish().unwrap());
+		assert!(detection.compression == Compression::Gzip);
+		assert_eq!(detection.describe(), "FASTA (gzip)");
+
+		// A single segment frame, with its size in a byte,
+		// holding a single raw block
+		let mut zstd = vec![0x28, 0xB5, 0x2F, 0xFD, 0x20, fasta.len() as u8];
+		let header = (fasta.len() as u32) << 3 | 1;
+		zstd.extend(&header.to_le_bytes()[..3]);
+		zstd.extend(fasta.as_bytes());
+		let detection = detect_file("fasta.zst", &zstd);
+		assert!(detection.compression == Compression::Zstd);
+		assert_eq!(detection.describe(), "FASTA (Zstandard)");
+
+		let detection = detect_file("fasta", fasta.as_bytes());
+		assert!(detection.compression == Compression::None);
+		assert!(detection.format == SequenceFormat::Fasta);
+	}
+}
@@ -1,19 +1,24 @@
 //! The module that implements [`ImportView`]
 
 use std::{
-	fs::File,
-	io::{BufRead, BufReader, Read},
-	sync::atomic::{AtomicBool, AtomicU32, Ordering},
+	io,
+	sync::{
+		atomic::{AtomicBool, AtomicU32, Ordering},
+		mpsc::RecvTimeoutError,
+	},
 	thread::spawn,
+	time::Duration,
 };
 
 use egui::*;
-use rnalib::{ProteinMap, ThreadedProteinLoader};
-use stringreader::StringReader;
+use rnalib::{LoadError, LoadEvent, ProteinMap, SourceFormat, ThreadedProteinLoader};
 
 use std::sync::{Arc, Mutex};
 
-use super::ImportSettings;
+use super::{
+	preprocessor::{Excerpt, Readable},
+	ImportSettings,
+};
 
 /// A window view that allows the user to peek
 /// into the importer's progress.
@@ -21,6 +26,13 @@ use super::ImportSettings;
 pub struct ImportView {
 	pub settings: ImportSettings,
 	job: ImportJob,
+	cancelled: bool,
+	/// The error that stopped the import
+	error: Option<LoadError>,
+	/// The source fragment surrounding an invalid character
+	excerpt: Option<Result<Excerpt, String>>,
+	/// Offset of the character the user wants to jump to
+	jump: Option<u64>,
 }
 
 impl ImportView {
@@ -34,29 +46,40 @@ impl ImportView {
 	}
 
 	/// Draws self to the ui.
-	pub fn show(&mut self, ui: &mut Ui) -> Option<Result<ProteinMap, String>> {
+	///
+	/// Returns the imported [`ProteinMap`] once the import
+	/// finishes. Errors are displayed by the view itself.
+	pub fn show(&mut self, ui: &mut Ui) -> Option<ProteinMap> {
+		if self.error.is_some() {
+			self.show_error(ui);
+			return None;
+		}
+
 		match self.job.finished() {
 			true => {
 				ui.label("Zaimportowano dane.");
 			}
 			false => {
-				ui.label("Importowanie w toku...");
+				ui.horizontal(|ui| {
+					ui.label("Importowanie w toku...");
+					if ui.button("Anuluj").clicked() {
+						self.job.cancel();
+						self.cancelled = true;
+					}
+				});
 				let progress = self.job.progress();
 				match progress {
 					0 => {
 						ui.label("Filtrowanie pliku wejściowego...");
-						ui.ctx().request_repaint();
 					}
 					100 => {
 						ui.label("Indeksowanie wyników...");
-						ui.ctx().request_repaint();
 					}
 					_ => {
 						let bar = ProgressBar::new(progress as f32 / 100.0)
 							.animate(true)
 							.show_percentage()
 							.text("Szukanie białek");
-						ui.ctx().request_repaint();
 						ui.add(bar);
 					}
 				}
@@ -64,68 +87,174 @@ impl ImportView {
 		};
 
 		if !self.job.started() {
-			self.job.run(self.settings.clone());
+			self.job.run(self.settings.clone(), ui.ctx().clone());
 		}
 
 		if self.job.finished() {
-			return self.job.take();
+			match self.job.take()? {
+				Ok(map) => return Some(map),
+				Err(err) => self.error = Some(err),
+			}
 		}
 
 		None
 	}
+
+	/// Returns true if the user cancelled the import,
+	/// or wants to return to the settings.
+	pub fn is_cancelled(&self) -> bool {
+		self.cancelled
+	}
+
+	/// Returns the offset of the source character the
+	/// user wants to jump to, if any.
+	pub fn get_jump(&self) -> Option<u64> {
+		self.jump
+	}
+
+	/// A helper function that displays the error that
+	/// stopped the import.
+	fn show_error(&mut self, ui: &mut Ui) {
+		let Some(error) = &self.error else { return };
+
+		ui.label(RichText::new("Import nie powiódł się.").strong());
+		ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+
+		let offset = match error {
+			LoadError::InvalidCharacter { offset, .. } => Some(*offset),
+			LoadError::InvalidRecord { offset, .. } => Some(*offset),
+			LoadError::Io(_) => None,
+		};
+
+		ui.horizontal(|ui| {
+			if ui.button("Wróć do ustawień").clicked() {
+				self.cancelled = true;
+			}
+			let Some(offset) = offset else { return };
+			if ui.button("Przejdź do błędu").clicked() {
+				match self.settings.from_file {
+					true => {
+						let excerpt = Excerpt::load(&self.settings, offset);
+						self.excerpt = Some(excerpt.map_err(|x| x.to_string()));
+					}
+					false => {
+						self.jump = Some(offset);
+						self.cancelled = true;
+					}
+				}
+			}
+		});
+
+		match &self.excerpt {
+			Some(Ok(excerpt)) => {
+				ui.separator();
+				ui.horizontal_wrapped(|ui| {
+					ui.spacing_mut().item_spacing.x = 0.0;
+					ui.monospace(&excerpt.before);
+					let character = RichText::new(&excerpt.character)
+						.monospace()
+						.background_color(ui.visuals().error_fg_color);
+					ui.label(character);
+					ui.monospace(&excerpt.after);
+				});
+			}
+			Some(Err(err)) => {
+				ui.colored_label(ui.visuals().error_fg_color, err);
+			}
+			None => {}
+		}
+	}
 }
 
 /// A multithreaded import job, that keeps track
 /// of current progress.
+///
+/// The job is cancelled when dropped.
 #[derive(Default)]
 struct ImportJob {
 	result: Arc<Mutex<Option<ProteinMap>>>,
 	progress: Arc<AtomicU32>,
 	started: Arc<AtomicBool>,
 	finished: Arc<AtomicBool>,
-	error: Arc<Mutex<Option<String>>>,
+	cancelled: Arc<AtomicBool>,
+	error: Arc<Mutex<Option<LoadError>>>,
 }
 
 impl ImportJob {
 	/// Starts the job, resetting the internal
 	/// state as necessary.
-	pub fn run(&mut self, settings: ImportSettings) {
+	///
+	/// The given context is repainted whenever
+	/// the job makes progress.
+	pub fn run(&mut self, settings: ImportSettings, ctx: Context) {
 		self.started.store(true, Ordering::Relaxed);
 		self.finished.store(false, Ordering::Relaxed);
 
 		let progess = self.progress.clone();
 		let result = self.result.clone();
 		let finished = self.finished.clone();
+		let cancelled = self.cancelled.clone();
 		let error = self.error.clone();
 
 		spawn(move || {
-			let source = Self::generate_output(&settings);
-
-			let mut importer = ThreadedProteinLoader::default();
-			importer.start(source);
+			let mut importer = settings.config.create_loader();
+			let events = importer.subscribe();
+			if let Err(err) = Self::start(&mut importer, &settings) {
+				if let Ok(mut lock) = error.lock() {
+					*lock = Some(LoadError::Io(err));
+				}
+				finished.store(true, Ordering::Relaxed);
+				ctx.request_repaint();
+				return;
+			}
 
-			while !importer.is_ready() {
-				let percentage = importer.get_progress() * 100.0;
-				let percentage = percentage.min(100.0) as u32;
-				progess.store(percentage, Ordering::Relaxed);
+			loop {
+				// Wake up periodically, so that a cancelled job does not
+				// wait for the source to deliver more data.
+				match events.recv_timeout(Duration::from_millis(100)) {
+					Ok(LoadEvent::Finished) => break,
+					Ok(LoadEvent::Progress { .. }) => {
+						let percentage = importer.get_progress() * 100.0;
+						progess.store(percentage.min(100.0) as u32, Ordering::Relaxed);
+						ctx.request_repaint();
+					}
+					Ok(LoadEvent::FrameFinished(_)) | Err(RecvTimeoutError::Timeout) => {}
+					Err(RecvTimeoutError::Disconnected) => break,
+				}
+				if cancelled.load(Ordering::Relaxed) {
+					importer.cancel();
+					return;
+				}
 			}
 
-			match importer.take() {
-				Some(map) => {
+			match importer.wait() {
+				Some(Ok(map)) => {
 					let mut guard = result.lock().unwrap();
 					*guard = Some(map);
 				}
-				None => {
+				Some(Err(err)) => {
+					let err = settings.locate_error(err);
 					if let Ok(mut lock) = error.lock() {
-						*lock = Some(ProteinMap::ERR_MESSAGE.into());
+						*lock = Some(err);
 					}
 				}
+				None => return,
 			}
 
 			finished.store(true, Ordering::Relaxed);
+			ctx.request_repaint();
 		});
 	}
 
+	/// Cancels the job, stopping all of its threads
+	/// and dropping the results found so far.
+	pub fn cancel(&mut self) {
+		self.cancelled.store(true, Ordering::Relaxed);
+		if let Ok(mut guard) = self.result.lock() {
+			*guard = None;
+		}
+	}
+
 	/// Returns true if this job had finished.
 	pub fn finished(&self) -> bool {
 		self.finished.load(Ordering::Relaxed)
@@ -147,81 +276,34 @@ impl ImportJob {
 	/// Returns [`None`] if nothing has been imported yet.
 	///
 	/// Returns [`Err`] if there was an error parsing the document.
-	pub fn take(&mut self) -> Option<Result<ProteinMap, String>> {
-		let Ok(mut guard) = self.result.lock() else { return Some(Err(ProteinMap::ERR_MESSAGE.into())) };
-		let Ok(mut lock) = self.error.lock() else { return Some(Err(ProteinMap::ERR_MESSAGE.into()))};
-		match lock.as_ref() {
-			Some(err) => {
-				let err = err.clone();
-				*lock = None;
-				Some(Err(err))
-			}
-			None => guard.take().map(Ok),
+	pub fn take(&mut self) -> Option<Result<ProteinMap, LoadError>> {
+		if let Some(err) = self.error.lock().ok()?.take() {
+			return Some(Err(err));
 		}
+		self.result.lock().ok()?.take().map(Ok)
 	}
 
-	/// A helper function that filters through the source
-	/// string and pre-processes it according to user settings.
-	fn generate_output(settings: &ImportSettings) -> String {
-		enum Readable<'a> {
-			Fs(File),
-			Str(StringReader<'a>),
-		}
-
-		impl<'a> Read for Readable<'a> {
-			fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-				match self {
-					Readable::Fs(reader) => reader.read(buf),
-					Readable::Str(reader) => reader.read(buf),
-				}
-			}
-		}
-
-		let readable = match settings.from_file {
-			true => Readable::Fs(File::open(&settings.path).unwrap()),
-			false => Readable::Str(StringReader::new(&settings.input_rna)),
-		};
-
-		let length = match &readable {
-			Readable::Fs(reader) => reader.metadata().unwrap().len() as usize,
-			Readable::Str(_) => settings.input_rna.len(),
-		};
-
-		let mut result_buffer = String::with_capacity(length);
-		let mut reader = BufReader::new(readable);
-
-		if settings.delete_header {
-			for _ in 0..settings.header_len {
-				reader.read_until(b'\n', &mut Vec::new()).ok();
-			}
-		}
-
-		let separator_len = settings.separator.len();
-		let rem_separator = separator_len != 0;
-
-		let mut byte_buff = [0u8; 4096];
-		while let Ok(len) = reader.read(&mut byte_buff) {
-			if len == 0 {
-				break;
-			}
-			for &ch in &byte_buff[0..len] {
-				let ch = ch as char;
-				if matches!(
-					ch,
-					'A' | 'G' | 'C' | 'U' | 'T' | 'a' | 'g' | 'c' | 'u' | 't'
-				) || !settings.delete_wrong_chars
-				{
-					result_buffer.push(ch);
-					if rem_separator && result_buffer.ends_with(&settings.separator) {
-						for _ in 0..separator_len {
-							result_buffer.pop();
-						}
-					}
-				}
+	/// A helper function that opens the source described by
+	/// the settings, and starts loading it with a given loader.
+	///
+	/// The source is loaded as described by [`ImportConfig::start`](rnalib::ImportConfig::start),
+	/// apart from large plain files that need no pre-processing,
+	/// which are memory-mapped instead, so that they are parsed
+	/// without being read into buffers, see [`Readable::map`].
+	fn start(importer: &mut ThreadedProteinLoader, settings: &ImportSettings) -> io::Result<()> {
+		if settings.config.format == SourceFormat::Sequence {
+			if let Some(map) = Readable::map(settings)? {
+				importer.start_bytes(map);
+				return Ok(());
 			}
 		}
+		let (source, size) = Readable::open(settings)?;
+		settings.config.start(importer, source, size)
+	}
+}
 
-		result_buffer.shrink_to_fit();
-		result_buffer
+impl Drop for ImportJob {
+	fn drop(&mut self) {
+		self.cancel();
 	}
 }
@@ -1,39 +1,96 @@
 //! The module that implements [`ImportSettings`]
 
 use egui::*;
-use rnalib::ProteinMap;
+use rnalib::{ImportConfig, LoadError, ProteinMap};
 
 use importer_view::ImportView;
+use preprocessor::Readable;
 use settings_view::SettingsView;
 
+mod detector;
 mod importer_view;
+mod preprocessor;
 mod settings_view;
 
+/// The settings of an import, i.e. the source along with
+/// the way it is imported, see [`ImportConfig`].
 #[derive(Default, Clone)]
 pub struct ImportSettings {
-	separator: String,
-	delete_wrong_chars: bool,
-	delete_header: bool,
-	header_len: u32,
+	config: ImportConfig,
 	from_file: bool,
 	input_rna: String,
 	path: String,
 }
 
+impl ImportSettings {
+	/// Checks whether both settings describe the same source.
+	fn same_source(&self, other: &Self) -> bool {
+		self.from_file == other.from_file
+			&& match self.from_file {
+				true => self.path == other.path,
+				false => self.input_rna == other.input_rna,
+			}
+	}
+
+	/// Checks whether both settings describe the same source,
+	/// parsed in the same way, regardless of what is searched
+	/// for in it.
+	fn same_parsing(&self, other: &Self) -> bool {
+		self.same_source(other)
+			&& self.config.format == other.config.format
+			&& self.config.preprocessing == other.config.preprocessing
+			&& self.config.quality_encoding == other.config.quality_encoding
+			&& self.config.quality_filter == other.config.quality_filter
+	}
+
+	/// Fills in the settings detected in the source, see
+	/// [`detector::Detection::get_settings`], except for the ones edited
+	/// by the user, i.e. the ones that no longer hold the values
+	/// filled in by the previous detection.
+	fn fill_detected(&mut self, previous: &Self, detected: &Self) {
+		macro_rules! fill {
+			($($field:ident).+) => {
+				if self.$($field).+ == previous.$($field).+ {
+					self.$($field).+ = detected.$($field).+.clone();
+				}
+			};
+		}
+		fill!(config.format);
+		fill!(config.preprocessing.separator);
+		fill!(config.preprocessing.delete_wrong_chars);
+		fill!(config.preprocessing.delete_header);
+		fill!(config.preprocessing.header_len);
+		fill!(config.quality_encoding);
+	}
+
+	/// Maps the position of an invalid character reported by
+	/// the loader, which only sees the pre-processed stream,
+	/// back onto the source described by the settings.
+	///
+	/// Flat files and FASTQ files are not pre-processed,
+	/// so their positions are left as they are.
+	fn locate_error(&self, err: LoadError) -> LoadError {
+		match Readable::open(self) {
+			Ok((source, _)) => self.config.locate_error(source, err),
+			Err(_) => err,
+		}
+	}
+}
+
 /// Enum representing the states of an import
 /// window.
 enum View {
 	/// A state that allows the user to configure
 	/// the import settings.
-	Settings(SettingsView),
+	Settings(Box<SettingsView>),
 	/// A state that allows the user to peek
 	/// into the importer's progress.
-	Import(ImportView),
+	Import(Box<ImportView>),
 }
 
 impl Default for View {
 	fn default() -> Self {
-		Self::Settings(Default::default())
+		Self::Settings(Box::default())
 	}
 }
 
@@ -47,13 +104,13 @@ pub struct ImportWindow {
 
 impl ImportWindow {
 	/// Shows self on the ui context.
-	pub fn show(&mut self, ctx: &Context) -> Option<Result<ProteinMap, String>> {
+	pub fn show(&mut self, ctx: &Context) -> Option<ProteinMap> {
 		let mut open = self.visible;
 		let mut result = None;
 
 		if !open {
-			if let View::Import(_) = self.view {
-				self.view = View::Settings(Default::default());
+			if let View::Import(view) = &self.view {
+				self.view = View::Settings(Box::new(SettingsView::new(view.settings.clone())));
 			}
 		}
 
@@ -64,11 +121,18 @@ impl ImportWindow {
 			.show(ctx, |ui| match &mut self.view {
 				View::Settings(view) => {
 					if view.show(ui) {
-						self.view = View::Import(ImportView::new(view.settings.clone()));
+						self.view = View::Import(Box::new(ImportView::new(view.settings.clone())));
 					}
 				}
 				View::Import(view) => {
 					result = view.show(ui);
+					if view.is_cancelled() {
+						let mut settings = SettingsView::new(view.settings.clone());
+						if let Some(offset) = view.get_jump() {
+							settings.jump_to(offset);
+						}
+						self.view = View::Settings(Box::new(settings));
+					}
 				}
 			});
 		self.visible = open;
@@ -0,0 +1,345 @@
+//! The module that implements [`Readable`], [`Preview`] and [`Excerpt`]
+
+use std::{
+	fs::File,
+	io::{self, BufReader, Cursor, Read, Result, Seek, SeekFrom},
+};
+
+use memmap2::Mmap;
+use rnalib::{ByteFate, Compression, Decompressor, Preprocessor};
+
+use super::ImportSettings;
+
+/// A source of the imported data.
+pub enum Readable {
+	Fs(File),
+	Str(Cursor<String>),
+}
+
+impl Readable {
+	/// Minimal size of a file that is memory-mapped, see
+	/// [`Readable::map`], in bytes.
+	const MAP_THRESHOLD: u64 = 64 << 20;
+
+	/// Opens the source described by the settings.
+	///
+	/// Returns the source along with its size, in bytes.
+	/// Compressed files are returned as they are, see
+	/// [`Readable::decompress`].
+	pub fn open(settings: &ImportSettings) -> Result<(Self, u64)> {
+		Ok(match settings.from_file {
+			true => {
+				let file = File::open(&settings.path)?;
+				let length = file.metadata()?.len();
+				(Readable::Fs(file), length)
+			}
+			false => {
+				let length = settings.input_rna.len() as u64;
+				(
+					Readable::Str(Cursor::new(settings.input_rna.clone())),
+					length,
+				)
+			}
+		})
+	}
+
+	/// Opens the source described by the settings, and
+	/// decompresses it on the fly if it is compressed.
+	///
+	/// Returns the source along with its size, in bytes,
+	/// before decompression.
+	pub fn decompress(settings: &ImportSettings) -> Result<(Decompressor<BufReader<Self>>, u64)> {
+		let (readable, length) = Self::open(settings)?;
+		Ok((Decompressor::new(BufReader::new(readable))?, length))
+	}
+
+	/// Maps the file described by the settings into memory, if
+	/// it can be loaded straight from there, i.e. it is larger than
+	/// [`Readable::MAP_THRESHOLD`], not compressed, and passes
+	/// through the pre-processor untouched.
+	///
+	/// Returns [`None`] if the source should be read instead.
+	pub fn map(settings: &ImportSettings) -> Result<Option<Mmap>> {
+		if !settings.from_file || !settings.config.preprocessing.is_identity() {
+			return Ok(None);
+		}
+		let file = File::open(&settings.path)?;
+		if file.metadata()?.len() < Self::MAP_THRESHOLD {
+			return Ok(None);
+		}
+		// SAFETY: the file is only read, and the user is not expected
+		// to modify it while it is imported. If they do, the import
+		// may yield garbage, but the memory is mapped read-only.
+		let map = unsafe { Mmap::map(&file)? };
+		match Compression::detect(&map) {
+			Compression::None => Ok(Some(map)),
+			_ => Ok(None),
+		}
+	}
+
+	/// Opens the source described by the settings, and
+	/// pre-processes it on the fly, see [`Preprocessor`].
+	///
+	/// Returns the stream along with the size of the source,
+	/// in bytes, before decompression.
+	pub fn preprocess(settings: &ImportSettings) -> Result<(Preprocessor<Self>, u64)> {
+		let (readable, length) = Self::open(settings)?;
+		Ok((
+			Preprocessor::new(readable, &settings.config.preprocessing)?,
+			length,
+		))
+	}
+}
+
+impl Read for Readable {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+		match self {
+			Readable::Fs(reader) => reader.read(buf),
+			Readable::Str(reader) => reader.read(buf),
+		}
+	}
+}
+
+impl Seek for Readable {
+	fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+		match self {
+			Readable::Fs(reader) => reader.seek(pos),
+			Readable::Str(reader) => reader.seek(pos),
+		}
+	}
+}
+
+/// The beginning of the source, split into runs of
+/// characters treated the same way by the [`Preprocessor`].
+pub struct Preview {
+	pub spans: Vec<(ByteFate, String)>,
+	/// Invalid characters of the sequence, along with
+	/// their counts, the most frequent first
+	pub invalid: Vec<(char, usize)>,
+	/// Whether the source continues past the preview
+	pub truncated: bool,
+}
+
+impl Preview {
+	/// Amount of bytes previewed, after decompression.
+	pub const LEN: u64 = 4096;
+
+	/// Reads the beginning of the source described by the
+	/// settings and finds out what the [`Preprocessor`]
+	/// does with each of its bytes.
+	pub fn load(settings: &ImportSettings) -> Result<Self> {
+		let mut bytes = Vec::new();
+		let (source, _) = Readable::decompress(settings)?;
+		source.take(Self::LEN + 1).read_to_end(&mut bytes)?;
+		let truncated = bytes.len() as u64 > Self::LEN;
+		if truncated {
+			bytes.truncate(Self::LEN as usize);
+			// The last character may be cut off
+			let start = bytes.iter().rposition(|&x| x & 0xC0 != 0x80).unwrap_or(0);
+			if bytes.len() - start < bytes[start].leading_ones().max(1) as usize {
+				bytes.truncate(start);
+			}
+		}
+
+		let fates = ByteFate::classify(&bytes, &settings.config.preprocessing);
+
+		let mut result = Self {
+			spans: Vec::new(),
+			invalid: Vec::new(),
+			truncated,
+		};
+		let mut start = 0;
+		while start < bytes.len() {
+			let fate = fates[start];
+			let len = fates[start..].iter().take_while(|&&x| x == fate).count();
+			let text = String::from_utf8_lossy(&bytes[start..start + len]).into_owned();
+			if matches!(fate, ByteFate::Invalid | ByteFate::Deleted) {
+				for character in text.chars() {
+					match result.invalid.iter_mut().find(|x| x.0 == character) {
+						Some((_, count)) => *count += 1,
+						None => result.invalid.push((character, 1)),
+					}
+				}
+			}
+			result.spans.push((fate, text));
+			start += len;
+		}
+		result
+			.invalid
+			.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+		Ok(result)
+	}
+}
+
+/// A fragment of a single line of the source,
+/// surrounding a given character.
+pub struct Excerpt {
+	pub before: String,
+	pub character: String,
+	pub after: String,
+}
+
+impl Excerpt {
+	/// Amount of bytes read on each side of the character.
+	const RADIUS: u64 = 60;
+
+	/// Reads the fragment of the source described by the
+	/// settings around the character at a given offset.
+	///
+	/// The offset refers to the source after decompression.
+	pub fn load(settings: &ImportSettings, offset: u64) -> Result<Self> {
+		let start = offset.saturating_sub(Self::RADIUS);
+		let (mut source, _) = Readable::decompress(settings)?;
+		match source.get_compression() {
+			Compression::None => {
+				source.get_mut().seek(SeekFrom::Start(start))?;
+			}
+			_ => {
+				io::copy(&mut (&mut source).take(start), &mut io::sink())?;
+			}
+		}
+
+		let mut bytes = Vec::new();
+		source.take(2 * Self::RADIUS + 4).read_to_end(&mut bytes)?;
+
+		let index = ((offset - start) as usize).min(bytes.len());
+		let len = bytes[index..]
+			.iter()
+			.skip(1)
+			.take_while(|&&x| x & 0xC0 == 0x80)
+			.count() + 1;
+		let len = len.min(bytes.len() - index);
+
+		let before = &bytes[..index];
+		let before = match before.iter().rposition(|&x| x == b'\n') {
+			Some(x) => &before[x + 1..],
+			None => before,
+		};
+		let after = &bytes[index + len..];
+		let after = match after.iter().position(|&x| x == b'\n') {
+			Some(x) => &after[..x],
+			None => after,
+		};
+
+		Ok(Self {
+			before: String::from_utf8_lossy(before).into(),
+			character: String::from_utf8_lossy(&bytes[index..index + len]).into(),
+			after: String::from_utf8_lossy(after).into(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rnalib::{ImportConfig, PreprocessorConfig};
+
+	use super::*;
+
+	/// A helper function that constructs the settings of
+	/// a given text, pre-processed in a given way.
+	fn settings(text: &str, preprocessing: PreprocessorConfig) -> ImportSettings {
+		ImportSettings {
+			config: ImportConfig {
+				preprocessing,
+				..Default::default()
+			},
+			input_rna: text.to_owned(),
+			..Default::default()
+		}
+	}
+
+	/// A helper function that returns the spans of a preview,
+	/// with their fates written as single letters.
+	fn spans(preview: &Preview) -> Vec<(char, &str)> {
+		let spans = preview.spans.iter().map(|(fate, text)| {
+			let fate = match fate {
+				ByteFate::Kept => 'K',
+				ByteFate::Invalid => 'I',
+				ByteFate::Header => 'H',
+				ByteFate::Separator => 'S',
+				ByteFate::Deleted => 'D',
+			};
+			(fate, text.as_str())
+		});
+		spans.collect()
+	}
+
+	#[test]
+	fn previews_mark_what_is_removed() {
+		let text = "title\n>seq\nAC, GX\nŻU,XC\n";
+		let config = PreprocessorConfig {
+			separator: ", ".to_owned(),
+			delete_wrong_chars: false,
+			delete_header: true,
+			header_len: 1,
+		};
+		let preview = Preview::load(&settings(text, config)).unwrap();
+		let expected = [
+			('H', "title\n"),
+			('K', ">seq\nAC"),
+			('S', ", "),
+			('K', "G"),
+			('I', "X"),
+			('K', "\n"),
+			('I', "Ż"),
+			('K', "U"),
+			('I', ",X"),
+			('K', "C\n"),
+		];
+		assert_eq!(spans(&preview), expected);
+		assert_eq!(preview.invalid, [('X', 2), (',', 1), ('Ż', 1)]);
+		assert!(!preview.truncated);
+
+		// Invalid characters are removed before separators are looked for
+		let config = PreprocessorConfig {
+			separator: ", ".to_owned(),
+			delete_wrong_chars: true,
+			..Default::default()
+		};
+		let preview = Preview::load(&settings(text, config)).unwrap();
+		let expected = [
+			('K', "t"),
+			('D', "i"),
+			('K', "t"),
+			('D', "le"),
+			('K', "\n>seq\nAC"),
+			('D', ","),
+			('K', " G"),
+			('D', "X"),
+			('K', "\n"),
+			('D', "Ż"),
+			('K', "U"),
+			('D', ",X"),
+			('K', "C\n"),
+		];
+		assert_eq!(spans(&preview), expected);
+		let invalid = [(',', 2), ('X', 2), ('e', 1), ('i', 1), ('l', 1), ('Ż', 1)];
+		assert_eq!(preview.invalid, invalid);
+	}
+
+	#[test]
+	fn previews_end_between_characters() {
+		let text = "A".repeat(Preview::LEN as usize - 1) + "ŻGG";
+		let preview = Preview::load(&settings(&text, Default::default())).unwrap();
+		assert!(preview.truncated);
+		let len: usize = preview.spans.iter().map(|x| x.1.len()).sum();
+		assert_eq!(len, Preview::LEN as usize - 1);
+		assert!(preview.invalid.is_empty());
+	}
+
+	#[test]
+	fn excerpts_surround_the_character() {
+		let text = "ACGU\nAGŻUC\nGG";
+		let offset = text.find('Ż').unwrap() as u64;
+		let excerpt = Excerpt::load(&settings(text, Default::default()), offset).unwrap();
+		assert_eq!(excerpt.before, "AG");
+		assert_eq!(excerpt.character, "Ż");
+		assert_eq!(excerpt.after, "UC");
+
+		let text = "G".repeat(100) + "X" + &"C".repeat(100);
+		let excerpt = Excerpt::load(&settings(&text, Default::default()), 100).unwrap();
+		assert_eq!(excerpt.before, "G".repeat(Excerpt::RADIUS as usize));
+		assert_eq!(excerpt.character, "X");
+		assert_eq!(excerpt.after, "C".repeat(Excerpt::RADIUS as usize + 3));
+	}
+}
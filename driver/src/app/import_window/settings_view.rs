@@ -1,9 +1,23 @@
 //! The module that implements [`SettingsView`]
 
-use egui::*;
+use std::thread::{spawn, JoinHandle};
+
+use egui::{
+	text::{CCursor, LayoutJob},
+	text_edit::{CCursorRange, TextEditState},
+	*,
+};
 use native_dialog::FileDialog;
+use rnalib::{
+	ByteFate, FlatFileFormat, GeneticCode, Nucleotide, OrfDefinition, QualityEncoding,
+	QualityFilter, SourceFormat, StartCodons,
+};
 
-use super::ImportSettings;
+use super::{
+	detector::{Detection, Sample},
+	preprocessor::Preview,
+	ImportSettings,
+};
 use crate::app::extras::FastTextEdit;
 
 /// A window view that allows the user to configure
@@ -11,9 +25,61 @@ use crate::app::extras::FastTextEdit;
 #[derive(Default)]
 pub struct SettingsView {
 	pub settings: ImportSettings,
+	/// Text of the custom start codon list
+	custom_starts: String,
+	/// Index of the input character to be selected
+	jump: Option<usize>,
+	/// The settings the source was last inspected with
+	inspected: Option<ImportSettings>,
+	/// The inspection of the source running in the background
+	inspection: Option<JoinHandle<Inspection>>,
+	/// Whether the settings detected in the source are to be
+	/// filled in once it is inspected again, which happens when
+	/// the user picks a new source or asks for it
+	redetect: bool,
+	/// The settings filled in by the last detection, see
+	/// [`ImportSettings::fill_detected`]
+	detected: Option<ImportSettings>,
+	/// Format detected in the source, or the reason
+	/// the source could not be read
+	detection: Option<Result<Detection, String>>,
+	/// The first few records of the source
+	sample: Option<Result<Sample, String>>,
+	/// The beginning of the source as it reaches the loader,
+	/// only for sources that are pre-processed
+	preview: Option<Result<Preview, String>>,
 }
 
 impl SettingsView {
+	/// Creates a new instance of [`SettingsView`],
+	/// with the given settings filled in.
+	///
+	/// The settings are kept as they are, even if they
+	/// do not fit the format detected in the source.
+	pub fn new(settings: ImportSettings) -> Self {
+		let custom_starts = match &settings.config.orf_options.starts {
+			StartCodons::Custom(codons) => codons
+				.iter()
+				.map(|x| x.iter().map(|x| x.to_string()).collect::<String>())
+				.collect::<Vec<_>>()
+				.join(", "),
+			_ => String::new(),
+		};
+		Self {
+			settings,
+			custom_starts,
+			..Self::default()
+		}
+	}
+
+	/// Selects the character at a given byte offset of
+	/// the manual input, once the view is drawn.
+	pub fn jump_to(&mut self, offset: u64) {
+		let input = &self.settings.input_rna;
+		let offset = (offset as usize).min(input.len());
+		self.jump = input.get(..offset).map(|x| x.chars().count());
+	}
+
 	/// Draws self to the ui.
 	pub fn show(&mut self, ui: &mut Ui) -> bool {
 		self.show_source_select(ui);
@@ -21,7 +87,12 @@ impl SettingsView {
 			true => self.show_file_select(ui),
 			false => self.show_rna_input(ui),
 		};
+		self.inspect(ui.ctx());
+		self.show_detection(ui);
 		self.show_preprocessing_opts(ui);
+		self.show_fastq_opts(ui);
+		self.show_translation_opts(ui);
+		self.show_orf_opts(ui);
 		self.show_import(ui)
 	}
 
@@ -30,8 +101,22 @@ impl SettingsView {
 	fn show_source_select(&mut self, ui: &mut Ui) {
 		ui.label(RichText::new("Ustawienia źródła:").strong());
 		ui.horizontal(|ui| {
-			ui.radio_value(&mut self.settings.from_file, true, "Plik");
-			ui.radio_value(&mut self.settings.from_file, false, "Z tekstu");
+			let from_file = &mut self.settings.from_file;
+			let file = ui.radio_value(from_file, true, "Plik");
+			let text = ui.radio_value(from_file, false, "Z tekstu");
+			self.redetect |= file.changed() || text.changed();
+		});
+		ui.horizontal(|ui| {
+			let format = &mut self.settings.config.format;
+			ui.label("Format:");
+			ui.radio_value(format, SourceFormat::Sequence, "Sekwencja / FASTA");
+			ui.radio_value(
+				format,
+				SourceFormat::FlatFile(FlatFileFormat::GenBank),
+				"GenBank",
+			);
+			ui.radio_value(format, SourceFormat::FlatFile(FlatFileFormat::Embl), "EMBL");
+			ui.radio_value(format, SourceFormat::Fastq, "FASTQ");
 		});
 	}
 
@@ -41,11 +126,16 @@ impl SettingsView {
 		ui.label("Ścieżka pliku: ");
 		ui.horizontal(|ui| {
 			ui.centered_and_justified(|ui| {
-				FastTextEdit::singleline(ui, &mut self.settings.path);
+				let path = FastTextEdit::singleline(ui, &mut self.settings.path);
+				self.redetect |= path.lost_focus();
 				if ui.button("Wybierz plik...").clicked() {
 					let path = FileDialog::new()
 						.set_location("~/Desktop")
 						.add_filter("Text file", &["txt"])
+						.add_filter("GenBank", &["gb", "gbk", "genbank"])
+						.add_filter("EMBL", &["embl", "dat"])
+						.add_filter("FASTQ", &["fq", "fastq"])
+						.add_filter("Compressed", &["gz", "bgz", "zst"])
 						.add_filter("all files", &["*"])
 						.show_open_single_file()
 						.unwrap();
@@ -53,6 +143,7 @@ impl SettingsView {
 						Some(path) => path.to_str().unwrap().to_owned(),
 						None => return,
 					};
+					self.redetect = true;
 				};
 			});
 		});
@@ -67,36 +158,413 @@ impl SettingsView {
 				ScrollArea::vertical()
 					.min_scrolled_height(72.0)
 					.show(ui, |ui| {
-						ui.text_edit_multiline(&mut self.settings.input_rna);
+						let id = Id::new("IMPORT_RNA_INPUT");
+						if let Some(index) = self.jump.take() {
+							let mut state = TextEditState::load(ui.ctx(), id).unwrap_or_default();
+							let (from, to) = (CCursor::new(index), CCursor::new(index + 1));
+							state.set_ccursor_range(Some(CCursorRange::two(from, to)));
+							state.store(ui.ctx(), id);
+							ui.memory().request_focus(id);
+						}
+						let input = TextEdit::multiline(&mut self.settings.input_rna).id(id);
+						let changed = ui.add(input).changed();
+						let paste = |x: &Event| matches!(x, Event::Paste(_));
+						let pasted = ui.input().events.iter().any(paste);
+						self.redetect |= changed && pasted;
 					});
 			});
 		});
 	}
 
+	/// A helper function that inspects the source in the background
+	/// once the way it is parsed changes, detecting its format once
+	/// it changes, and parsing a sample of it, see [`Inspection`].
+	///
+	/// The settings detected are only filled in when the user picks
+	/// a new source or asks for it, see [`SettingsView::redetect`].
+	fn inspect(&mut self, ctx: &Context) {
+		if let Some(inspection) = &self.inspection {
+			if !inspection.is_finished() {
+				return;
+			}
+			if let Some(Ok(inspection)) = self.inspection.take().map(JoinHandle::join) {
+				self.finish_inspection(inspection);
+			}
+		}
+
+		let inspected = self.inspected.as_ref();
+		if !self.redetect && inspected.is_some_and(|x| x.same_parsing(&self.settings)) {
+			return;
+		}
+		let detect = self.redetect || !inspected.is_some_and(|x| x.same_source(&self.settings));
+		let redetect = std::mem::take(&mut self.redetect);
+		let previous = redetect.then(|| self.detected.clone().unwrap_or_default());
+		let settings = self.settings.clone();
+		let ctx = ctx.clone();
+		self.inspected = Some(settings.clone());
+		self.inspection = Some(spawn(move || {
+			let inspection = Inspection::run(settings, detect, previous);
+			ctx.request_repaint();
+			inspection
+		}));
+	}
+
+	/// A helper function that takes the results of a finished
+	/// inspection of the source, filling in the settings detected
+	/// in it if they were asked for.
+	///
+	/// Settings edited in the meantime are kept as they are.
+	fn finish_inspection(&mut self, inspection: Inspection) {
+		if let Some(Ok(detection)) = &inspection.detection {
+			let detected = detection.get_settings();
+			if inspection.filled {
+				let previous = self.detected.clone().unwrap_or_default();
+				self.settings.fill_detected(&previous, &detected);
+			}
+			// The settings of the first detection are the ones the
+			// user has not edited, even if they were not filled in
+			if inspection.filled || self.detected.is_none() {
+				self.detected = Some(detected);
+			}
+		}
+		if inspection.detection.is_some() {
+			self.detection = inspection.detection;
+		}
+		self.sample = Some(inspection.sample);
+		self.preview = inspection.preview;
+		self.inspected = Some(inspection.settings);
+	}
+
+	/// A helper function that previews the pre-processing
+	/// of the source, if it is pre-processed at all.
+	fn load_preview(settings: &ImportSettings) -> Option<Result<Preview, String>> {
+		(settings.config.format == SourceFormat::Sequence)
+			.then(|| Preview::load(settings).map_err(|x| x.to_string()))
+	}
+
+	/// A helper function that displays the detected format
+	/// and the first few records of the source.
+	fn show_detection(&mut self, ui: &mut Ui) {
+		ui.separator();
+		ui.label(RichText::new("Podgląd danych:").strong());
+		match &self.detection {
+			Some(Ok(detection)) => {
+				ui.horizontal(|ui| {
+					ui.label(format!("Wykryty format: {}", detection.describe()));
+					let button = ui.button("Wykryj ponownie");
+					let button = button.on_hover_text("Uzupełnia ustawienia niezmienione ręcznie");
+					self.redetect |= button.clicked();
+				});
+			}
+			Some(Err(err)) => {
+				let text = format!("Nie można odczytać danych: {err}");
+				ui.colored_label(ui.visuals().error_fg_color, text);
+				return;
+			}
+			None => return,
+		}
+		let sample = match &self.sample {
+			Some(Ok(sample)) => sample,
+			Some(Err(err)) => {
+				let text = format!("Nie można odczytać danych: {err}");
+				ui.colored_label(ui.visuals().error_fg_color, text);
+				return;
+			}
+			None => return,
+		};
+
+		if sample.records.is_empty() && sample.error.is_none() {
+			ui.label(match self.settings.config.format {
+				SourceFormat::Fastq => "Brak odczytów spełniających kryteria filtrowania.",
+				_ => "Brak rekordów.",
+			});
+		}
+		Grid::new("IMPORT_SAMPLE").striped(true).show(ui, |ui| {
+			for record in &sample.records {
+				let id = match record.header.get_id() {
+					"" => "(bez nagłówka)",
+					x => x,
+				};
+				let description = record.header.get_description();
+				let label = ui.label(id);
+				if !description.is_empty() {
+					label.on_hover_text(description);
+				}
+				ui.label(match record.truncated {
+					true => format!("≥ {} nt", record.length),
+					false => format!("{} nt", record.length),
+				});
+				let mut preview = record.preview.clone();
+				if record.length > preview.len() {
+					preview.push('…');
+				}
+				ui.label(RichText::new(preview).monospace());
+				ui.end_row();
+			}
+		});
+		if let Some(err) = &sample.error {
+			ui.colored_label(ui.visuals().error_fg_color, err.to_string());
+		}
+	}
+
 	/// A helper function that displays the pre-processor
 	/// settings section.
+	///
+	/// Annotated flat files are not pre-processed.
 	fn show_preprocessing_opts(&mut self, ui: &mut Ui) {
 		ui.separator();
 		ui.label(RichText::new("Ustawienia preprocesora:").strong());
+		let enabled = self.settings.config.format == SourceFormat::Sequence;
+		let preprocessing = &mut self.settings.config.preprocessing;
+		ui.add_enabled_ui(enabled, |ui| {
+			ui.horizontal(|ui| {
+				ui.label("Separator:");
+				ui.centered_and_justified(|ui| {
+					ui.text_edit_singleline(&mut preprocessing.separator);
+				});
+			});
+			ui.checkbox(
+				&mut preprocessing.delete_wrong_chars,
+				"Usuń niepoprawne znaki",
+			);
+			ui.checkbox(&mut preprocessing.delete_header, "Usuń nagłówek");
+			ui.horizontal(|ui| {
+				ui.add_enabled_ui(preprocessing.delete_header, |ui| {
+					ui.label("Ilość linii do usunięcia:");
+					ui.add(DragValue::new(&mut preprocessing.header_len));
+				});
+			});
+		});
+		self.show_preview(ui);
+	}
+
+	/// A helper function that displays the beginning of the
+	/// source as it reaches the loader, marking the characters
+	/// removed by the pre-processor, along with the counts of
+	/// invalid characters.
+	fn show_preview(&mut self, ui: &mut Ui) {
+		let preview = match &self.preview {
+			Some(Ok(preview)) => preview,
+			Some(Err(err)) => {
+				let text = format!("Nie można odczytać danych: {err}");
+				ui.colored_label(ui.visuals().error_fg_color, text);
+				return;
+			}
+			None => return,
+		};
+
+		let visuals = ui.visuals();
+		let format = |fate| {
+			let mut format = TextFormat::simple(FontId::monospace(12.0), visuals.text_color());
+			match fate {
+				ByteFate::Kept => {}
+				ByteFate::Invalid => {
+					format.color = visuals.error_fg_color;
+					format.underline = Stroke::new(1.0, visuals.error_fg_color);
+				}
+				ByteFate::Header => {
+					format.color = visuals.weak_text_color();
+					format.strikethrough = Stroke::new(1.0, visuals.weak_text_color());
+				}
+				ByteFate::Separator => format.background = visuals.selection.bg_fill,
+				ByteFate::Deleted => {
+					format.background = visuals.warn_fg_color.linear_multiply(0.4);
+				}
+			}
+			format
+		};
+
+		let mut legend = LayoutJob::default();
+		let entries = [
+			(ByteFate::Header, "nagłówek"),
+			(ByteFate::Separator, "separator"),
+			(ByteFate::Deleted, "usunięte"),
+			(ByteFate::Invalid, "niepoprawne"),
+		];
+		for (fate, name) in entries {
+			legend.append(name, 8.0, format(fate));
+		}
+		let mut job = LayoutJob::default();
+		for (fate, text) in &preview.spans {
+			job.append(text, 0.0, format(*fate));
+		}
+		if preview.truncated {
+			job.append("…", 0.0, format(ByteFate::Kept));
+		}
+
 		ui.horizontal(|ui| {
-			ui.label("Separator:");
-			ui.centered_and_justified(|ui| {
-				ui.text_edit_singleline(&mut self.settings.separator);
+			ui.label("Podgląd po przetworzeniu:");
+			ui.label(legend);
+		});
+		ScrollArea::vertical()
+			.id_source("IMPORT_PREVIEW")
+			.max_height(160.0)
+			.show(ui, |ui| {
+				ui.label(job);
+			});
+
+		if preview.invalid.is_empty() {
+			ui.label("Brak niepoprawnych znaków.");
+			return;
+		}
+		let counts = preview
+			.invalid
+			.iter()
+			.map(|(character, count)| format!("'{}' × {count}", character.escape_debug()))
+			.collect::<Vec<_>>()
+			.join(", ");
+		let text = match self.settings.config.preprocessing.delete_wrong_chars {
+			true => format!("Niepoprawne znaki (zostaną usunięte): {counts}"),
+			false => format!("Niepoprawne znaki (przerwą import): {counts}"),
+		};
+		let color = match self.settings.config.preprocessing.delete_wrong_chars {
+			true => ui.visuals().warn_fg_color,
+			false => ui.visuals().error_fg_color,
+		};
+		ui.colored_label(color, text);
+	}
+
+	/// A helper function that displays the read filtering
+	/// settings section, shown for FASTQ files only.
+	fn show_fastq_opts(&mut self, ui: &mut Ui) {
+		if self.settings.config.format != SourceFormat::Fastq {
+			return;
+		}
+		let filter = &mut self.settings.config.quality_filter;
+		let encoding = &mut self.settings.config.quality_encoding;
+		ui.separator();
+		ui.label(RichText::new("Filtrowanie odczytów:").strong());
+		ui.horizontal(|ui| {
+			ui.label("Kodowanie jakości:");
+			ui.radio_value(encoding, QualityEncoding::Phred33, "Phred+33");
+			ui.radio_value(encoding, QualityEncoding::Phred64, "Phred+64");
+		});
+
+		let mut trimmed = filter.window > 0;
+		ui.horizontal(|ui| {
+			ui.checkbox(&mut trimmed, "Przycinaj oknem przesuwnym:");
+			let mut window = match filter.window {
+				0 => QualityFilter::default().window,
+				x => x,
+			};
+			let value = DragValue::new(&mut window)
+				.clamp_range(1..=100)
+				.suffix(" nt");
+			ui.add_enabled(trimmed, value);
+			filter.window = if trimmed { window } else { 0 };
+		});
+		ui.horizontal(|ui| {
+			ui.add_enabled_ui(trimmed, |ui| {
+				ui.label("Minimalna średnia jakość okna:");
+				ui.add(DragValue::new(&mut filter.min_quality).clamp_range(0..=93));
 			});
 		});
+		ui.horizontal(|ui| {
+			ui.label("Minimalna długość odczytu:");
+			ui.add(DragValue::new(&mut filter.min_length).suffix(" nt"));
+		});
+	}
+
+	/// A helper function that displays the translation
+	/// settings section.
+	fn show_translation_opts(&mut self, ui: &mut Ui) {
+		let options = &mut self.settings.config.orf_options;
+		ui.separator();
+		ui.label(RichText::new("Ustawienia translacji:").strong());
+		ui.horizontal(|ui| {
+			ui.label("Kod genetyczny:");
+			ComboBox::from_id_source("GENETIC_CODE_SELECT")
+				.selected_text(options.code.to_string())
+				.show_ui(ui, |ui| {
+					for code in GeneticCode::ALL {
+						ui.selectable_value(&mut options.code, *code, code.to_string());
+					}
+				});
+		});
 		ui.checkbox(
-			&mut self.settings.delete_wrong_chars,
-			"Usuń niepoprawne znaki",
+			&mut options.both_strands,
+			"Szukaj również na nici komplementarnej",
 		);
-		ui.checkbox(&mut self.settings.delete_header, "Usuń nagłówek");
 		ui.horizontal(|ui| {
-			ui.add_enabled_ui(self.settings.delete_header, |ui| {
-				ui.label("Ilość linii do usunięcia:");
-				ui.add(DragValue::new(&mut self.settings.header_len));
+			ui.label("Liczba wątków:");
+			ui.add(DragValue::new(&mut self.settings.config.threads).clamp_range(0..=256))
+				.on_hover_text("0 – po jednym wątku na każdy rdzeń procesora");
+		});
+	}
+
+	/// A helper function that displays the open reading
+	/// frame settings section.
+	fn show_orf_opts(&mut self, ui: &mut Ui) {
+		let options = &mut self.settings.config.orf_options;
+		ui.separator();
+		ui.label(RichText::new("Definicja białka:").strong());
+		ui.horizontal(|ui| {
+			ui.radio_value(
+				&mut options.definition,
+				OrfDefinition::StartToStop,
+				"Od startu do stopu",
+			);
+			ui.radio_value(
+				&mut options.definition,
+				OrfDefinition::StopToStop,
+				"Od stopu do stopu",
+			);
+		});
+
+		ui.add_enabled_ui(options.definition == OrfDefinition::StartToStop, |ui| {
+			ui.horizontal(|ui| {
+				ui.label("Kodony start:");
+				ui.radio_value(&mut options.starts, StartCodons::Aug, "AUG");
+				ui.radio_value(
+					&mut options.starts,
+					StartCodons::Table,
+					"Z kodu genetycznego",
+				);
+				let custom = matches!(options.starts, StartCodons::Custom(_));
+				if ui.radio(custom, "Własne").clicked() && !custom {
+					options.starts = StartCodons::Custom(Self::parse_codons(&self.custom_starts));
+				}
 			});
+			if let StartCodons::Custom(codons) = &mut options.starts {
+				ui.horizontal(|ui| {
+					ui.label("Lista kodonów:");
+					if ui.text_edit_singleline(&mut self.custom_starts).changed() {
+						*codons = Self::parse_codons(&self.custom_starts);
+					}
+				});
+			}
+			ui.checkbox(&mut options.nested, "Uwzględniaj zagnieżdżone starty");
+		});
+
+		ui.checkbox(&mut options.partial, "Zachowuj białka bez kodonu stop");
+		ui.horizontal(|ui| {
+			ui.label("Minimalna długość:");
+			ui.add(DragValue::new(&mut options.min_length).suffix(" aa"));
+		});
+		ui.horizontal(|ui| {
+			let mut limited = options.max_length.is_some();
+			ui.checkbox(&mut limited, "Maksymalna długość:");
+			let mut max_length = options.max_length.unwrap_or(1000);
+			ui.add_enabled(limited, DragValue::new(&mut max_length).suffix(" aa"));
+			options.max_length = limited.then_some(max_length);
 		});
 	}
 
+	/// A helper function that parses a list of codons,
+	/// e.g. "AUG, GUG, UUG", skipping any invalid entries.
+	fn parse_codons(text: &str) -> Vec<[Nucleotide; 3]> {
+		text.split(|x: char| !x.is_alphabetic())
+			.filter_map(|x| match x.chars().collect::<Vec<_>>()[..] {
+				[a, b, c] => Some([
+					Nucleotide::parse(a)?,
+					Nucleotide::parse(b)?,
+					Nucleotide::parse(c)?,
+				]),
+				_ => None,
+			})
+			.collect()
+	}
+
 	/// A helper function that displays the import
 	/// button and its separator.
 	fn show_import(&mut self, ui: &mut Ui) -> bool {
@@ -104,3 +572,42 @@ impl SettingsView {
 		ui.button("Importuj").clicked()
 	}
 }
+
+/// The results of inspecting the source, which is done
+/// on a separate thread, as the source may be large or
+/// slow to read.
+struct Inspection {
+	/// The settings the source was parsed with
+	settings: ImportSettings,
+	/// Whether the settings detected in the source were
+	/// filled in before parsing it
+	filled: bool,
+	/// The format detected in the source, if it was detected
+	detection: Option<Result<Detection, String>>,
+	sample: Result<Sample, String>,
+	preview: Option<Result<Preview, String>>,
+}
+
+impl Inspection {
+	/// Inspects the source described by the settings, detecting
+	/// its format first if asked to.
+	///
+	/// If the settings filled in by the previous detection are given,
+	/// see [`ImportSettings::fill_detected`], the settings detected
+	/// are filled in before the source is parsed.
+	fn run(mut settings: ImportSettings, detect: bool, previous: Option<ImportSettings>) -> Self {
+		let detection = detect.then(|| Detection::run(&settings).map_err(|x| x.to_string()));
+		let mut filled = false;
+		if let (Some(previous), Some(Ok(detection))) = (previous, &detection) {
+			settings.fill_detected(&previous, &detection.get_settings());
+			filled = true;
+		}
+		Self {
+			sample: Sample::load(&settings).map_err(|x| x.to_string()),
+			preview: SettingsView::load_preview(&settings),
+			detection,
+			filled,
+			settings,
+		}
+	}
+}
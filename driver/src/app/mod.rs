@@ -1,10 +1,15 @@
 //! The graphical user interface root module
 
-use std::rc::Rc;
+use std::{
+	fs::File,
+	io::{self, BufWriter},
+	sync::Arc,
+};
 
 use eframe::{epaint::Shadow, Frame};
 use egui::*;
-use rnalib::ProteinMap;
+use native_dialog::FileDialog;
+use rnalib::{FastaWriter, IupacNucleotide, NucleotideString, ProteinMap};
 
 mod import_window;
 use import_window::ImportWindow;
@@ -97,10 +102,7 @@ impl eframe::App for App {
 		}
 
 		if let Some(map) = self.import_window.show(ctx) {
-			match map {
-				Ok(map) => self.set_map(map),
-				Err(err) => self.error = Some(err),
-			}
+			self.set_map(map);
 		}
 
 		TopBottomPanel::top("TOP").show(ctx, |ui| {
@@ -110,20 +112,26 @@ impl eframe::App for App {
 				ui.label("Ciąg RNA:");
 				FastTextEdit::singleline(ui, &mut self.rna);
 				if ui.button("Wczytaj").clicked() {
-					match ProteinMap::parse(self.rna.to_string()) {
-						Ok(map) => {
-							self.set_map(map);
-						}
-						Err(err) => self.error = Some(err),
+					let sequence = NucleotideString::parse(&self.rna);
+					match sequence.and_then(ProteinMap::from_sequence) {
+						Ok(map) => self.set_map(map),
+						Err(err) => self.error = Some(err.to_string()),
 					};
 				};
 				if ui.button("Wytnij niepoprawne znaki").clicked() {
 					self.rna
-						.retain(|x| "AGCUT ".contains(x.to_ascii_uppercase()));
+						.retain(|x| x == ' ' || IupacNucleotide::parse(x).is_some());
 				}
 				if ui.button("Zaawansowany import...").clicked() {
 					self.import_window.visible = true;
 				}
+				ui.add_enabled_ui(!self.proteins.is_empty(), |ui| {
+					if ui.button("Eksportuj białka...").clicked() {
+						if let Err(err) = self.export_proteins() {
+							self.error = Some(format!("Nie udało się zapisać pliku: {err}."));
+						}
+					}
+				});
 			});
 			ui.add_space(2.0);
 		});
@@ -134,8 +142,8 @@ impl eframe::App for App {
 			.show(ctx, |ui| {
 				ui.set_enabled(error_disable);
 				if let Some(selection) = self.protein_selector.show(ui, &self.proteins) {
-					self.protein_viewer.protein = Some(Rc::clone(&selection));
-					self.property_viewer.set(selection);
+					self.protein_viewer.protein = Some(Arc::clone(&selection));
+					self.property_viewer.set(selection, &self.proteins);
 				}
 			});
 
@@ -171,4 +179,18 @@ impl App {
 		self.protein_selector.clear_cache();
 		self.proteins = map;
 	}
+
+	/// Asks the user for a path, then writes all the
+	/// proteins to it in the FASTA format.
+	fn export_proteins(&self) -> io::Result<()> {
+		let path = FileDialog::new()
+			.set_location("~/Desktop")
+			.set_filename("bialka.fasta")
+			.add_filter("FASTA", &["fasta", "fa", "faa"])
+			.show_save_single_file();
+		let Ok(Some(path)) = path else { return Ok(()) };
+
+		let mut writer = FastaWriter::new(BufWriter::new(File::create(path)?));
+		writer.write_proteins(&self.proteins)
+	}
 }
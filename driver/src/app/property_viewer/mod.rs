@@ -1,9 +1,9 @@
 //! The module that implements [`PropertyViewer`]
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use egui::*;
-use rnalib::Protein;
+use rnalib::{Feature, IupacNucleotide, Location, Protein, ProteinMap};
 
 mod property;
 use property::*;
@@ -15,10 +15,23 @@ mod math;
 
 use super::extras::Extras;
 
+/// Describes where an occurrence of a protein
+/// comes from.
+#[derive(Default)]
+struct Source {
+	/// Identifier of the source record
+	record: String,
+	/// Description of the annotated feature the occurrence
+	/// was translated from, if it was not predicted
+	feature: Option<String>,
+}
+
 /// A ui widget that displays a list of protein
 /// properties.
 pub struct PropertyViewer {
-	protein: Option<Rc<Protein>>,
+	protein: Option<Arc<Protein>>,
+	/// Sources of the protein, one for every occurrence
+	sources: Vec<Source>,
 	hydro: CachedPainter<Hydro>,
 	pi: CachedPainter<Pi>,
 	extinction: CachedPainter<Extinction>,
@@ -27,8 +40,21 @@ pub struct PropertyViewer {
 }
 
 impl PropertyViewer {
-	/// Sets the protein to view the properties of.
-	pub fn set(&mut self, protein: Rc<Protein>) {
+	/// Sets the protein to view the properties of, looking
+	/// up its source records and annotated features in a
+	/// given [`ProteinMap`].
+	pub fn set(&mut self, protein: Arc<Protein>, map: &ProteinMap) {
+		self.sources = protein
+			.get_locations()
+			.iter()
+			.map(|location| Source {
+				record: map
+					.get_record(location)
+					.map_or("", |x| x.get_id())
+					.to_owned(),
+				feature: map.get_feature(location).map(Self::describe_feature),
+			})
+			.collect();
 		self.hydro.set(&protein);
 		self.charge.set(&protein);
 		self.extinction.set(&protein);
@@ -39,13 +65,15 @@ impl PropertyViewer {
 
 	/// Draws self to the ui.
 	pub fn show(&mut self, ui: &mut Ui) {
-		let Some(_) = &self.protein else {
+		let Some(protein) = &self.protein else {
 			ui.centered_and_justified(|ui| ui.label("Brak danych"));
 			return;
 		};
 
 		Extras::title_bar(ui, "Właściwości białka");
 
+		Self::show_locations(ui, protein.get_locations(), &self.sources);
+
 		Grid::new("PROTEIN_PROPERTY_GRID")
 			.min_row_height(ui.available_height() / 5.0_f32 - 5.0)
 			.num_columns(3)
@@ -58,12 +86,75 @@ impl PropertyViewer {
 				self.mass.draw(ui);
 			});
 	}
+
+	/// A helper function that displays where the protein
+	/// was found in the source sequence.
+	fn show_locations(ui: &mut Ui, locations: &[Location], sources: &[Source]) {
+		let default = Source::default();
+		let source = |index: usize| sources.get(index).unwrap_or(&default);
+		match locations {
+			[] => (),
+			[location] => Self::show_location(ui, location, source(0)),
+			locations => {
+				CollapsingHeader::new(format!("Wystąpienia: {}", locations.len()))
+					.id_source("PROTEIN_LOCATIONS")
+					.show(ui, |ui| {
+						ScrollArea::vertical().max_height(100.0).show(ui, |ui| {
+							for (index, location) in locations.iter().enumerate() {
+								Self::show_location(ui, location, source(index));
+							}
+						});
+					});
+			}
+		}
+	}
+
+	/// A helper function that displays a single occurrence
+	/// of the protein, coming from a given source.
+	fn show_location(ui: &mut Ui, location: &Location, source: &Source) {
+		let codon = |x: [IupacNucleotide; 3]| x.iter().map(|x| x.to_string()).collect::<String>();
+		let start_codon = location.get_start_codon().map_or("—".into(), codon);
+		let stop_codon = location.get_stop_codon().map_or("—".into(), codon);
+
+		ui.horizontal_wrapped(|ui| {
+			if !source.record.is_empty() {
+				ui.label(format!("Rekord: {}", source.record));
+				ui.separator();
+			}
+			match &source.feature {
+				Some(feature) => ui.label(format!("Adnotacja: {feature}")),
+				None => ui.label("Przewidziane (ORF)"),
+			};
+			ui.separator();
+			ui.label(format!("Ramka odczytu: {}", location.get_frame()));
+			ui.separator();
+			ui.label(format!("Pozycja: {location}"));
+			ui.separator();
+			ui.label(format!("Kodon start: {start_codon}"));
+			ui.separator();
+			ui.label(format!("Kodon stop: {stop_codon}"));
+		});
+	}
+
+	/// A helper function that describes an annotated feature
+	/// by its key, label and location, e.g. `CDS abc join(1..10,20..30)`.
+	fn describe_feature(feature: &Feature) -> String {
+		let mut result = feature.get_kind().to_owned();
+		if let Some(label) = feature.get_label() {
+			result += &format!(" {label}");
+		}
+		if let Some(location) = feature.get_location() {
+			result += &format!(" {location}");
+		}
+		result
+	}
 }
 
 impl Default for PropertyViewer {
 	fn default() -> Self {
 		Self {
 			protein: Default::default(),
+			sources: Vec::new(),
 			hydro: CachedPainter::new(&Hydro),
 			pi: CachedPainter::new(&Pi),
 			extinction: CachedPainter::new(&Extinction),
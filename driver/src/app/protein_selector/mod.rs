@@ -1,9 +1,9 @@
 //! The module that implements [`ProteinSelector`]
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use egui::*;
-use rnalib::{Protein, ProteinMap};
+use rnalib::{Protein, ProteinId, ProteinMap};
 
 use super::extras::Extras;
 
@@ -15,10 +15,10 @@ pub struct ProteinSelector {
 	page: usize,
 	/// Previously rendered page
 	last_render_page: usize,
-	/// A cache of paginated items
-	paginated: Vec<String>,
-	/// Index of the selected protein (page, entry)
-	selected_index: Option<(usize, usize)>,
+	/// A cache of paginated items (id, label)
+	paginated: Vec<(ProteinId, String)>,
+	/// The selected protein
+	selected: Option<ProteinId>,
 }
 
 impl ProteinSelector {
@@ -30,7 +30,7 @@ impl ProteinSelector {
 	const DARK_BUTTON: Color32 = Color32::from_gray(46);
 
 	/// Draws self to the ui.
-	pub fn show(&mut self, ui: &mut Ui, proteins: &ProteinMap) -> Option<Rc<Protein>> {
+	pub fn show(&mut self, ui: &mut Ui, proteins: &ProteinMap) -> Option<Arc<Protein>> {
 		let mut result = None;
 		let min_y = ui.cursor().min.y;
 		let max_y = ui.available_height();
@@ -46,13 +46,13 @@ impl ProteinSelector {
 	pub fn clear_cache(&mut self) {
 		self.page = 0;
 		self.paginated.clear();
-		self.selected_index = None;
+		self.selected = None;
 	}
 
 	/// A helper function that displays an appropriate message
 	/// if no proteins have been loaded.
 	fn show_empty_message(&self, ui: &mut Ui, proteins: &ProteinMap) {
-		if proteins.keys().len() == 0 {
+		if proteins.is_empty() {
 			ui.centered_and_justified(|ui| ui.label("Brak białek do wyświetlenia"));
 		};
 	}
@@ -63,20 +63,27 @@ impl ProteinSelector {
 		self.paginated.clear();
 		self.last_render_page = self.page;
 
-		let mut iter = proteins.keys();
-		for _ in 0..self.page * Self::PAGINATION {
-			iter.next();
-		}
-
-		for protein in iter.take(Self::PAGINATION) {
-			self.paginated.push(protein.0.clone());
+		let start = self.page * Self::PAGINATION;
+		for (id, protein) in proteins.range(start..start + Self::PAGINATION) {
+			let label = match protein.get_locations() {
+				[] => protein.to_string(),
+				[location] => format!("{}  {}", location.get_frame(), protein),
+				locations => format!("×{}  {}", locations.len(), protein),
+			};
+			// Proteins translated from annotated coding
+			// sequences are told apart from predicted ones
+			let label = match protein.get_locations().iter().any(|x| x.is_annotated()) {
+				true => format!("CDS {label}"),
+				false => label,
+			};
+			self.paginated.push((id, label));
 		}
 	}
 
 	/// A helper function that shows the controls necessary
 	/// to navigate between pages.
 	fn show_pagination_header(&mut self, ui: &mut Ui, proteins: &ProteinMap) {
-		let pages = proteins.keys().len() / Self::PAGINATION;
+		let pages = proteins.len() / Self::PAGINATION;
 		if pages > 0 {
 			Extras::title_bar(ui, "Wybór białka");
 			ui.horizontal(|ui| {
@@ -123,8 +130,8 @@ impl ProteinSelector {
 		proteins: &ProteinMap,
 		min_y: f32,
 		max_y: f32,
-	) -> Option<Rc<Protein>> {
-		self.page = self.page.min(proteins.keys().len() / Self::PAGINATION);
+	) -> Option<Arc<Protein>> {
+		self.page = self.page.min(proteins.len() / Self::PAGINATION);
 		if self.last_render_page != self.page || self.paginated.is_empty() {
 			self.update_pagination(proteins);
 		}
@@ -132,7 +139,7 @@ impl ProteinSelector {
 		let mut result = None;
 		let button_width = ui.available_width();
 
-		for (index, stringed) in self.paginated.iter().enumerate() {
+		for (id, label) in &self.paginated {
 			let old_clip_rect = ui.clip_rect();
 
 			let cursor = ui.cursor().min.y;
@@ -140,9 +147,7 @@ impl ProteinSelector {
 			ui.style_mut().override_text_style = Some(TextStyle::Monospace);
 
 			ui.set_clip_rect(Rect::NOTHING);
-			let rect = ui
-				.add_sized([button_width, 30.], Button::new(stringed))
-				.rect;
+			let rect = ui.add_sized([button_width, 30.], Button::new(label)).rect;
 			ui.set_clip_rect(old_clip_rect);
 
 			if cursor < min_y - rect.height() || cursor > max_y + 100.0 {
@@ -150,22 +155,17 @@ impl ProteinSelector {
 			}
 
 			ui.allocate_ui_at_rect(rect, |ui| {
-				let selected = match self.selected_index {
-					Some((page, idx)) => page == self.page && idx == index,
-					None => false,
-				};
-
-				let color = match selected {
+				let color = match self.selected == Some(*id) {
 					true => Self::LIGHT_BUTTON,
 					false => Self::DARK_BUTTON,
 				};
 
 				if ui
-					.add_sized([button_width, 30.], Button::new(stringed).fill(color))
+					.add_sized([button_width, 30.], Button::new(label).fill(color))
 					.clicked()
 				{
-					result = proteins.get_by_string(stringed.clone());
-					self.selected_index = Some((self.page, index));
+					result = proteins.get_by_id(*id);
+					self.selected = Some(*id);
 				}
 			});
 
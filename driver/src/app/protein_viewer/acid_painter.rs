@@ -75,12 +75,12 @@ impl AcidPainter {
 			ui.add_space(-body_top + offset);
 			ui.vertical(|ui| {
 				ui.add_space(72.0 * self.scale);
-				body.show_size(ui, Vec2::new(body_size.x, body_size.y * -1.0));
+				body.show_size(ui, Vec2::new(body_size.x, -body_size.y));
 				ui.add_space(body_size.y + 18.0 * self.scale);
 				ui.horizontal(|ui| {
 					ui.add_space(body_top - offset);
 					base_rect = base
-						.show_size(ui, Vec2::new(base_size.x, base_size.y * -1.0))
+						.show_size(ui, Vec2::new(base_size.x, -base_size.y))
 						.rect;
 				});
 			});
@@ -95,6 +95,7 @@ pub const fn get_body(index: char) -> Option<Body> {
 		'V' => Body::new(V),
 		'W' => Body::new_flipped(W, W_FLIP),
 		'Y' => Body::new(Y),
+		'X' => Body::new(X),
 		_ => return None,
 	})
 }
@@ -116,5 +117,5 @@ include_strs!(
 	A, C, C_FLIP, D, E, F, H, H_FLIP, I, 
 	K, K_FLIP, L, M, M_FLIP, N, N_FLIP, P,
 	Q, Q_FLIP, R, R_FLIP, S, T, U, U_FLIP,
-	V, W, W_FLIP, Y, G
+	V, W, W_FLIP, Y, G, X
 );
@@ -1,6 +1,6 @@
 //! The module that implements [`ProteinViewer`]
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use egui::*;
 
@@ -27,7 +27,7 @@ use super::extras::Extras;
 #[derive(Default)]
 pub struct ProteinViewer {
 	/// The protein to display.
-	pub protein: Option<Rc<Protein>>,
+	pub protein: Option<Arc<Protein>>,
 	/// Cached texture assets.
 	cache: ViewerCache,
 	/// The acid painter.
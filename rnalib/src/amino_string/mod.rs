@@ -5,16 +5,22 @@ use std::fmt::{Display, Write};
 mod counts;
 use counts::Counts;
 
-use crate::{Acid, Codon};
+use crate::{Acid, Codon, Triplet};
 
 /// Represents a string of amino acids in a sequence.
 /// Internally, this is an abstraction over [`Vec<Codon>`].
+///
+/// A string translated from nucleotides may also keep the
+/// [`Triplet`] each of its amino acids was encoded by.
 #[derive(Default, Clone)]
 pub struct AminoString {
 	/// Internal data structure
 	codons: Vec<Codon>,
 	/// Internal ionizable amino acid counter
 	counts: Counts,
+	/// Originating triplets, parallel to `codons`,
+	/// or empty if they are not known
+	triplets: Vec<Option<Triplet>>,
 }
 
 impl AminoString {
@@ -25,14 +31,39 @@ impl AminoString {
 		for codon in &codons {
 			counts.add(codon);
 		}
-		Self { codons, counts }
+		Self {
+			codons,
+			counts,
+			triplets: Vec::new(),
+		}
+	}
+
+	/// Constructs an [`AminoString`] from a vector of
+	/// [`Codon`] instances, along with the [`Triplet`]
+	/// each of them was translated from. Triplets that
+	/// are not known, e.g. because they were ambiguous,
+	/// are [`None`].
+	///
+	/// Panics if the lengths of the vectors differ.
+	pub fn with_triplets(codons: Vec<Codon>, triplets: Vec<Option<Triplet>>) -> Self {
+		assert_eq!(codons.len(), triplets.len());
+		Self {
+			triplets,
+			..Self::from(codons)
+		}
 	}
 
 	/// Pushes a single [`Codon`] into the internal
 	/// [`Vec`].
+	///
+	/// If this string keeps its originating triplets,
+	/// the triplet of the new codon is not known.
 	pub fn push(&mut self, codon: Codon) {
 		self.counts.add(&codon);
 		self.codons.push(codon);
+		if !self.triplets.is_empty() {
+			self.triplets.push(None);
+		}
 	}
 
 	/// Returns a sub [`AminoString`] contained within
@@ -41,14 +72,12 @@ impl AminoString {
 	/// The returned data is an owned [`AminoString`]
 	/// created by copying the underlying data.
 	pub fn slice(&self, start: usize, length: usize) -> Self {
-		Self::from(
-			self.codons
-				.iter()
-				.copied()
-				.skip(start)
-				.take(length)
-				.collect(),
-		)
+		let codons = self.codons.iter().copied().skip(start).take(length);
+		let triplets = self.triplets.iter().copied().skip(start).take(length);
+		Self {
+			triplets: triplets.collect(),
+			..Self::from(codons.collect())
+		}
 	}
 
 	/// Returns the length of this [`AminoString`].
@@ -66,10 +95,21 @@ impl AminoString {
 		&self.codons
 	}
 
+	/// Returns the [`Triplet`] every amino acid of this
+	/// string was translated from, [`None`] where it is
+	/// not known.
+	///
+	/// Returns an empty slice if the string was not
+	/// translated from a nucleotide sequence.
+	pub fn get_triplets(&self) -> &[Option<Triplet>] {
+		&self.triplets
+	}
+
 	/// Clears the underlying [`Vec`] and resets [`Counts`]
 	/// to default.
 	pub fn clear(&mut self) {
 		self.codons.clear();
+		self.triplets.clear();
 		self.counts = Counts::default();
 	}
 
@@ -128,11 +168,17 @@ impl AminoString {
 
 	/// Returns the net charge of the amino acid represented
 	/// by this [`AminoString`] at a given pH level.
+	///
+	/// Unknown amino acids ([`Codon::X`]) are ignored, including
+	/// when determining the terminal groups.
 	pub fn net_charge(&self, ph: f32) -> f32 {
 		let mut result = 0.0;
 
+		let first = self.codons.iter().find_map(Codon::get_acid);
+		let last = self.codons.iter().rev().find_map(Codon::get_acid);
+
 		let counts_acids = [
-			(1, self.get_first().get_acid().unwrap().pk1),
+			(first.is_some() as u16, first.map_or(0.0, |x| x.pk1)),
 			(self.counts.get_d(), Acid::D.pk3.unwrap()),
 			(self.counts.get_e(), Acid::E.pk3.unwrap()),
 			(self.counts.get_c(), Acid::C.pk3.unwrap()),
@@ -140,7 +186,7 @@ impl AminoString {
 		];
 
 		let counts_bases = [
-			(1, self.get_last().get_acid().unwrap().pk2),
+			(last.is_some() as u16, last.map_or(0.0, |x| x.pk2)),
 			(self.counts.get_k(), Acid::K.pk3.unwrap()),
 			(self.counts.get_r(), Acid::R.pk3.unwrap()),
 			(self.counts.get_h(), Acid::H.pk3.unwrap()),
@@ -167,8 +213,7 @@ impl AminoString {
 	/// by this [`AminoString`].
 	pub fn get_phob(&self, _n: usize) -> f32 {
 		let mut hydrophobicity = 7.9;
-		for codon in &self.codons {
-			let acid_data = Codon::get_acid(codon).unwrap();
+		for acid_data in self.codons.iter().filter_map(Codon::get_acid) {
 			hydrophobicity += acid_data.sc_phob;
 		}
 		hydrophobicity
@@ -28,6 +28,9 @@ pub enum Codon {
 	D,
 	E,
 	G,
+	/// An unknown amino acid, encoded by
+	/// an ambiguous triplet.
+	X,
 }
 
 impl Codon {
@@ -46,7 +49,9 @@ impl Codon {
 	}
 
 	/// Constructs a [`Codon`] from three sequential instances
-	/// of [`Nucleotide`].
+	/// of [`Nucleotide`], using the standard genetic code.
+	///
+	/// See [`GeneticCode::translate`](crate::GeneticCode::translate) for other translation tables.
 	#[doc=include_str!("doc/amino_wheel.svg")]
 	pub const fn new(a: Nucleotide, b: Nucleotide, c: Nucleotide) -> Self {
 		use Nucleotide::*;
@@ -82,10 +87,44 @@ impl Codon {
 		}
 	}
 
+	/// Constructs a [`Codon`] from the single-letter shorthand
+	/// of the amino acid it encodes. Ignores letter-case.
+	/// The '*' and '_' characters are interpreted as [`Codon::STOP`].
+	///
+	/// Returns [`None`] if no such amino acid exists.
+	pub const fn from_shorthand(shorthand: char) -> Option<Self> {
+		Some(match shorthand.to_ascii_uppercase() {
+			'*' | '_' => Codon::STOP,
+			'M' => Codon::M,
+			'F' => Codon::F,
+			'L' => Codon::L,
+			'S' => Codon::S,
+			'Y' => Codon::Y,
+			'C' => Codon::C,
+			'W' => Codon::W,
+			'P' => Codon::P,
+			'H' => Codon::H,
+			'Q' => Codon::Q,
+			'R' => Codon::R,
+			'I' => Codon::I,
+			'T' => Codon::T,
+			'N' => Codon::N,
+			'K' => Codon::K,
+			'V' => Codon::V,
+			'A' => Codon::A,
+			'D' => Codon::D,
+			'E' => Codon::E,
+			'G' => Codon::G,
+			'X' => Codon::X,
+			_ => return None,
+		})
+	}
+
 	/// Returns physical properties of an amino acid
 	/// coded by this [`Codon`].
 	///
-	/// Returns [`None`] if this [`Codon`] instance is [`Codon::STOP`].
+	/// Returns [`None`] if this [`Codon`] instance is [`Codon::STOP`]
+	/// or [`Codon::X`].
 	pub const fn get_acid(&self) -> Option<Acid> {
 		Acid::from_shorthand(self.get_acid_shorthand())
 	}
@@ -118,6 +157,7 @@ impl Codon {
 			Codon::D => 'D',
 			Codon::E => 'E',
 			Codon::G => 'G',
+			Codon::X => 'X',
 		}
 	}
 }
@@ -0,0 +1,307 @@
+//! The module that implements [`Decompressor`]
+
+use std::io::{self, BufRead, BufReader, ErrorKind, Read};
+
+use flate2::bufread::MultiGzDecoder;
+use ruzstd::decoding::{
+	errors::{FrameDecoderError, ReadFrameHeaderError},
+	BlockDecodingStrategy, FrameDecoder,
+};
+
+/// The compression formats recognized by [`Decompressor`].
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+	/// Plain, uncompressed data
+	#[default]
+	None,
+	/// gzip, including the blocked variant written by
+	/// bgzip, which is a series of gzip members
+	Gzip,
+	/// Zstandard, possibly split into many frames
+	Zstd,
+}
+
+impl Compression {
+	/// Amount of bytes needed to tell the formats apart.
+	pub const MAGIC_LEN: usize = 4;
+
+	/// Detects the compression of data starting with
+	/// given bytes, by their magic number.
+	pub fn detect(bytes: &[u8]) -> Self {
+		match bytes {
+			[0x1F, 0x8B, ..] => Compression::Gzip,
+			[0x28, 0xB5, 0x2F, 0xFD, ..] => Compression::Zstd,
+			// Skippable frames, which may precede the Zstandard ones
+			[0x50..=0x5F, 0x2A, 0x4D, 0x18, ..] => Compression::Zstd,
+			_ => Compression::None,
+		}
+	}
+}
+
+/// A [`Read`] source that knows how many bytes of its
+/// underlying data it has consumed, which may differ from
+/// the amount of bytes read from it, e.g. when the data
+/// is decompressed or filtered.
+pub trait TrackedRead: Read {
+	/// Returns the amount of bytes of the underlying
+	/// data consumed so far.
+	fn get_consumed(&self) -> u64;
+}
+
+impl<R: TrackedRead> TrackedRead for BufReader<R> {
+	fn get_consumed(&self) -> u64 {
+		self.get_ref().get_consumed()
+	}
+}
+
+/// A [`Read`] stream that decompresses its source on the fly,
+/// if it is compressed in any of the [`Compression`] formats.
+///
+/// The compression is detected by the magic number the source
+/// starts with, so the name of a file does not matter. Series
+/// of gzip members and of Zstandard frames are decompressed one
+/// after another, and plain sources are passed through untouched.
+pub struct Decompressor<R> {
+	decoder: Decoder<R>,
+}
+
+/// The decoder of a given [`Compression`] format.
+enum Decoder<R> {
+	Plain(Counted<R>),
+	Gzip(MultiGzDecoder<Counted<R>>),
+	Zstd(Box<ZstdDecoder<R>>),
+}
+
+impl<R: BufRead> Decompressor<R> {
+	/// Constructs a [`Decompressor`] reading from a given
+	/// source, and detects its compression.
+	///
+	/// Only the bytes the source has buffered are inspected,
+	/// so a source returning fewer than [`Compression::MAGIC_LEN`]
+	/// bytes at once may not be recognized.
+	pub fn new(mut reader: R) -> io::Result<Self> {
+		let compression = loop {
+			match reader.fill_buf() {
+				Ok(bytes) => break Compression::detect(bytes),
+				Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+				Err(err) => return Err(err),
+			}
+		};
+
+		let source = Counted {
+			reader,
+			consumed: 0,
+		};
+		let decoder = match compression {
+			Compression::None => Decoder::Plain(source),
+			Compression::Gzip => Decoder::Gzip(MultiGzDecoder::new(source)),
+			Compression::Zstd => Decoder::Zstd(Box::new(ZstdDecoder {
+				source,
+				frame: FrameDecoder::new(),
+				in_frame: false,
+			})),
+		};
+		Ok(Self { decoder })
+	}
+
+	/// Returns the compression of the source.
+	pub fn get_compression(&self) -> Compression {
+		match self.decoder {
+			Decoder::Plain(_) => Compression::None,
+			Decoder::Gzip(_) => Compression::Gzip,
+			Decoder::Zstd(_) => Compression::Zstd,
+		}
+	}
+
+	/// Returns a reference to the underlying source.
+	pub fn get_ref(&self) -> &R {
+		&self.get_counted().reader
+	}
+
+	/// Returns a mutable reference to the underlying source.
+	///
+	/// Reading from it directly is only sound while the
+	/// source is not compressed.
+	pub fn get_mut(&mut self) -> &mut R {
+		match &mut self.decoder {
+			Decoder::Plain(source) => &mut source.reader,
+			Decoder::Gzip(decoder) => &mut decoder.get_mut().reader,
+			Decoder::Zstd(decoder) => &mut decoder.source.reader,
+		}
+	}
+
+	/// A helper function that returns the counter of the
+	/// bytes consumed from the source.
+	fn get_counted(&self) -> &Counted<R> {
+		match &self.decoder {
+			Decoder::Plain(source) => source,
+			Decoder::Gzip(decoder) => decoder.get_ref(),
+			Decoder::Zstd(decoder) => &decoder.source,
+		}
+	}
+}
+
+impl<R: BufRead> Read for Decompressor<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		match &mut self.decoder {
+			Decoder::Plain(source) => source.read(buf),
+			Decoder::Gzip(decoder) => decoder.read(buf),
+			Decoder::Zstd(decoder) => decoder.read(buf),
+		}
+	}
+}
+
+impl<R: BufRead> TrackedRead for Decompressor<R> {
+	/// Returns the amount of compressed bytes consumed from
+	/// the source so far, including those buffered by the
+	/// decoder but not decompressed yet.
+	fn get_consumed(&self) -> u64 {
+		self.get_counted().consumed
+	}
+}
+
+/// A buffered source that counts the bytes consumed from it.
+struct Counted<R> {
+	reader: R,
+	consumed: u64,
+}
+
+impl<R: BufRead> Read for Counted<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let len = self.reader.read(buf)?;
+		self.consumed += len as u64;
+		Ok(len)
+	}
+}
+
+impl<R: BufRead> BufRead for Counted<R> {
+	fn fill_buf(&mut self) -> io::Result<&[u8]> {
+		self.reader.fill_buf()
+	}
+
+	fn consume(&mut self, amt: usize) {
+		self.consumed += amt as u64;
+		self.reader.consume(amt);
+	}
+}
+
+/// A decoder of Zstandard data made of any number of frames,
+/// some of which may be skippable ones.
+struct ZstdDecoder<R> {
+	source: Counted<R>,
+	frame: FrameDecoder,
+	/// True while inside of a frame that has not been
+	/// fully read yet
+	in_frame: bool,
+}
+
+impl<R: BufRead> Read for ZstdDecoder<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		if buf.is_empty() {
+			return Ok(0);
+		}
+		loop {
+			if !self.in_frame {
+				if self.source.fill_buf()?.is_empty() {
+					return Ok(0);
+				}
+				match self.frame.reset(&mut self.source) {
+					Ok(()) => self.in_frame = true,
+					Err(FrameDecoderError::ReadFrameHeaderError(
+						ReadFrameHeaderError::SkipFrame { length, .. },
+					)) => {
+						let mut skipped = (&mut self.source).take(length as u64);
+						io::copy(&mut skipped, &mut io::sink())?;
+						continue;
+					}
+					Err(err) => return Err(io::Error::new(ErrorKind::InvalidData, err)),
+				}
+			}
+
+			while self.frame.can_collect() < buf.len() && !self.frame.is_finished() {
+				let needed = buf.len() - self.frame.can_collect();
+				let strategy = BlockDecodingStrategy::UptoBytes(needed);
+				if let Err(err) = self.frame.decode_blocks(&mut self.source, strategy) {
+					return Err(io::Error::new(ErrorKind::InvalidData, err));
+				}
+			}
+			let len = self.frame.read(buf)?;
+			if len > 0 {
+				return Ok(len);
+			}
+			self.in_frame = !self.frame.is_finished();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Write;
+
+	use flate2::{write::GzEncoder, Compression as Level};
+
+	use super::*;
+
+	/// A helper function that compresses a given text
+	/// into a single gzip member.
+	fn gzip(text: &str) -> Vec<u8> {
+		let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+		encoder.write_all(text.as_bytes()).unwrap();
+		encoder.finish().unwrap()
+	}
+
+	/// A helper function that stores a given text in a
+	/// Zstandard frame with a single raw block, preceded
+	/// by a skippable frame.
+	fn zstd(text: &str) -> Vec<u8> {
+		let mut bytes = vec![0x5A, 0x2A, 0x4D, 0x18, 3, 0, 0, 0, 1, 2, 3];
+		// Single segment frame, with its size in a byte
+		bytes.extend([0x28, 0xB5, 0x2F, 0xFD, 0x20, text.len() as u8]);
+		let header = (text.len() as u32) << 3 | 1;
+		bytes.extend(&header.to_le_bytes()[..3]);
+		bytes.extend(text.as_bytes());
+		bytes
+	}
+
+	/// A helper function that decompresses given bytes,
+	/// returning the detected compression and the text.
+	fn decompress(bytes: &[u8]) -> (Compression, String) {
+		let mut decompressor = Decompressor::new(bytes).unwrap();
+		let mut text = String::new();
+		decompressor.read_to_string(&mut text).unwrap();
+		assert_eq!(decompressor.get_consumed(), bytes.len() as u64);
+		(decompressor.get_compression(), text)
+	}
+
+	#[test]
+	fn magic_numbers_are_detected() {
+		assert!(Compression::detect(&[0x1F, 0x8B, 0x08, 0x00]) == Compression::Gzip);
+		assert!(Compression::detect(&[0x28, 0xB5, 0x2F, 0xFD]) == Compression::Zstd);
+		assert!(Compression::detect(&[0x50, 0x2A, 0x4D, 0x18]) == Compression::Zstd);
+		assert!(Compression::detect(&[0x5F, 0x2A, 0x4D, 0x18]) == Compression::Zstd);
+		assert!(Compression::detect(&[0x60, 0x2A, 0x4D, 0x18]) == Compression::None);
+		assert!(Compression::detect(&[0x28, 0xB5, 0x2F]) == Compression::None);
+		assert!(Compression::detect(b">seq\nACGU") == Compression::None);
+		assert!(Compression::detect(&[]) == Compression::None);
+	}
+
+	#[test]
+	fn sources_are_decompressed() {
+		let (compression, text) = decompress(b">seq\nACGU\n");
+		assert!(compression == Compression::None);
+		assert_eq!(text, ">seq\nACGU\n");
+
+		// Every member of a series is read, as written by bgzip
+		let mut bytes = gzip(">first\nACGU\n");
+		bytes.extend(gzip(">second\nUGCA\n"));
+		let (compression, text) = decompress(&bytes);
+		assert!(compression == Compression::Gzip);
+		assert_eq!(text, ">first\nACGU\n>second\nUGCA\n");
+
+		let mut bytes = zstd(">first\nACGU\n");
+		bytes.extend(zstd(">second\nUGCA\n"));
+		let (compression, text) = decompress(&bytes);
+		assert!(compression == Compression::Zstd);
+		assert_eq!(text, ">first\nACGU\n>second\nUGCA\n");
+	}
+}
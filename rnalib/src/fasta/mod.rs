@@ -0,0 +1,66 @@
+//! The module that implements [`FastaRecord`]
+
+use std::ops::Range;
+
+mod reader;
+pub use reader::*;
+
+mod writer;
+pub use writer::*;
+
+use crate::{NucleotideString, RecordHeader};
+
+/// A single record of a FASTA file, i.e. a header
+/// followed by a nucleotide sequence.
+#[derive(Default, Clone)]
+pub struct FastaRecord {
+	header: RecordHeader,
+	sequence: NucleotideString,
+	/// Sorted, disjoint ranges of soft-masked nucleotides
+	masked: Vec<Range<usize>>,
+}
+
+impl FastaRecord {
+	/// Constructs a [`FastaRecord`] from its header, sequence,
+	/// and sorted ranges of soft-masked nucleotides.
+	pub fn new(
+		header: RecordHeader,
+		sequence: NucleotideString,
+		masked: Vec<Range<usize>>,
+	) -> Self {
+		Self {
+			header,
+			sequence,
+			masked,
+		}
+	}
+
+	/// Returns the header of this record.
+	pub fn get_header(&self) -> &RecordHeader {
+		&self.header
+	}
+
+	/// Returns the sequence of this record.
+	pub fn get_sequence(&self) -> &NucleotideString {
+		&self.sequence
+	}
+
+	/// Takes ownership of the sequence of this record.
+	pub fn into_sequence(self) -> NucleotideString {
+		self.sequence
+	}
+
+	/// Returns the ranges of soft-masked nucleotides, i.e.
+	/// the ones written in lowercase, usually marking
+	/// repeats or low complexity regions.
+	pub fn get_masked(&self) -> &[Range<usize>] {
+		&self.masked
+	}
+
+	/// Returns true if the nucleotide at a given
+	/// index is soft-masked.
+	pub fn is_masked(&self, index: usize) -> bool {
+		let next = self.masked.partition_point(|x| x.end <= index);
+		self.masked.get(next).is_some_and(|x| x.contains(&index))
+	}
+}
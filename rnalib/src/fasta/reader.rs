@@ -0,0 +1,152 @@
+//! The module that implements [`FastaReader`]
+
+use std::io::{BufRead, ErrorKind};
+
+use crate::{nucleotide_string::parser::SequenceParser, LoadError, NucleotideString, RecordHeader};
+
+use super::FastaRecord;
+
+/// An iterator over the records of a FASTA file,
+/// which reads them one by one from any [`BufRead`]
+/// source.
+///
+/// Sequences may be wrapped over any number of lines,
+/// and may contain IUPAC ambiguity codes and gaps, see
+/// [`NucleotideString::parse`]. Lowercase nucleotides are
+/// reported as soft-masked. A sequence preceding the first
+/// header is read as a record with an empty header.
+///
+/// Yields a [`LoadError`] pointing at the first character
+/// that is not a nucleotide, then stops.
+pub struct FastaReader<R> {
+	reader: R,
+	parser: SequenceParser,
+	/// Header of the record being read, if it had one
+	header: Option<RecordHeader>,
+	/// Sequence of the record being read
+	sequence: NucleotideString,
+	finished: bool,
+}
+
+impl<R: BufRead> FastaReader<R> {
+	/// Constructs a [`FastaReader`] reading from a given source.
+	pub fn new(reader: R) -> Self {
+		Self {
+			reader,
+			parser: SequenceParser::fasta(true),
+			header: None,
+			sequence: NucleotideString::default(),
+			finished: false,
+		}
+	}
+
+	/// A helper function that takes the record read so far,
+	/// or returns [`None`] if nothing has been read.
+	fn take_record(&mut self) -> Option<FastaRecord> {
+		let header = self.header.take();
+		let masked = self.parser.take_masked();
+		if header.is_none() && self.sequence.is_empty() {
+			return None;
+		}
+		let sequence = std::mem::take(&mut self.sequence);
+		Some(FastaRecord::new(
+			header.unwrap_or_default(),
+			sequence,
+			masked,
+		))
+	}
+}
+
+impl<R: BufRead> Iterator for FastaReader<R> {
+	type Item = Result<FastaRecord, LoadError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while !self.finished {
+			let bytes = match self.reader.fill_buf() {
+				Ok(x) => x,
+				Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+				Err(err) => {
+					self.finished = true;
+					return Some(Err(err.into()));
+				}
+			};
+
+			let (len, header) = match bytes.is_empty() {
+				true => match self.parser.finish() {
+					Some(header) => (0, Some(header)),
+					None => {
+						self.finished = true;
+						return self.take_record().map(Ok);
+					}
+				},
+				false => match self.parser.parse(bytes, &mut self.sequence) {
+					Ok(x) => x,
+					Err(err) => {
+						self.finished = true;
+						return Some(Err(err));
+					}
+				},
+			};
+			self.reader.consume(len);
+
+			if let Some(header) = header {
+				let record = self.take_record();
+				self.header = Some(header);
+				if record.is_some() {
+					return record.map(Ok);
+				}
+			}
+		}
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::BufReader;
+
+	use super::*;
+
+	/// A helper function that reads every record through
+	/// a buffer of a given capacity.
+	fn read(text: &str, capacity: usize) -> Vec<Result<FastaRecord, LoadError>> {
+		FastaReader::new(BufReader::with_capacity(capacity, text.as_bytes())).collect()
+	}
+
+	#[test]
+	fn records_end_at_headers() {
+		let text = "ACGU\n>first one\nacGU\nUUaa\n>second\n\n>third\nNRY-\n";
+		for capacity in [1, 2, 3, 5, 8, 1024] {
+			let records = read(text, capacity).into_iter().map(Result::unwrap);
+			let records: Vec<_> = records.collect();
+			let headers: Vec<_> = records.iter().map(|x| x.get_header().to_string()).collect();
+			let sequences = records.iter().map(|x| x.get_sequence().to_string());
+			let sequences: Vec<_> = sequences.collect();
+			assert_eq!(headers, ["", "first one", "second", "third"]);
+			assert_eq!(sequences, ["ACGU", "ACGUUUAA", "", "NRY"]);
+			assert!(records[1].get_masked() == [0..2, 6..8]);
+			assert!(records[1].is_masked(7) && !records[1].is_masked(2));
+		}
+		assert!(read("", 16).is_empty());
+		assert!(read("\n\n", 16).is_empty());
+	}
+
+	#[test]
+	fn errors_point_at_the_character() {
+		for capacity in [1, 4, 1024] {
+			let mut records = read(">a\nAC\n>b\nGU\nAxG\n>c\nAA\n", capacity).into_iter();
+			let first = records.next().unwrap().unwrap();
+			assert_eq!(first.get_sequence().to_string(), "AC");
+			match records.next() {
+				Some(Err(LoadError::InvalidCharacter {
+					character,
+					offset,
+					line,
+					column,
+				})) => assert_eq!((character, offset, line, column), ('x', 13, 5, 2)),
+				_ => panic!("expected an invalid character"),
+			}
+			assert!(records.next().is_none());
+		}
+	}
+}
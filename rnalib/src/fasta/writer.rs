@@ -0,0 +1,158 @@
+//! The module that implements [`FastaWriter`]
+
+use std::io::{Result, Write};
+
+use crate::{AminoString, IupacNucleotide, Molecule, ProteinMap, RecordHeader};
+
+use super::FastaRecord;
+
+/// Writes nucleotide or protein sequences to any [`Write`]
+/// target in the FASTA format, wrapping the sequences at a
+/// given line width.
+pub struct FastaWriter<W> {
+	writer: W,
+	/// Maximal amount of characters in a sequence line,
+	/// or 0 if sequences are not wrapped
+	line_width: usize,
+}
+
+impl<W: Write> FastaWriter<W> {
+	/// Line width used by [`FastaWriter::new`], as
+	/// used by NCBI.
+	pub const DEFAULT_LINE_WIDTH: usize = 60;
+
+	/// Constructs a [`FastaWriter`] wrapping the sequences
+	/// at [`FastaWriter::DEFAULT_LINE_WIDTH`] characters.
+	pub fn new(writer: W) -> Self {
+		Self::with_line_width(writer, Self::DEFAULT_LINE_WIDTH)
+	}
+
+	/// Constructs a [`FastaWriter`] wrapping the sequences at
+	/// a given amount of characters, or not wrapping them if
+	/// the width is 0.
+	pub fn with_line_width(writer: W, line_width: usize) -> Self {
+		Self { writer, line_width }
+	}
+
+	/// Writes a nucleotide record, with the soft-masked
+	/// nucleotides in lowercase.
+	pub fn write_record(&mut self, record: &FastaRecord) -> Result<()> {
+		let sequence = record.get_sequence();
+		let molecule = sequence.get_molecule();
+		let masked = record.get_masked();
+		let mut next_mask = 0;
+
+		let text = sequence.iter().enumerate().map(|(index, x)| {
+			let letter = match (x, molecule) {
+				(IupacNucleotide::U, Molecule::Dna) => 'T',
+				_ => x.get_symbol(),
+			};
+			while masked.get(next_mask).is_some_and(|x| x.end <= index) {
+				next_mask += 1;
+			}
+			match masked.get(next_mask) {
+				Some(range) if range.contains(&index) => letter.to_ascii_lowercase(),
+				_ => letter,
+			}
+		});
+
+		self.write_header(record.get_header())?;
+		self.write_wrapped(text)
+	}
+
+	/// Writes a protein record with a given header.
+	pub fn write_protein(&mut self, header: &RecordHeader, protein: &AminoString) -> Result<()> {
+		self.write_header(header)?;
+		self.write_wrapped(protein.get_codons().iter().map(|x| x.get_acid_shorthand()))
+	}
+
+	/// Writes every occurrence of every protein of a given
+	/// [`ProteinMap`] as a separate record, in the order of
+	/// their position in the source.
+	///
+	/// Records are named after the source record and numbered
+	/// within it, e.g. `chr1_orf12`. The description holds the
+	/// source record, frame and coordinates of the occurrence,
+	/// and whether it was annotated or predicted, e.g.
+	/// `record=chr1 frame=-2 location=complement(11..40) length=9 source=predicted`.
+	/// Annotated occurrences are described by the location
+	/// of their feature.
+	pub fn write_proteins(&mut self, map: &ProteinMap) -> Result<()> {
+		let mut occurrences: Vec<_> = map.occurrences().collect();
+		occurrences.sort_unstable_by_key(|(_, x)| {
+			(
+				x.get_record(),
+				x.get_start(),
+				x.get_end(),
+				x.get_frame(),
+				x.get_feature(),
+			)
+		});
+
+		let mut current = None;
+		let mut counter = 0;
+		for (protein, location) in occurrences {
+			if current != Some(location.get_record()) {
+				current = Some(location.get_record());
+				counter = 0;
+			}
+			counter += 1;
+
+			let record = map.get_record(location).map_or("", |x| x.get_id());
+			let record = match record.is_empty() {
+				true => "seq",
+				false => record,
+			};
+			let coordinates = match map.get_feature(location).and_then(|x| x.get_location()) {
+				Some(x) => x.to_string(),
+				None => location.to_string(),
+			};
+			let source = match location.is_annotated() {
+				true => "annotated",
+				false => "predicted",
+			};
+
+			let header = RecordHeader::new(
+				format!("{record}_orf{counter}"),
+				format!(
+					"record={record} frame={} location={coordinates} length={} source={source}",
+					location.get_frame(),
+					protein.len()
+				),
+			);
+			self.write_protein(&header, protein)?;
+		}
+		self.writer.flush()
+	}
+
+	/// Flushes and returns the underlying [`Write`] target.
+	pub fn into_inner(mut self) -> Result<W> {
+		self.writer.flush()?;
+		Ok(self.writer)
+	}
+
+	/// A helper function that writes a header line.
+	fn write_header(&mut self, header: &RecordHeader) -> Result<()> {
+		writeln!(self.writer, ">{header}")
+	}
+
+	/// A helper function that writes the characters of a
+	/// sequence, wrapping them at the line width.
+	fn write_wrapped(&mut self, text: impl Iterator<Item = char>) -> Result<()> {
+		let mut line = String::new();
+		let mut len = 0;
+		for x in text {
+			line.push(x);
+			len += 1;
+			if len == self.line_width {
+				writeln!(self.writer, "{line}")?;
+				line.clear();
+				len = 0;
+			}
+		}
+		if !line.is_empty() {
+			writeln!(self.writer, "{line}")?;
+		}
+		Ok(())
+	}
+}
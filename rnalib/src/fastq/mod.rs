@@ -0,0 +1,77 @@
+//! The module that implements [`FastqRecord`]
+
+mod quality;
+pub use quality::*;
+
+mod reader;
+pub use reader::*;
+
+use crate::{NucleotideString, RecordHeader};
+
+/// A single read of a FASTQ file, i.e. a header followed
+/// by a nucleotide sequence and the quality of each of its
+/// nucleotides.
+#[derive(Default, Clone)]
+pub struct FastqRecord {
+	header: RecordHeader,
+	sequence: NucleotideString,
+	/// Decoded Phred quality scores, one per nucleotide
+	qualities: Vec<u8>,
+}
+
+impl FastqRecord {
+	/// Constructs a [`FastqRecord`] from its header, sequence,
+	/// and Phred quality scores.
+	///
+	/// Panics if there is not exactly one quality score
+	/// for every nucleotide.
+	pub fn new(header: RecordHeader, sequence: NucleotideString, qualities: Vec<u8>) -> Self {
+		assert_eq!(sequence.len(), qualities.len());
+		Self {
+			header,
+			sequence,
+			qualities,
+		}
+	}
+
+	/// Returns the header of this read.
+	pub fn get_header(&self) -> &RecordHeader {
+		&self.header
+	}
+
+	/// Returns the sequence of this read.
+	pub fn get_sequence(&self) -> &NucleotideString {
+		&self.sequence
+	}
+
+	/// Takes ownership of the sequence of this read.
+	pub fn into_sequence(self) -> NucleotideString {
+		self.sequence
+	}
+
+	/// Returns the Phred quality scores of the nucleotides
+	/// of this read, i.e. `-10 log10(p)` where `p` is the
+	/// probability that the nucleotide is wrong.
+	pub fn get_qualities(&self) -> &[u8] {
+		&self.qualities
+	}
+
+	/// Returns the amount of nucleotides in this read.
+	pub fn len(&self) -> usize {
+		self.qualities.len()
+	}
+
+	/// Returns true if this read holds no nucleotides.
+	pub fn is_empty(&self) -> bool {
+		self.qualities.is_empty()
+	}
+
+	/// Shortens this read to its first `len` nucleotides.
+	/// Does nothing if the read is not longer than that.
+	pub fn truncate(&mut self, len: usize) {
+		if len < self.len() {
+			self.sequence = self.sequence.slice(0, len);
+			self.qualities.truncate(len);
+		}
+	}
+}
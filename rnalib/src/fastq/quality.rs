@@ -0,0 +1,108 @@
+//! The module that implements [`QualityEncoding`] and [`QualityFilter`]
+
+use serde::{Deserialize, Serialize};
+
+use super::FastqRecord;
+
+/// Describes how Phred quality scores are stored
+/// as characters in a FASTQ file.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum QualityEncoding {
+	/// Scores offset by 33, i.e. from '!' up, used by
+	/// Sanger and Illumina 1.8 and later
+	#[default]
+	Phred33,
+	/// Scores offset by 64, i.e. from '@' up, used by
+	/// Illumina from 1.3 to 1.7
+	Phred64,
+}
+
+impl QualityEncoding {
+	/// Returns the character code of the score 0.
+	pub const fn get_offset(&self) -> u8 {
+		match self {
+			QualityEncoding::Phred33 => 33,
+			QualityEncoding::Phred64 => 64,
+		}
+	}
+
+	/// Decodes a Phred quality score from a character.
+	///
+	/// Returns [`None`] if the character does not encode
+	/// a score, i.e. is below the offset or not printable.
+	pub const fn decode(&self, character: u8) -> Option<u8> {
+		match character {
+			x if x >= self.get_offset() && x <= b'~' => Some(x - self.get_offset()),
+			_ => None,
+		}
+	}
+}
+
+/// Describes how the reads of a FASTQ file are trimmed
+/// and filtered based on their quality.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QualityFilter {
+	/// Size of the sliding window, in nucleotides,
+	/// or 0 if reads are not trimmed
+	pub window: usize,
+	/// Minimal average quality score of a window
+	pub min_quality: u8,
+	/// Minimal length of a trimmed read, in nucleotides
+	pub min_length: usize,
+}
+
+impl QualityFilter {
+	/// Returns the length a read with given quality scores
+	/// is trimmed to.
+	///
+	/// The read is scanned with a sliding window from its
+	/// start, and cut right before the first window whose
+	/// average quality is below the minimum. Reads shorter
+	/// than the window are checked as a whole.
+	pub fn trimmed_len(&self, qualities: &[u8]) -> usize {
+		let window = self.window.min(qualities.len());
+		if window == 0 {
+			return qualities.len();
+		}
+
+		let threshold = self.min_quality as usize * window;
+		let mut sum: usize = qualities[..window].iter().map(|&x| x as usize).sum();
+		for start in 0..=qualities.len() - window {
+			if start > 0 {
+				sum += qualities[start + window - 1] as usize;
+				sum -= qualities[start - 1] as usize;
+			}
+			if sum < threshold {
+				return start;
+			}
+		}
+		qualities.len()
+	}
+
+	/// Trims a given read, see [`QualityFilter::trimmed_len`].
+	///
+	/// Returns [`None`] if the read is shorter than the
+	/// minimal length once trimmed, or is empty.
+	pub fn apply(&self, mut record: FastqRecord) -> Option<FastqRecord> {
+		let len = self.trimmed_len(record.get_qualities());
+		if len == 0 || len < self.min_length {
+			return None;
+		}
+		record.truncate(len);
+		Some(record)
+	}
+}
+
+impl Default for QualityFilter {
+	/// Returns the filter suggested by Trimmomatic, i.e.
+	/// a window of 4 nucleotides with an average quality
+	/// of at least 15, and reads of at least 36 nucleotides.
+	fn default() -> Self {
+		Self {
+			window: 4,
+			min_quality: 15,
+			min_length: 36,
+		}
+	}
+}
@@ -0,0 +1,258 @@
+//! The module that implements [`FastqReader`]
+
+use std::io::{BufRead, ErrorKind};
+
+use crate::{nucleotide_string::parser::SequenceParser, LoadError, NucleotideString, RecordHeader};
+
+use super::{FastqRecord, QualityEncoding};
+
+/// An iterator over the reads of a FASTQ file, which
+/// reads them one by one from any [`BufRead`] source.
+///
+/// Both sequences and qualities may be wrapped over any
+/// number of lines, and blank lines between reads are
+/// skipped. Sequences are read as in [`NucleotideString::parse`],
+/// but may not contain alignment gaps.
+///
+/// Yields a [`LoadError`] pointing at the first character
+/// that is neither a nucleotide nor a quality score, or at
+/// the first malformed read, then stops.
+pub struct FastqReader<R> {
+	reader: R,
+	encoding: QualityEncoding,
+	/// Parser of the sequence lines, which also keeps
+	/// track of the position in the source
+	parser: SequenceParser,
+	/// The line being read
+	line: Vec<u8>,
+	/// Amount of bytes read from the source
+	offset: u64,
+	/// Amount of lines read from the source
+	lines: u64,
+	finished: bool,
+}
+
+impl<R: BufRead> FastqReader<R> {
+	/// Constructs a [`FastqReader`] reading from a given
+	/// source, with qualities stored in a given encoding.
+	pub fn new(reader: R, encoding: QualityEncoding) -> Self {
+		Self {
+			reader,
+			encoding,
+			parser: SequenceParser::default(),
+			line: Vec::new(),
+			offset: 0,
+			lines: 0,
+			finished: false,
+		}
+	}
+
+	/// Returns the amount of bytes read from the source so far,
+	/// i.e. up to the end of the last read returned.
+	pub fn get_offset(&self) -> u64 {
+		self.offset
+	}
+
+	/// Returns a reference to the underlying source.
+	pub fn get_ref(&self) -> &R {
+		&self.reader
+	}
+
+	/// A helper function that reads the next read.
+	///
+	/// Returns [`None`] at the end of the source.
+	fn read_record(&mut self) -> Result<Option<FastqRecord>, LoadError> {
+		loop {
+			if !self.read_line()? {
+				return Ok(None);
+			}
+			if !self.line.trim_ascii().is_empty() {
+				break;
+			}
+			self.parser.skip(&self.line);
+		}
+
+		let Some(header) = self.line.strip_prefix(b"@") else {
+			return Err(self.invalid_record("brak nagłówka rozpoczętego znakiem '@'"));
+		};
+		let header = RecordHeader::parse(&String::from_utf8_lossy(header));
+		self.parser.skip(&self.line);
+
+		let mut sequence = NucleotideString::default();
+		let mut length = 0;
+		loop {
+			if !self.read_line()? {
+				return Err(self.invalid_record("brak linii rozpoczętej znakiem '+'"));
+			}
+			if self.line.starts_with(b"+") {
+				self.parser.skip(&self.line);
+				break;
+			}
+			length += self
+				.line
+				.iter()
+				.filter(|x| !x.is_ascii_whitespace())
+				.count();
+			self.parser.parse(&self.line, &mut sequence)?;
+		}
+
+		let mut qualities = Vec::with_capacity(length);
+		while qualities.len() < length {
+			if !self.read_line()? {
+				return Err(self.invalid_record("za mało wartości jakości"));
+			}
+			for (index, &x) in self.line.iter().enumerate() {
+				if x.is_ascii_whitespace() {
+					continue;
+				}
+				match self.encoding.decode(x) {
+					Some(x) => qualities.push(x),
+					None => return Err(self.invalid_character(index)),
+				}
+			}
+			self.parser.skip(&self.line);
+		}
+
+		if qualities.len() != length {
+			return Err(self.invalid_record("za dużo wartości jakości"));
+		}
+		if sequence.len() != length {
+			return Err(self.invalid_record("sekwencja zawiera przerwy"));
+		}
+		Ok(Some(FastqRecord::new(header, sequence, qualities)))
+	}
+
+	/// A helper function that reads the next line.
+	///
+	/// Returns false at the end of the source.
+	fn read_line(&mut self) -> Result<bool, LoadError> {
+		self.line.clear();
+		loop {
+			match self.reader.read_until(b'\n', &mut self.line) {
+				Ok(0) => return Ok(false),
+				Ok(len) => {
+					self.offset += len as u64;
+					self.lines += 1;
+					return Ok(true);
+				}
+				Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+				Err(err) => return Err(err.into()),
+			}
+		}
+	}
+
+	/// A helper function that constructs an error pointing
+	/// at the line being read.
+	fn invalid_record(&self, reason: &'static str) -> LoadError {
+		LoadError::InvalidRecord {
+			reason,
+			offset: self.offset - self.line.len() as u64,
+			line: self.lines.max(1),
+		}
+	}
+
+	/// A helper function that constructs an error pointing
+	/// at the character at a given index of the line being read.
+	fn invalid_character(&self, index: usize) -> LoadError {
+		let line = &self.line;
+		LoadError::InvalidCharacter {
+			character: SequenceParser::decode_char(&line[index..]),
+			offset: self.offset - (line.len() - index) as u64,
+			line: self.lines,
+			// UTF-8 continuation bytes do not start a new character
+			column: line[..index].iter().filter(|&&x| x & 0xC0 != 0x80).count() as u64 + 1,
+		}
+	}
+}
+
+impl<R: BufRead> Iterator for FastqReader<R> {
+	type Item = Result<FastqRecord, LoadError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.finished {
+			return None;
+		}
+		let result = self.read_record();
+		self.finished = !matches!(result, Ok(Some(_)));
+		result.transpose()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A helper function that returns the error that
+	/// stopped reading a given source.
+	fn error(text: &str, encoding: QualityEncoding) -> LoadError {
+		let mut reader = FastqReader::new(text.as_bytes(), encoding);
+		let error = reader.find_map(Result::err).unwrap();
+		assert!(reader.next().is_none());
+		error
+	}
+
+	/// A helper function that returns the position of
+	/// an [`LoadError::InvalidRecord`].
+	fn record_error(text: &str) -> (&'static str, u64, u64) {
+		match error(text, QualityEncoding::Phred33) {
+			LoadError::InvalidRecord {
+				reason,
+				offset,
+				line,
+			} => (reason, offset, line),
+			err => panic!("expected an invalid record, got: {err}"),
+		}
+	}
+
+	#[test]
+	fn reads_end_after_their_qualities() {
+		// Quality lines may start with '@' or '+'
+		let text = "@r1 first read\nACGU\nGU\n+\nIIII\n+I\n\n\n@r2\nAC\n+r2\n@@\n";
+		let mut reader = FastqReader::new(text.as_bytes(), QualityEncoding::Phred33);
+		let first = reader.next().unwrap().unwrap();
+		assert_eq!(first.get_header().to_string(), "r1 first read");
+		assert_eq!(first.get_sequence().to_string(), "ACGUGU");
+		assert_eq!(first.get_qualities(), [40, 40, 40, 40, 10, 40]);
+		assert_eq!(reader.get_offset(), text.find("\n\n").unwrap() as u64 + 1);
+
+		let second = reader.next().unwrap().unwrap();
+		assert_eq!(second.get_header().get_id(), "r2");
+		assert_eq!(second.get_sequence().to_string(), "AC");
+		assert_eq!(second.get_qualities(), [31, 31]);
+		assert!(reader.next().is_none());
+	}
+
+	#[test]
+	fn errors_point_at_the_read() {
+		let text = "@r\nAC\n+\nhh\n@s\nACxU\n+\nhhhh\n";
+		match error(text, QualityEncoding::Phred64) {
+			LoadError::InvalidCharacter {
+				character,
+				offset,
+				line,
+				column,
+			} => assert_eq!((character, offset, line, column), ('x', 16, 6, 3)),
+			err => panic!("expected an invalid character, got: {err}"),
+		}
+		match error("@r\nAC\n+\nh5\n", QualityEncoding::Phred64) {
+			LoadError::InvalidCharacter {
+				character,
+				offset,
+				line,
+				column,
+			} => assert_eq!((character, offset, line, column), ('5', 9, 4, 2)),
+			err => panic!("expected an invalid character, got: {err}"),
+		}
+
+		let header = record_error("\nr\nAC\n+\nII\n");
+		assert_eq!(header, ("brak nagłówka rozpoczętego znakiem '@'", 1, 2));
+		let separator = record_error("@r\nAC\nGU\n");
+		assert_eq!(separator, ("brak linii rozpoczętej znakiem '+'", 9, 3));
+		let too_few = record_error("@r\nACG\n+\nII\n");
+		assert_eq!(too_few, ("za mało wartości jakości", 12, 4));
+		let too_many = record_error("@r\nAC\n+\nIII\n@s\n");
+		assert_eq!(too_many, ("za dużo wartości jakości", 8, 4));
+		let gaps = record_error("@r\nA-C\n+\nIII\n");
+		assert_eq!(gaps, ("sekwencja zawiera przerwy", 9, 4));
+	}
+}
@@ -0,0 +1,549 @@
+//! The module that implements [`Feature`] and [`FeatureLocation`]
+
+use std::{fmt::Display, ops::Range};
+
+use crate::{AminoString, Codon, Frame, GeneticCode, Location, NucleotideString, Strand, Triplet};
+
+/// The location of a feature of a GenBank or EMBL record,
+/// made of one or more segments of the record's sequence.
+///
+/// Segments are kept in the order of translation, each with
+/// the strand it is read from, so that `complement(join(1..10,20..30))`
+/// is the same as `join(complement(20..30),complement(1..10))`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct FeatureLocation {
+	/// 0-based ranges of the forward strand, along with
+	/// the strand they are read from
+	segments: Vec<(Range<usize>, Strand)>,
+	/// True if the feature extends past its first nucleotide,
+	/// in the order of translation
+	partial_start: bool,
+	/// True if the feature extends past its last nucleotide,
+	/// in the order of translation
+	partial_end: bool,
+}
+
+/// A single segment of a [`FeatureLocation`], as read by
+/// [`LocationParser`].
+struct Segment {
+	range: Range<usize>,
+	strand: Strand,
+	/// True if the first nucleotide of the segment, as read
+	/// on its strand, is not the real start
+	partial_start: bool,
+	/// True if the last nucleotide of the segment, as read
+	/// on its strand, is not the real end
+	partial_end: bool,
+}
+
+impl Segment {
+	/// Returns the same segment, read from the other strand.
+	fn complement(self) -> Self {
+		Self {
+			strand: match self.strand {
+				Strand::Forward => Strand::Reverse,
+				Strand::Reverse => Strand::Forward,
+			},
+			partial_start: self.partial_end,
+			partial_end: self.partial_start,
+			..self
+		}
+	}
+}
+
+/// A recursive descent parser of the feature location syntax.
+struct LocationParser<'a> {
+	text: &'a str,
+}
+
+impl LocationParser<'_> {
+	/// Reads a location, i.e. a range, or a `complement`,
+	/// `join` or `order` of other locations.
+	fn location(&mut self) -> Option<Vec<Segment>> {
+		if self.eat("complement(") {
+			let segments = self.location()?;
+			self.expect(")")?;
+			return Some(
+				segments
+					.into_iter()
+					.rev()
+					.map(Segment::complement)
+					.collect(),
+			);
+		}
+		if self.eat("join(") || self.eat("order(") {
+			let mut segments = Vec::new();
+			loop {
+				segments.extend(self.location()?);
+				if !self.eat(",") {
+					break;
+				}
+			}
+			self.expect(")")?;
+			return Some(segments);
+		}
+		self.range()
+	}
+
+	/// Reads a range, e.g. `<1..>200`, or a single base. Sites
+	/// between two bases, e.g. `10^11`, hold no nucleotides.
+	fn range(&mut self) -> Option<Vec<Segment>> {
+		let partial_start = self.eat("<");
+		let start = self.number()?;
+		let (partial_end, end) = match (self.eat(".."), self.eat("^")) {
+			(true, _) => (self.eat(">"), self.number()?),
+			(_, true) => {
+				self.number()?;
+				return Some(Vec::new());
+			}
+			_ => (self.eat(">"), start),
+		};
+
+		if start == 0 || end < start {
+			return None;
+		}
+		Some(vec![Segment {
+			range: start - 1..end,
+			strand: Strand::Forward,
+			partial_start,
+			partial_end,
+		}])
+	}
+
+	/// Reads a 1-based coordinate.
+	fn number(&mut self) -> Option<usize> {
+		let len = self.text.bytes().take_while(u8::is_ascii_digit).count();
+		let (number, rest) = self.text.split_at(len);
+		self.text = rest;
+		number.parse().ok()
+	}
+
+	/// Skips a given token, returning true if it was present.
+	fn eat(&mut self, token: &str) -> bool {
+		match self.text.strip_prefix(token) {
+			Some(rest) => {
+				self.text = rest;
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Skips a given token, returning [`None`] if it was absent.
+	fn expect(&mut self, token: &str) -> Option<()> {
+		self.eat(token).then_some(())
+	}
+}
+
+impl FeatureLocation {
+	/// Parses a feature location, as written in the feature
+	/// table, e.g. `complement(join(<1..200,300..>450))`.
+	/// Whitespace is ignored.
+	///
+	/// Returns [`None`] for malformed locations and for those
+	/// referring to other entries, e.g. `J00194.1:100..202`.
+	pub fn parse(text: &str) -> Option<Self> {
+		let text: String = text.chars().filter(|x| !x.is_whitespace()).collect();
+		let mut parser = LocationParser { text: &text };
+		let segments = parser.location()?;
+		if !parser.text.is_empty() || segments.is_empty() {
+			return None;
+		}
+
+		Some(Self {
+			partial_start: segments.first().is_some_and(|x| x.partial_start),
+			partial_end: segments.last().is_some_and(|x| x.partial_end),
+			segments: segments.into_iter().map(|x| (x.range, x.strand)).collect(),
+		})
+	}
+
+	/// Returns the segments of this location, in the order of
+	/// translation. The ranges are 0-based offsets of the
+	/// forward strand.
+	pub fn get_segments(&self) -> &[(Range<usize>, Strand)] {
+		&self.segments
+	}
+
+	/// Returns the offset of the first nucleotide covered
+	/// by any of the segments, on the forward strand.
+	pub fn get_start(&self) -> usize {
+		self.segments.iter().map(|x| x.0.start).min().unwrap_or(0)
+	}
+
+	/// Returns the offset one past the last nucleotide covered
+	/// by any of the segments, on the forward strand.
+	pub fn get_end(&self) -> usize {
+		self.segments.iter().map(|x| x.0.end).max().unwrap_or(0)
+	}
+
+	/// Returns the [`Strand`] of the first segment.
+	pub fn get_strand(&self) -> Strand {
+		self.segments.first().map_or(Strand::Forward, |x| x.1)
+	}
+
+	/// Returns the total amount of nucleotides covered
+	/// by the segments.
+	pub fn len(&self) -> usize {
+		self.segments.iter().map(|x| x.0.len()).sum()
+	}
+
+	/// Returns true if the segments cover no nucleotides.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns true if the feature is known to start
+	/// before its first nucleotide, e.g. `<1..200`.
+	pub fn is_partial_start(&self) -> bool {
+		self.partial_start
+	}
+
+	/// Returns true if the feature is known to end
+	/// after its last nucleotide, e.g. `1..>200`.
+	pub fn is_partial_end(&self) -> bool {
+		self.partial_end
+	}
+
+	/// Returns the nucleotides covered by this location, read
+	/// in the order of translation from a given sequence.
+	///
+	/// Returns [`None`] if the location does not fit
+	/// in the sequence.
+	pub fn extract(&self, sequence: &NucleotideString) -> Option<NucleotideString> {
+		let mut result = NucleotideString::new(sequence.get_molecule());
+		for (range, strand) in &self.segments {
+			if range.end > sequence.len() {
+				return None;
+			}
+			let segment = sequence.slice(range.start, range.len());
+			match strand {
+				Strand::Forward => result.extend(segment.iter()),
+				Strand::Reverse => result.extend(segment.reverse_complement().iter()),
+			}
+		}
+		Some(result)
+	}
+
+	/// A helper function that formats a list of segments
+	/// in the given order, wrapping the ones read from the
+	/// reverse strand in `complement` if requested.
+	fn fmt_join<'a>(
+		&self,
+		f: &mut std::fmt::Formatter<'_>,
+		segments: impl ExactSizeIterator<Item = &'a (Range<usize>, Strand)>,
+		complement: bool,
+	) -> std::fmt::Result {
+		let join = segments.len() > 1;
+		if join {
+			f.write_str("join(")?;
+		}
+		for (index, (range, strand)) in segments.enumerate() {
+			if index > 0 {
+				f.write_str(",")?;
+			}
+			match complement && *strand == Strand::Reverse {
+				true => {
+					f.write_str("complement(")?;
+					self.fmt_range(f, range, *strand)?;
+					f.write_str(")")?;
+				}
+				false => self.fmt_range(f, range, *strand)?,
+			}
+		}
+		if join {
+			f.write_str(")")?;
+		}
+		Ok(())
+	}
+
+	/// A helper function that formats a single range,
+	/// marking the partial ends of the feature.
+	fn fmt_range(
+		&self,
+		f: &mut std::fmt::Formatter<'_>,
+		range: &Range<usize>,
+		strand: Strand,
+	) -> std::fmt::Result {
+		let first = self.segments.first().is_some_and(|x| x.0 == *range);
+		let last = self.segments.last().is_some_and(|x| x.0 == *range);
+		let (before, after) = match strand {
+			Strand::Forward => (first && self.partial_start, last && self.partial_end),
+			Strand::Reverse => (last && self.partial_end, first && self.partial_start),
+		};
+
+		if before {
+			f.write_str("<")?;
+		}
+		write!(f, "{}", range.start + 1)?;
+		if range.len() > 1 || after {
+			f.write_str("..")?;
+			if after {
+				f.write_str(">")?;
+			}
+			write!(f, "{}", range.end)?;
+		}
+		Ok(())
+	}
+}
+
+impl Display for FeatureLocation {
+	/// Formats the location in the feature table syntax,
+	/// e.g. `complement(join(1..10,20..30))`.
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.segments.iter().all(|x| x.1 == Strand::Reverse) {
+			true => {
+				f.write_str("complement(")?;
+				self.fmt_join(f, self.segments.iter().rev(), false)?;
+				f.write_str(")")
+			}
+			false => self.fmt_join(f, self.segments.iter(), true),
+		}
+	}
+}
+
+/// A single entry of the feature table of a GenBank or
+/// EMBL record, such as a gene or a coding sequence.
+#[derive(Clone)]
+pub struct Feature {
+	/// The feature key, e.g. `CDS`
+	kind: String,
+	/// The location, if it could be parsed
+	location: Option<FeatureLocation>,
+	/// Qualifier names, without the leading '/', along
+	/// with their unquoted values
+	qualifiers: Vec<(String, String)>,
+}
+
+impl Feature {
+	/// Constructs a [`Feature`] from its key, location
+	/// and qualifiers.
+	pub fn new(
+		kind: String,
+		location: Option<FeatureLocation>,
+		qualifiers: Vec<(String, String)>,
+	) -> Self {
+		Self {
+			kind,
+			location,
+			qualifiers,
+		}
+	}
+
+	/// Returns the feature key, e.g. `CDS` or `gene`.
+	pub fn get_kind(&self) -> &str {
+		&self.kind
+	}
+
+	/// Returns the location of this feature.
+	///
+	/// Returns [`None`] if the location is malformed, or
+	/// refers to other entries.
+	pub fn get_location(&self) -> Option<&FeatureLocation> {
+		self.location.as_ref()
+	}
+
+	/// Returns all the qualifiers of this feature, as
+	/// pairs of names and values.
+	pub fn get_qualifiers(&self) -> &[(String, String)] {
+		&self.qualifiers
+	}
+
+	/// Returns the value of the first qualifier with a given
+	/// name, e.g. `gene`. Qualifiers without a value, such
+	/// as `/pseudo`, have an empty one.
+	pub fn get_qualifier(&self, name: &str) -> Option<&str> {
+		self.qualifiers
+			.iter()
+			.find(|x| x.0 == name)
+			.map(|x| x.1.as_str())
+	}
+
+	/// Returns a short name of this feature, taken from the
+	/// first of its `gene`, `locus_tag`, `protein_id` or
+	/// `product` qualifiers that is present.
+	pub fn get_label(&self) -> Option<&str> {
+		["gene", "locus_tag", "protein_id", "product"]
+			.iter()
+			.find_map(|x| self.get_qualifier(x))
+	}
+
+	/// Returns true if this feature is a coding sequence
+	/// that is translated into a protein, i.e. a `CDS` not
+	/// marked as a pseudogene.
+	pub fn is_coding(&self) -> bool {
+		self.kind == "CDS"
+			&& self.get_qualifier("pseudo").is_none()
+			&& self.get_qualifier("pseudogene").is_none()
+	}
+
+	/// Translates this coding sequence, read from a given
+	/// sequence of its record, into a protein, along with its
+	/// [`Location`] within the record.
+	///
+	/// The `transl_table` and `codon_start` qualifiers are taken
+	/// into account, and a given [`GeneticCode`] is used if the
+	/// former is absent. As with ORFs predicted from the sequence,
+	/// neither the start codon of a complete feature nor the
+	/// terminal stop codon is translated, so that both yield
+	/// the same protein.
+	///
+	/// Returns [`None`] if the feature is not coding, its
+	/// location does not fit in the sequence, or it holds
+	/// no codons apart from the start and the stop codon.
+	pub fn translate(
+		&self,
+		sequence: &NucleotideString,
+		code: GeneticCode,
+	) -> Option<(AminoString, Location)> {
+		let location = self.location.as_ref().filter(|_| self.is_coding())?;
+		let code = self
+			.get_qualifier("transl_table")
+			.and_then(|x| x.parse().ok())
+			.and_then(GeneticCode::from_id)
+			.unwrap_or(code);
+		let skip: usize = match self.get_qualifier("codon_start") {
+			Some("2") => 1,
+			Some("3") => 2,
+			_ => 0,
+		};
+
+		let nucleotides = location.extract(sequence)?;
+		let mut triplets: Vec<_> = nucleotides
+			.codons(Frame::new(Strand::Forward, skip as u8))
+			.collect();
+		let stop_codon = match triplets.last() {
+			Some(&[a, b, c]) if code.translate_ambiguous(a, b, c) == Codon::STOP => triplets.pop(),
+			_ => None,
+		};
+		let &[a, b, c] = triplets.first()?;
+		let complete = skip == 0 && !location.is_partial_start();
+		let start_codon = (complete && code.is_start_ambiguous(a, b, c)).then_some([a, b, c]);
+
+		if start_codon.is_some() {
+			triplets.remove(0);
+		}
+		if triplets.is_empty() {
+			return None;
+		}
+
+		let codons = triplets
+			.iter()
+			.map(|&[a, b, c]| code.translate_ambiguous(a, b, c))
+			.collect();
+		let triplets = triplets
+			.iter()
+			.map(|&[a, b, c]| Triplet::resolve(a, b, c))
+			.collect();
+
+		let frame = match location.segments[0] {
+			(ref range, Strand::Forward) => {
+				Frame::new(Strand::Forward, ((range.start + skip) % 3) as u8)
+			}
+			(ref range, Strand::Reverse) => {
+				let skipped = sequence.len() - range.end + skip;
+				Frame::new(Strand::Reverse, (skipped % 3) as u8)
+			}
+		};
+		let result = Location::new(
+			location.get_start(),
+			location.get_end(),
+			frame,
+			start_codon,
+			stop_codon,
+		);
+		Some((AminoString::with_triplets(codons, triplets), result))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::IupacNucleotide;
+
+	/// A helper function that translates a CDS with a given
+	/// location and qualifiers from a given sequence, using
+	/// the standard genetic code unless overridden.
+	fn translate(
+		location: &str,
+		qualifiers: &[(&str, &str)],
+		sequence: &str,
+	) -> Option<(String, Location)> {
+		let qualifiers = qualifiers
+			.iter()
+			.map(|&(x, y)| (x.to_owned(), y.to_owned()));
+		let feature = Feature::new(
+			"CDS".to_owned(),
+			FeatureLocation::parse(location),
+			qualifiers.collect(),
+		);
+		let sequence = NucleotideString::parse(sequence).unwrap();
+		let (protein, location) = feature.translate(&sequence, GeneticCode::STANDARD)?;
+		Some((protein.to_string(), location))
+	}
+
+	/// A helper function that formats a codon.
+	fn codon(codon: Option<[IupacNucleotide; 3]>) -> Option<String> {
+		codon.map(|x| x.iter().map(ToString::to_string).collect())
+	}
+
+	#[test]
+	fn complete_features_skip_their_start_codon() {
+		let (protein, location) = translate("1..9", &[], "AUGGCUUAA").unwrap();
+		assert_eq!(protein, "A");
+		assert_eq!((location.get_start(), location.get_end()), (0, 9));
+		assert_eq!(codon(location.get_start_codon()).as_deref(), Some("AUG"));
+		assert_eq!(codon(location.get_stop_codon()).as_deref(), Some("UAA"));
+		assert!(translate("1..9", &[("pseudo", "")], "AUGGCUUAA").is_none());
+	}
+
+	#[test]
+	fn partial_features_translate_their_first_codon() {
+		let (protein, location) = translate("<1..9", &[], "AUGGCUUAA").unwrap();
+		assert_eq!(protein, "MA");
+		assert!(location.get_start_codon().is_none());
+		assert_eq!(codon(location.get_stop_codon()).as_deref(), Some("UAA"));
+
+		let (protein, location) = translate("<1..>6", &[], "GCUGCU").unwrap();
+		assert_eq!(protein, "AA");
+		assert!(location.get_start_codon().is_none() && location.get_stop_codon().is_none());
+	}
+
+	#[test]
+	fn codon_start_shifts_the_frame() {
+		let codon_start = [("codon_start", "2")];
+		let (protein, location) = translate("1..10", &codon_start, "CAUGGCUUAA").unwrap();
+		// The first codon of a shifted feature is never a start codon
+		assert_eq!(protein, "MA");
+		assert!(location.get_start_codon().is_none());
+		assert_eq!((location.get_start(), location.get_end()), (0, 10));
+		assert!(location.get_frame() == Frame::new(Strand::Forward, 1));
+	}
+
+	#[test]
+	fn complement_joins_are_read_backwards() {
+		// AUG GCU UUU UAA, with an intron of 5 nucleotides,
+		// on the reverse strand
+		let sequence = "UUAAAAGGGGGAGCCAUCC";
+		let location = "complement(join(1..6,12..17))";
+		let (protein, location) = translate(location, &[], sequence).unwrap();
+		assert_eq!(protein, "AF");
+		assert_eq!((location.get_start(), location.get_end()), (0, 17));
+		assert_eq!(location.to_string(), "complement(1..17)");
+		assert!(location.get_frame() == Frame::new(Strand::Reverse, 2));
+		assert_eq!(codon(location.get_start_codon()).as_deref(), Some("AUG"));
+		assert_eq!(codon(location.get_stop_codon()).as_deref(), Some("UAA"));
+	}
+
+	#[test]
+	fn transl_table_overrides_the_genetic_code() {
+		// UGA codes tryptophan and AGA is a stop codon
+		// in the vertebrate mitochondrial code
+		let (protein, location) = translate("1..9", &[("transl_table", "2")], "AUGUGAAGA").unwrap();
+		assert_eq!(protein, "W");
+		assert_eq!(codon(location.get_stop_codon()).as_deref(), Some("AGA"));
+
+		let (protein, location) = translate("1..9", &[], "AUGUGAAGA").unwrap();
+		assert_eq!(protein, "_R");
+		assert!(location.get_stop_codon().is_none());
+	}
+}
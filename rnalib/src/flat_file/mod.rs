@@ -0,0 +1,126 @@
+//! The module that implements [`FlatFileRecord`]
+
+mod feature;
+pub use feature::*;
+
+mod reader;
+pub use reader::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AminoString, GeneticCode, Location, NucleotideString, RecordHeader};
+
+/// The annotated flat file formats of the sequence databases.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlatFileFormat {
+	/// The GenBank format used by NCBI, with the sequence
+	/// following an `ORIGIN` line
+	GenBank,
+	/// The format used by ENA, with two-letter line codes
+	/// and the sequence following an `SQ` line
+	Embl,
+}
+
+/// A single entry of a GenBank or EMBL file, i.e. a
+/// nucleotide sequence along with its feature table.
+#[derive(Default, Clone)]
+pub struct FlatFileRecord {
+	header: RecordHeader,
+	sequence: NucleotideString,
+	features: Vec<Feature>,
+}
+
+impl FlatFileRecord {
+	/// Constructs a [`FlatFileRecord`] from its header,
+	/// sequence, and features.
+	pub fn new(header: RecordHeader, sequence: NucleotideString, features: Vec<Feature>) -> Self {
+		Self {
+			header,
+			sequence,
+			features,
+		}
+	}
+
+	/// Returns the header of this record. The identifier is the
+	/// versioned accession if present, otherwise the entry name,
+	/// and the description is the definition line.
+	pub fn get_header(&self) -> &RecordHeader {
+		&self.header
+	}
+
+	/// Returns the sequence of this record.
+	pub fn get_sequence(&self) -> &NucleotideString {
+		&self.sequence
+	}
+
+	/// Takes ownership of the sequence of this record.
+	pub fn into_sequence(self) -> NucleotideString {
+		self.sequence
+	}
+
+	/// Returns the features of this record, in the order
+	/// of the feature table.
+	pub fn get_features(&self) -> &[Feature] {
+		&self.features
+	}
+
+	/// Returns an iterator over the proteins encoded by the
+	/// coding sequences of this record, along with the features
+	/// they were translated from and their locations.
+	///
+	/// See [`Feature::translate`] for how a [`GeneticCode`]
+	/// is picked.
+	pub fn translate(
+		&self,
+		code: GeneticCode,
+	) -> impl Iterator<Item = (&Feature, AminoString, Location)> {
+		self.features.iter().filter_map(move |feature| {
+			let (string, location) = feature.translate(&self.sequence, code)?;
+			Some((feature, string, location))
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+
+	use crate::{ProteinMap, ThreadedProteinLoader};
+
+	use super::*;
+
+	/// A GenBank entry with a single coding sequence,
+	/// which is an ORF predicted from the sequence as well.
+	const GENBANK: &str = "\
+LOCUS       TEST1    19 bp    DNA     linear   BCT 01-JAN-2020
+DEFINITION  Test sequence.
+ACCESSION   TEST1
+VERSION     TEST1.1
+FEATURES             Location/Qualifiers
+     source          1..19
+     CDS             3..17
+                     /product=\"test protein\"
+ORIGIN
+        1 ggatgaaacc cttttaagg
+//
+";
+
+	/// A helper function that loads proteins from a flat file.
+	fn load(text: &str, format: FlatFileFormat) -> ProteinMap {
+		let mut loader = ThreadedProteinLoader::default();
+		let source = Cursor::new(text.to_owned());
+		loader.start_flat_file(source, format, text.len() as u64);
+		loader.wait().unwrap().ok().unwrap()
+	}
+
+	#[test]
+	fn annotated_and_predicted_proteins_merge() {
+		let map = load(GENBANK, FlatFileFormat::GenBank);
+		assert_eq!(map.len(), 1);
+		let (_, protein) = map.iter().next().unwrap();
+		assert_eq!(protein.to_string(), "KPF");
+		let annotated = protein.get_locations().iter().filter(|x| x.is_annotated());
+		assert_eq!(annotated.count(), 1);
+		assert_eq!(protein.get_locations().len(), 2);
+	}
+}
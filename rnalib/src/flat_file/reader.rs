@@ -0,0 +1,401 @@
+//! The module that implements [`FlatFileReader`]
+
+use std::io::{BufRead, ErrorKind};
+
+use crate::{nucleotide_string::parser::SequenceParser, LoadError, NucleotideString, RecordHeader};
+
+use super::{Feature, FeatureLocation, FlatFileFormat, FlatFileRecord};
+
+/// The section of a record the current line belongs to.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum Section {
+	#[default]
+	Other,
+	Definition,
+	Features,
+	Sequence,
+}
+
+/// Assembles the features of a record from the lines
+/// of its feature table.
+///
+/// Lines are expected in the GenBank layout, with keys
+/// starting at the 6th column, and locations and qualifiers
+/// at the 22nd. EMBL lines are the same past their line code.
+#[derive(Default)]
+struct FeatureTable {
+	features: Vec<Feature>,
+	/// The key of the feature being read, if any
+	kind: Option<String>,
+	/// The location of the feature being read, which
+	/// may span multiple lines
+	location: String,
+	/// The qualifiers of the feature being read
+	qualifiers: Vec<(String, String)>,
+}
+
+impl FeatureTable {
+	/// Reads a single line of the feature table.
+	fn push_line(&mut self, line: &str) {
+		let content = line.trim();
+		if line
+			.as_bytes()
+			.get(5)
+			.is_some_and(|x| !x.is_ascii_whitespace())
+		{
+			self.finish_feature();
+			let (kind, location) = content
+				.split_once(char::is_whitespace)
+				.unwrap_or((content, ""));
+			self.kind = Some(kind.into());
+			self.location.push_str(location.trim());
+		} else if let Some(qualifier) = content.strip_prefix('/') {
+			let (name, value) = qualifier.split_once('=').unwrap_or((qualifier, ""));
+			self.qualifiers.push((name.into(), value.into()));
+		} else if let Some((name, value)) = self.qualifiers.last_mut() {
+			// Sequences of amino acids are wrapped without spaces
+			if name != "translation" {
+				value.push(' ');
+			}
+			value.push_str(content);
+		} else {
+			self.location.push_str(content);
+		}
+	}
+
+	/// Stores the feature being read, if any.
+	fn finish_feature(&mut self) {
+		let location = std::mem::take(&mut self.location);
+		let mut qualifiers = std::mem::take(&mut self.qualifiers);
+		let Some(kind) = self.kind.take() else { return };
+
+		for (_, value) in &mut qualifiers {
+			let unquoted = match value.strip_prefix('"') {
+				Some(x) => x.strip_suffix('"').unwrap_or(x).replace("\"\"", "\""),
+				None => value.clone(),
+			};
+			*value = unquoted;
+		}
+		let location = FeatureLocation::parse(&location);
+		self.features.push(Feature::new(kind, location, qualifiers));
+	}
+
+	/// Returns all the features read.
+	fn finish(mut self) -> Vec<Feature> {
+		self.finish_feature();
+		self.features
+	}
+}
+
+/// A record of a flat file being read.
+#[derive(Default)]
+struct RecordBuilder {
+	/// True once any line of the record has been read
+	started: bool,
+	section: Section,
+	/// The entry name, e.g. from the `LOCUS` line
+	name: String,
+	/// The versioned accession, if present
+	version: Option<String>,
+	description: String,
+	features: FeatureTable,
+	sequence: NucleotideString,
+}
+
+impl RecordBuilder {
+	/// Appends a line of the definition to the description.
+	fn describe(&mut self, text: &str) {
+		if !self.description.is_empty() {
+			self.description.push(' ');
+		}
+		self.description.push_str(text.trim());
+	}
+
+	/// Finishes reading the record.
+	fn finish(self) -> FlatFileRecord {
+		let id = self.version.unwrap_or(self.name);
+		let description = self
+			.description
+			.strip_suffix('.')
+			.unwrap_or(&self.description);
+		let header = RecordHeader::new(id, description.into());
+		FlatFileRecord::new(header, self.sequence, self.features.finish())
+	}
+}
+
+/// An iterator over the records of a GenBank or EMBL file,
+/// which reads them one by one from any [`BufRead`] source.
+///
+/// Only the identifiers, definitions, feature tables and
+/// sequences are read, the other sections are skipped. Every
+/// record is held in memory as a whole.
+///
+/// Yields a [`LoadError`] pointing at the first character
+/// of a sequence that is not a nucleotide, then stops.
+pub struct FlatFileReader<R> {
+	reader: R,
+	format: FlatFileFormat,
+	/// Parser of the sequence lines, which also keeps
+	/// track of the position in the source
+	parser: SequenceParser,
+	/// The line being read
+	line: Vec<u8>,
+	/// Amount of bytes read from the source
+	offset: u64,
+	finished: bool,
+}
+
+impl<R: BufRead> FlatFileReader<R> {
+	/// Constructs a [`FlatFileReader`] reading a given
+	/// format from a given source.
+	pub fn new(reader: R, format: FlatFileFormat) -> Self {
+		Self {
+			reader,
+			format,
+			parser: SequenceParser::numbered(),
+			line: Vec::new(),
+			offset: 0,
+			finished: false,
+		}
+	}
+
+	/// Returns the amount of bytes read from the source so far,
+	/// i.e. up to the end of the last record returned.
+	pub fn get_offset(&self) -> u64 {
+		self.offset
+	}
+
+	/// Returns a reference to the underlying source.
+	pub fn get_ref(&self) -> &R {
+		&self.reader
+	}
+
+	/// A helper function that reads a single line into a given
+	/// record.
+	///
+	/// Returns true if the line ended the record.
+	fn read_line(&mut self, record: &mut RecordBuilder) -> Result<bool, LoadError> {
+		let text = String::from_utf8_lossy(&self.line);
+		let text = text.trim_end();
+		if text.starts_with("//") {
+			self.parser.skip(&self.line);
+			return Ok(true);
+		}
+		record.started |= !text.is_empty();
+
+		let sequence = match self.format {
+			FlatFileFormat::GenBank => Self::read_genbank(text, record),
+			FlatFileFormat::Embl => Self::read_embl(text, record),
+		};
+		match sequence {
+			true => self
+				.parser
+				.parse(&self.line, &mut record.sequence)
+				.map(|_| false),
+			false => {
+				self.parser.skip(&self.line);
+				Ok(false)
+			}
+		}
+	}
+
+	/// A helper function that reads a single line of a GenBank
+	/// record, returning true if it holds nucleotides.
+	fn read_genbank(line: &str, record: &mut RecordBuilder) -> bool {
+		if line.starts_with(char::is_whitespace) {
+			match record.section {
+				Section::Definition => record.describe(line),
+				Section::Features => record.features.push_line(line),
+				Section::Sequence => return true,
+				Section::Other => {}
+			}
+			return false;
+		}
+
+		let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+		let value = rest.split_whitespace().next().unwrap_or_default();
+		record.section = match keyword {
+			"LOCUS" => {
+				record.name = value.into();
+				Section::Other
+			}
+			"DEFINITION" => {
+				record.describe(rest);
+				Section::Definition
+			}
+			"VERSION" => {
+				record.version = (!value.is_empty()).then(|| value.into());
+				Section::Other
+			}
+			"FEATURES" => Section::Features,
+			"ORIGIN" => Section::Sequence,
+			_ => Section::Other,
+		};
+		false
+	}
+
+	/// A helper function that reads a single line of an EMBL
+	/// record, returning true if it holds nucleotides.
+	fn read_embl(line: &str, record: &mut RecordBuilder) -> bool {
+		let code = line.get(..2).unwrap_or(line);
+		let rest = line.get(2..).unwrap_or_default();
+		match code {
+			"ID" => {
+				// e.g. "ID   X56734; SV 1; linear; mRNA; STD; PLN; 1859 BP."
+				let mut fields = rest.split(';').map(str::trim);
+				record.name = fields.next().unwrap_or_default().into();
+				if let Some(version) = fields.next().and_then(|x| x.strip_prefix("SV ")) {
+					record.version = Some(format!("{}.{}", record.name, version.trim()));
+				}
+			}
+			"DE" => record.describe(rest),
+			"FT" => record.features.push_line(&format!("  {rest}")),
+			"SQ" => record.section = Section::Sequence,
+			"  " => return record.section == Section::Sequence,
+			_ => {}
+		}
+		false
+	}
+}
+
+impl<R: BufRead> Iterator for FlatFileReader<R> {
+	type Item = Result<FlatFileRecord, LoadError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let mut record = RecordBuilder::default();
+		while !self.finished {
+			self.line.clear();
+			let len = match self.reader.read_until(b'\n', &mut self.line) {
+				Ok(x) => x,
+				Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+				Err(err) => {
+					self.finished = true;
+					return Some(Err(err.into()));
+				}
+			};
+			if len == 0 {
+				self.finished = true;
+				break;
+			}
+			self.offset += len as u64;
+
+			match self.read_line(&mut record) {
+				Ok(true) if record.started => return Some(Ok(record.finish())),
+				Ok(_) => {}
+				Err(err) => {
+					self.finished = true;
+					return Some(Err(err));
+				}
+			}
+		}
+		record.started.then(|| Ok(record.finish()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const GENBANK: &str = "\
+LOCUS       FIRST    8 bp    DNA     linear   BCT 01-JAN-2020
+DEFINITION  First
+            record.
+VERSION     FIRST.2
+FEATURES             Location/Qualifiers
+     gene            complement(1..
+                     8)
+                     /gene=\"abc\"
+ORIGIN
+        1 acgtacgt
+//
+LOCUS       SECOND   4 bp    RNA     linear   BCT 01-JAN-2020
+ORIGIN
+        1 acgu
+//
+LOCUS       THIRD    5 bp    DNA     linear   BCT 01-JAN-2020
+ORIGIN
+        1 acgtn nnxgt
+//
+";
+
+	const EMBL: &str = "\
+ID   X56734; SV 3; linear; mRNA; STD; PLN; 8 BP.
+DE   An EMBL
+DE   record.
+FT   CDS             1..6
+FT                   /product=\"test protein\"
+SQ   Sequence 8 BP;
+     acgtacgt                                                           8
+//
+ID   X56735; linear; mRNA; STD; PLN; 4 BP.
+SQ   Sequence 4 BP;
+     ac gu                                                              4
+";
+
+	/// A helper function that returns the error position of
+	/// a character, as the reader should report it.
+	fn locate(text: &str, character: char) -> (char, u64, u64, u64) {
+		let offset = text.find(character).unwrap();
+		let line_start = text[..offset].rfind('\n').map_or(0, |x| x + 1);
+		let line = text[..offset].matches('\n').count() + 1;
+		let column = offset - line_start + 1;
+		(character, offset as u64, line as u64, column as u64)
+	}
+
+	#[test]
+	fn genbank_records_end_at_slashes() {
+		let mut reader = FlatFileReader::new(GENBANK.as_bytes(), FlatFileFormat::GenBank);
+		let first = reader.next().unwrap().unwrap();
+		let second_start = GENBANK.find("LOCUS       SECOND").unwrap();
+		assert_eq!(reader.get_offset(), second_start as u64);
+		assert_eq!(first.get_header().to_string(), "FIRST.2 First record");
+		assert_eq!(first.get_sequence().to_string(), "ACGTACGT");
+		let gene = &first.get_features()[0];
+		assert_eq!(gene.get_kind(), "gene");
+		assert_eq!(gene.get_qualifier("gene"), Some("abc"));
+		assert_eq!(gene.get_location().unwrap().to_string(), "complement(1..8)");
+
+		let second = reader.next().unwrap().unwrap();
+		assert_eq!(second.get_header().to_string(), "SECOND");
+		assert_eq!(second.get_sequence().to_string(), "ACGU");
+		assert!(second.get_features().is_empty());
+
+		match reader.next() {
+			Some(Err(LoadError::InvalidCharacter {
+				character,
+				offset,
+				line,
+				column,
+			})) => assert_eq!((character, offset, line, column), locate(GENBANK, 'x')),
+			_ => panic!("expected an invalid character"),
+		}
+		assert!(reader.next().is_none());
+	}
+
+	#[test]
+	fn embl_records_end_at_slashes_or_the_end() {
+		let records = FlatFileReader::new(EMBL.as_bytes(), FlatFileFormat::Embl);
+		let records: Vec<_> = records.map(Result::unwrap).collect();
+		assert_eq!(records.len(), 2);
+		let header = records[0].get_header();
+		assert_eq!(header.to_string(), "X56734.3 An EMBL record");
+		assert_eq!(records[0].get_sequence().to_string(), "ACGTACGT");
+		let cds = &records[0].get_features()[0];
+		assert_eq!(cds.get_qualifier("product"), Some("test protein"));
+		assert_eq!(records[1].get_header().to_string(), "X56735");
+		assert_eq!(records[1].get_sequence().to_string(), "ACGU");
+
+		let text = EMBL.replace("ac gu", "ac gu!");
+		let mut records = FlatFileReader::new(text.as_bytes(), FlatFileFormat::Embl);
+		assert!(records.next().unwrap().is_ok());
+		match records.next() {
+			Some(Err(LoadError::InvalidCharacter {
+				character,
+				offset,
+				line,
+				column,
+			})) => assert_eq!((character, offset, line, column), locate(&text, '!')),
+			_ => panic!("expected an invalid character"),
+		}
+	}
+}
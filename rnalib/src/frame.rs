@@ -0,0 +1,82 @@
+//! The module that implements [`Frame`] and [`Strand`]
+
+use std::fmt::{Display, Write};
+
+/// Represents one of the two strands of a
+/// double-stranded nucleotide sequence.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Strand {
+	/// The strand given as the input
+	Forward,
+	/// The reverse complement of the input
+	Reverse,
+}
+
+impl Strand {
+	/// Returns the sign used to denote this strand,
+	/// i.e. '+' for [`Strand::Forward`] and '-' for
+	/// [`Strand::Reverse`].
+	pub const fn get_sign(&self) -> char {
+		match self {
+			Strand::Forward => '+',
+			Strand::Reverse => '-',
+		}
+	}
+}
+
+impl Display for Strand {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_char(self.get_sign())
+	}
+}
+
+/// Represents one of the six reading frames of
+/// a nucleotide sequence.
+///
+/// Frames are displayed in the conventional notation,
+/// from `+1` to `+3` for the forward strand and from
+/// `-1` to `-3` for the reverse strand. Frame `-1`
+/// starts at the last nucleotide of the input.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Frame {
+	strand: Strand,
+	offset: u8,
+}
+
+impl Frame {
+	/// Constructs a [`Frame`] from a strand and an
+	/// offset in range from 0 to 2.
+	pub const fn new(strand: Strand, offset: u8) -> Self {
+		Self {
+			strand,
+			offset: offset % 3,
+		}
+	}
+
+	/// Returns the strand this frame is read from.
+	pub const fn get_strand(&self) -> Strand {
+		self.strand
+	}
+
+	/// Returns the amount of nucleotides skipped on
+	/// this frame's strand before reading the first codon.
+	pub const fn get_offset(&self) -> u8 {
+		self.offset
+	}
+
+	/// All six reading frames, forward frames first.
+	pub const ALL: [Frame; 6] = [
+		Frame::new(Strand::Forward, 0),
+		Frame::new(Strand::Forward, 1),
+		Frame::new(Strand::Forward, 2),
+		Frame::new(Strand::Reverse, 0),
+		Frame::new(Strand::Reverse, 1),
+		Frame::new(Strand::Reverse, 2),
+	];
+}
+
+impl Display for Frame {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}{}", self.strand, self.offset + 1)
+	}
+}
@@ -0,0 +1,334 @@
+//! The module that implements [`GeneticCode`]
+
+use std::fmt::{Display, Formatter, Result};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Codon, IupacNucleotide, Nucleotide, Triplet};
+
+/// Represents one of the NCBI translation tables, which
+/// define how codons are translated into amino acids, and
+/// which of them can start or end a protein.
+///
+/// See the [NCBI reference](https://www.ncbi.nlm.nih.gov/Taxonomy/Utils/wprintgc.cgi)
+/// for a description of every table.
+#[derive(Clone, Copy)]
+pub struct GeneticCode {
+	/// The NCBI `transl_table` identifier.
+	id: u8,
+	/// A human readable name of the table.
+	name: &'static str,
+	/// Amino acid shorthands of all 64 codons, in
+	/// the NCBI (TCAG) order. '*' denotes a STOP codon.
+	acids: &'static [u8; 64],
+	/// Start and stop markers of all 64 codons, in
+	/// the NCBI (TCAG) order. 'M' denotes a START codon,
+	/// '*' denotes a STOP codon.
+	starts: &'static [u8; 64],
+}
+
+impl GeneticCode {
+	/// Creates a constant translation table from the NCBI
+	/// encoded amino acid and start strings.
+	const fn new(
+		id: u8,
+		name: &'static str,
+		acids: &'static [u8; 64],
+		starts: &'static [u8; 64],
+	) -> Self {
+		Self {
+			id,
+			name,
+			acids,
+			starts,
+		}
+	}
+
+	/// Returns the NCBI `transl_table` identifier of this table.
+	pub const fn get_id(&self) -> u8 {
+		self.id
+	}
+
+	/// Returns the human readable name of this table.
+	pub const fn get_name(&self) -> &'static str {
+		self.name
+	}
+
+	/// Translates three sequential instances of [`Nucleotide`]
+	/// into a [`Codon`], according to this table.
+	///
+	/// For [`GeneticCode::STANDARD`] this is the same as
+	/// [`Codon::new`].
+	pub const fn translate(&self, a: Nucleotide, b: Nucleotide, c: Nucleotide) -> Codon {
+		if self.is_stop(a, b, c) {
+			return Codon::STOP;
+		}
+		match Codon::from_shorthand(self.acids[Self::index(a, b, c)] as char) {
+			Some(codon) => codon,
+			None => Codon::STOP,
+		}
+	}
+
+	/// Returns true if the given triplet can initiate
+	/// translation according to this table.
+	pub const fn is_start(&self, a: Nucleotide, b: Nucleotide, c: Nucleotide) -> bool {
+		self.starts[Self::index(a, b, c)] == b'M'
+	}
+
+	/// Returns true if the given triplet terminates
+	/// translation according to this table.
+	///
+	/// Tables with context dependent terminators (27, 28 and 31)
+	/// treat those codons as STOP codons.
+	pub const fn is_stop(&self, a: Nucleotide, b: Nucleotide, c: Nucleotide) -> bool {
+		let index = Self::index(a, b, c);
+		self.acids[index] == b'*' || self.starts[index] == b'*'
+	}
+
+	/// Translates three sequential instances of [`IupacNucleotide`]
+	/// into a [`Codon`], according to this table.
+	///
+	/// The triplet is resolved to an amino acid (or a STOP) only
+	/// if every possible expansion of it translates to the same
+	/// [`Codon`]. Returns [`Codon::X`] otherwise.
+	pub fn translate_ambiguous(
+		&self,
+		a: IupacNucleotide,
+		b: IupacNucleotide,
+		c: IupacNucleotide,
+	) -> Codon {
+		let mut result = None;
+		for &a in a.expand() {
+			for &b in b.expand() {
+				for &c in c.expand() {
+					let codon = self.translate(a, b, c);
+					match result {
+						None => result = Some(codon),
+						Some(x) if x != codon => return Codon::X,
+						_ => (),
+					}
+				}
+			}
+		}
+		result.unwrap_or(Codon::X)
+	}
+
+	/// Returns true if every possible expansion of the given
+	/// triplet can initiate translation according to this table.
+	pub fn is_start_ambiguous(
+		&self,
+		a: IupacNucleotide,
+		b: IupacNucleotide,
+		c: IupacNucleotide,
+	) -> bool {
+		let mut result = false;
+		for &a in a.expand() {
+			for &b in b.expand() {
+				for &c in c.expand() {
+					if !self.is_start(a, b, c) {
+						return false;
+					}
+					result = true;
+				}
+			}
+		}
+		result
+	}
+
+	/// Returns a [`GeneticCode`] given its NCBI `transl_table`
+	/// identifier.
+	///
+	/// Returns [`None`] if no such table exists.
+	pub fn from_id(id: u8) -> Option<Self> {
+		Self::ALL.iter().copied().find(|x| x.id == id)
+	}
+
+	/// Returns the index of a triplet in the NCBI (TCAG)
+	/// ordered tables.
+	const fn index(a: Nucleotide, b: Nucleotide, c: Nucleotide) -> usize {
+		Triplet::new(a, b, c).get_index()
+	}
+}
+
+impl Default for GeneticCode {
+	fn default() -> Self {
+		Self::STANDARD
+	}
+}
+
+impl PartialEq for GeneticCode {
+	fn eq(&self, other: &Self) -> bool {
+		self.id == other.id
+	}
+}
+
+impl Eq for GeneticCode {}
+
+impl Display for GeneticCode {
+	fn fmt(&self, f: &mut Formatter) -> Result {
+		write!(f, "{}. {}", self.id, self.name)
+	}
+}
+
+/// Tables are stored as their NCBI `transl_table` identifiers.
+impl Serialize for GeneticCode {
+	fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+		serializer.serialize_u8(self.id)
+	}
+}
+
+impl<'de> Deserialize<'de> for GeneticCode {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+		let id = u8::deserialize(deserializer)?;
+		Self::from_id(id).ok_or_else(|| D::Error::custom(format!("unknown genetic code: {id}")))
+	}
+}
+
+/// This macro constructs the list of constant
+/// NCBI translation tables.
+///
+/// Much like `acid_table!`, it allows to keep
+/// the tables in the same tabular format NCBI
+/// publishes them in.
+macro_rules! genetic_code_table {
+	( $( $id:ident, $number:expr, $name:expr, $acids:expr, $starts:expr )* ) => {
+		impl GeneticCode {
+			$(pub const $id: GeneticCode = GeneticCode::new($number, $name, $acids, $starts);)*
+
+			/// Every translation table defined by NCBI,
+			/// ordered by identifier.
+			pub const ALL: &'static [GeneticCode] = &[$(Self::$id),*];
+		}
+	};
+}
+
+//	Constant						Id	Name
+//		Amino acids (TCAG order)
+//		Starts (TCAG order)
+#[rustfmt::skip]
+genetic_code_table!(
+	STANDARD,						1,	"Standard",
+		b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"---M------**--*----M---------------M----------------------------"
+	VERTEBRATE_MITOCHONDRIAL,		2,	"Vertebrate Mitochondrial",
+		b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+		b"----------**--------------------MMMM----------**---M------------"
+	YEAST_MITOCHONDRIAL,			3,	"Yeast Mitochondrial",
+		b"FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"----------**----------------------MM---------------M------------"
+	MOLD_MITOCHONDRIAL,				4,	"Mold, Protozoan, Coelenterate Mitochondrial and Mycoplasma/Spiroplasma",
+		b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"--MM------**-------M------------MMMM---------------M------------"
+	INVERTEBRATE_MITOCHONDRIAL,		5,	"Invertebrate Mitochondrial",
+		b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+		b"---M------**--------------------MMMM---------------M------------"
+	CILIATE_NUCLEAR,				6,	"Ciliate, Dasycladacean and Hexamita Nuclear",
+		b"FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"--------------*--------------------M----------------------------"
+	ECHINODERM_MITOCHONDRIAL,		9,	"Echinoderm and Flatworm Mitochondrial",
+		b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+		b"----------**-----------------------M---------------M------------"
+	EUPLOTID_NUCLEAR,				10,	"Euplotid Nuclear",
+		b"FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"----------**-----------------------M----------------------------"
+	BACTERIAL_PLASTID,				11,	"Bacterial, Archaeal and Plant Plastid",
+		b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"---M------**--*----M------------MMMM---------------M------------"
+	ALTERNATIVE_YEAST_NUCLEAR,		12,	"Alternative Yeast Nuclear",
+		b"FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"----------**--*----M---------------M----------------------------"
+	ASCIDIAN_MITOCHONDRIAL,			13,	"Ascidian Mitochondrial",
+		b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+		b"---M------**----------------------MM---------------M------------"
+	ALTERNATIVE_FLATWORM_MITOCHONDRIAL,	14,	"Alternative Flatworm Mitochondrial",
+		b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+		b"-----------*-----------------------M----------------------------"
+	BLEPHARISMA_NUCLEAR,			15,	"Blepharisma Nuclear",
+		b"FFLLSSSSYY*QCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"----------*---*--------------------M----------------------------"
+	CHLOROPHYCEAN_MITOCHONDRIAL,	16,	"Chlorophycean Mitochondrial",
+		b"FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"----------*---*--------------------M----------------------------"
+	TREMATODE_MITOCHONDRIAL,		21,	"Trematode Mitochondrial",
+		b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+		b"----------**-----------------------M---------------M------------"
+	SCENEDESMUS_MITOCHONDRIAL,		22,	"Scenedesmus obliquus Mitochondrial",
+		b"FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"------*---*---*--------------------M----------------------------"
+	THRAUSTOCHYTRIUM_MITOCHONDRIAL,	23,	"Thraustochytrium Mitochondrial",
+		b"FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"--*-------**--*-----------------M--M---------------M------------"
+	RHABDOPLEURIDAE_MITOCHONDRIAL,	24,	"Rhabdopleuridae Mitochondrial",
+		b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+		b"---M------**-------M---------------M---------------M------------"
+	SR1_GRACILIBACTERIA,			25,	"Candidate Division SR1 and Gracilibacteria",
+		b"FFLLSSSSYY**CCGWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"---M------**-----------------------M---------------M------------"
+	PACHYSOLEN_NUCLEAR,				26,	"Pachysolen tannophilus Nuclear",
+		b"FFLLSSSSYY**CC*WLLLAPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"----------**--*----M---------------M----------------------------"
+	KARYORELICT_NUCLEAR,			27,	"Karyorelict Nuclear",
+		b"FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"--------------*--------------------M----------------------------"
+	CONDYLOSTOMA_NUCLEAR,			28,	"Condylostoma Nuclear",
+		b"FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"----------**--*--------------------M----------------------------"
+	MESODINIUM_NUCLEAR,				29,	"Mesodinium Nuclear",
+		b"FFLLSSSSYYYYCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"--------------*--------------------M----------------------------"
+	PERITRICH_NUCLEAR,				30,	"Peritrich Nuclear",
+		b"FFLLSSSSYYEECC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"--------------*--------------------M----------------------------"
+	BLASTOCRITHIDIA_NUCLEAR,		31,	"Blastocrithidia Nuclear",
+		b"FFLLSSSSYYEECCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"----------**-----------------------M----------------------------"
+	BALANOPHORACEAE_PLASTID,		32,	"Balanophoraceae Plastid",
+		b"FFLLSSSSYY*WCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+		b"---M------*---*----M------------MMMM---------------M------------"
+	CEPHALODISCIDAE_MITOCHONDRIAL,	33,	"Cephalodiscidae Mitochondrial",
+		b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+		b"---M-------*-------M---------------M---------------M------------"
+);
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use Nucleotide::*;
+
+	/// A helper function that lists the start codons
+	/// of a table, in the NCBI (TCAG) order.
+	fn starts(code: GeneticCode) -> Vec<String> {
+		let mut result = Vec::new();
+		for a in [U, C, A, G] {
+			for b in [U, C, A, G] {
+				for c in [U, C, A, G] {
+					if code.is_start(a, b, c) {
+						result.push(format!("{a}{b}{c}"));
+					}
+				}
+			}
+		}
+		result
+	}
+
+	#[test]
+	fn tables_have_their_own_starts() {
+		let bacterial = GeneticCode::from_id(11).unwrap();
+		assert!(bacterial == GeneticCode::BACTERIAL_PLASTID);
+		assert_eq!(starts(GeneticCode::STANDARD), ["UUG", "CUG", "AUG"]);
+		assert_eq!(
+			starts(bacterial),
+			["UUG", "CUG", "AUU", "AUC", "AUA", "AUG", "GUG"]
+		);
+		assert!(GeneticCode::from_id(7).is_none());
+	}
+
+	#[test]
+	fn tables_have_their_own_stops() {
+		let mitochondrial = GeneticCode::VERTEBRATE_MITOCHONDRIAL;
+		assert!(GeneticCode::STANDARD.translate(U, G, A) == Codon::STOP);
+		assert!(mitochondrial.translate(U, G, A) == Codon::from_shorthand('W').unwrap());
+		assert!(GeneticCode::STANDARD.translate(A, G, A) == Codon::from_shorthand('R').unwrap());
+		assert!(mitochondrial.is_stop(A, G, A));
+	}
+}
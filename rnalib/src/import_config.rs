@@ -0,0 +1,170 @@
+//! The module that implements [`ImportConfig`]
+
+use std::io::{self, BufReader, Read};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	FlatFileFormat, LoadError, OrfOptions, Preprocessor, PreprocessorConfig, QualityEncoding,
+	QualityFilter, ThreadedProteinLoader,
+};
+
+/// The format of the imported data.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SourceFormat {
+	/// A raw sequence or FASTA records, which
+	/// pass through the [`Preprocessor`]
+	#[default]
+	Sequence,
+	/// An annotated GenBank or EMBL file, whose coding
+	/// sequences are imported along with the ORFs found
+	FlatFile(FlatFileFormat),
+	/// Raw reads with qualities, which are filtered
+	/// before they are scanned
+	Fastq,
+}
+
+/// Describes how a source is parsed, pre-processed and
+/// searched for proteins, regardless of where it comes from.
+///
+/// The configuration can be stored and shared, so that every
+/// tool imports a given source in exactly the same way.
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportConfig {
+	pub format: SourceFormat,
+	/// Used for [`SourceFormat::Sequence`] only
+	pub preprocessing: PreprocessorConfig,
+	/// Used for [`SourceFormat::Fastq`] only
+	pub quality_encoding: QualityEncoding,
+	/// Used for [`SourceFormat::Fastq`] only
+	pub quality_filter: QualityFilter,
+	pub orf_options: OrfOptions,
+	/// Amount of threads scanning the source,
+	/// 0 for one per core
+	pub threads: usize,
+}
+
+impl ImportConfig {
+	/// Constructs a [`ThreadedProteinLoader`] that searches
+	/// for proteins as described by this configuration.
+	pub fn create_loader(&self) -> ThreadedProteinLoader {
+		ThreadedProteinLoader::with_threads(self.orf_options.clone(), self.threads)
+	}
+
+	/// Starts loading a given source, which may be compressed,
+	/// with a given loader, parsing and pre-processing it as
+	/// described by this configuration.
+	///
+	/// The `size` of the source, in bytes, is only used to
+	/// report the progress, and may be 0 if it is not known.
+	pub fn start(
+		&self,
+		loader: &mut ThreadedProteinLoader,
+		source: impl Read + Send + 'static,
+		size: u64,
+	) -> io::Result<()> {
+		match self.format {
+			SourceFormat::Sequence => {
+				let source = Preprocessor::new(source, &self.preprocessing)?;
+				loader.start_tracked(source, size);
+			}
+			SourceFormat::FlatFile(format) => {
+				loader.start_flat_file(BufReader::new(source), format, size);
+			}
+			SourceFormat::Fastq => {
+				let encoding = self.quality_encoding;
+				let filter = self.quality_filter.clone();
+				loader.start_fastq(BufReader::new(source), encoding, filter, size);
+			}
+		}
+		Ok(())
+	}
+
+	/// Maps the position of an invalid character reported by
+	/// the loader back onto a given source, read from its start,
+	/// see [`Preprocessor::locate_error`].
+	///
+	/// Flat files and FASTQ files are not pre-processed,
+	/// so their errors are returned as they are.
+	pub fn locate_error(&self, source: impl Read, err: LoadError) -> LoadError {
+		if self.format != SourceFormat::Sequence {
+			return err;
+		}
+		match Preprocessor::new(source, &self.preprocessing) {
+			Ok(preprocessor) => preprocessor.locate_error(err),
+			Err(_) => err,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+
+	use super::*;
+	use crate::{GeneticCode, Nucleotide, StartCodons};
+
+	#[test]
+	fn configs_are_stored_as_text() {
+		let mut config = ImportConfig {
+			format: SourceFormat::FlatFile(FlatFileFormat::Embl),
+			threads: 3,
+			..Default::default()
+		};
+		config.preprocessing.separator = ",".to_owned();
+		config.quality_filter.window = 0;
+		config.orf_options.code = GeneticCode::BACTERIAL_PLASTID;
+		config.orf_options.starts =
+			StartCodons::Custom(vec![[Nucleotide::G, Nucleotide::U, Nucleotide::G]]);
+		config.orf_options.max_length = Some(100);
+
+		let text = ron::to_string(&config).unwrap();
+		let stored: ImportConfig = ron::from_str(&text).unwrap();
+		assert!(stored.format == config.format);
+		assert!(stored.preprocessing == config.preprocessing);
+		assert!(stored.quality_filter == config.quality_filter);
+		assert!(stored.orf_options.code == GeneticCode::BACTERIAL_PLASTID);
+		assert!(stored.orf_options.starts == config.orf_options.starts);
+		assert_eq!(stored.orf_options.max_length, Some(100));
+		assert_eq!(stored.threads, 3);
+
+		// Missing settings are left at their defaults
+		let stored: ImportConfig = ron::from_str("(threads: 2, orf_options: (code: 4))").unwrap();
+		assert!(stored.format == SourceFormat::Sequence);
+		assert!(stored.quality_filter == QualityFilter::default());
+		assert!(stored.orf_options.code == GeneticCode::MOLD_MITOCHONDRIAL);
+		assert_eq!(stored.orf_options.min_length, 1);
+		assert!(ron::from_str::<ImportConfig>("(orf_options: (code: 7))").is_err());
+	}
+
+	#[test]
+	fn sources_are_imported_as_configured() {
+		let text = "title\nAUG,AAA,UAA,\nAUGX\n";
+		let mut config = ImportConfig::default();
+		config.preprocessing.separator = ",".to_owned();
+		config.preprocessing.delete_header = true;
+		config.preprocessing.header_len = 1;
+
+		let mut loader = config.create_loader();
+		config.start(&mut loader, Cursor::new(text), 0).unwrap();
+		let err = match loader.wait() {
+			Some(Err(err)) => err,
+			_ => panic!("expected the invalid character to be reported"),
+		};
+		match config.locate_error(text.as_bytes(), err) {
+			LoadError::InvalidCharacter {
+				character,
+				offset,
+				line,
+				column,
+			} => assert_eq!((character, offset, line, column), ('X', 22, 3, 4)),
+			err => panic!("expected an invalid character, got: {err}"),
+		}
+
+		config.preprocessing.delete_wrong_chars = true;
+		config.start(&mut loader, Cursor::new(text), 0).unwrap();
+		let map = loader.wait().unwrap().unwrap();
+		assert_eq!(map.keys().map(|x| x.0.as_str()).collect::<Vec<_>>(), ["K"]);
+	}
+}
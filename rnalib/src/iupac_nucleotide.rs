@@ -0,0 +1,265 @@
+//! The module that implements [`IupacNucleotide`]
+
+use std::fmt::{Display, Write};
+
+use crate::Nucleotide;
+
+/// Represents a single symbol of the extended IUPAC
+/// nucleotide alphabet, which, apart from the four
+/// nucleotides, allows to express uncertainty about
+/// the base present at a given position.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum IupacNucleotide {
+	/// Guanine
+	G,
+	/// Uracil / Thymine
+	U,
+	/// Adenine
+	A,
+	/// Cytosine
+	C,
+	/// Purine (A or G)
+	R,
+	/// Pyrimidine (C or U)
+	Y,
+	/// Strong interaction (G or C)
+	S,
+	/// Weak interaction (A or U)
+	W,
+	/// Keto group (G or U)
+	K,
+	/// Amino group (A or C)
+	M,
+	/// Not A (C, G or U)
+	B,
+	/// Not C (A, G or U)
+	D,
+	/// Not G (A, C or U)
+	H,
+	/// Not U (A, C or G)
+	V,
+	/// Any nucleotide
+	N,
+	/// An alignment gap, does not represent any nucleotide
+	Gap,
+}
+
+impl IupacNucleotide {
+	/// Every symbol, in the order of their codes,
+	/// see [`IupacNucleotide::from_code`].
+	const ALL: [Self; 16] = {
+		use IupacNucleotide::*;
+		[G, U, A, C, R, Y, S, W, K, M, B, D, H, V, N, Gap]
+	};
+
+	/// Returns the symbol with a given code, i.e. the index of its
+	/// variant, as returned by `symbol as u8`. The four nucleotides
+	/// have codes from 0 to 3, and only the lowest four bits of
+	/// the code are read.
+	pub(crate) const fn from_code(code: u8) -> Self {
+		Self::ALL[(code & 15) as usize]
+	}
+
+	/// Attempt to parse and return an [`IupacNucleotide`] variant
+	/// from a [`char`]. Ignores letter-case. The '-' and '.'
+	/// characters are interpreted as [`IupacNucleotide::Gap`].
+	/// If an unknown letter is passed as an argument, returns [`None`].
+	///
+	/// See [`Nucleotide::parse`] for more.
+	pub const fn parse(from: char) -> Option<Self> {
+		if from.is_ascii() {
+			Self::parse_raw(from as u8)
+		} else {
+			None
+		}
+	}
+
+	/// Attempt to parse and return an [`IupacNucleotide`] variant
+	/// from a [`u8`]. This function takes the ASCII table
+	/// byte representation of a character representing the
+	/// [`IupacNucleotide`]. For more, refer to [`IupacNucleotide::parse`].
+	pub const fn parse_raw(from: u8) -> Option<Self> {
+		use IupacNucleotide::*;
+		Some(match from.to_ascii_uppercase() {
+			b'G' => G,
+			b'U' | b'T' => U,
+			b'A' => A,
+			b'C' => C,
+			b'R' => R,
+			b'Y' => Y,
+			b'S' => S,
+			b'W' => W,
+			b'K' => K,
+			b'M' => M,
+			b'B' => B,
+			b'D' => D,
+			b'H' => H,
+			b'V' => V,
+			b'N' => N,
+			b'-' | b'.' => Gap,
+			_ => return None,
+		})
+	}
+
+	/// Returns every [`Nucleotide`] this symbol may stand for.
+	///
+	/// Returns an empty slice for [`IupacNucleotide::Gap`].
+	pub const fn expand(&self) -> &'static [Nucleotide] {
+		use Nucleotide as Nt;
+		match self {
+			Self::G => &[Nt::G],
+			Self::U => &[Nt::U],
+			Self::A => &[Nt::A],
+			Self::C => &[Nt::C],
+			Self::R => &[Nt::A, Nt::G],
+			Self::Y => &[Nt::C, Nt::U],
+			Self::S => &[Nt::G, Nt::C],
+			Self::W => &[Nt::A, Nt::U],
+			Self::K => &[Nt::G, Nt::U],
+			Self::M => &[Nt::A, Nt::C],
+			Self::B => &[Nt::C, Nt::G, Nt::U],
+			Self::D => &[Nt::A, Nt::G, Nt::U],
+			Self::H => &[Nt::A, Nt::C, Nt::U],
+			Self::V => &[Nt::A, Nt::C, Nt::G],
+			Self::N => &[Nt::G, Nt::U, Nt::A, Nt::C],
+			Self::Gap => &[],
+		}
+	}
+
+	/// Returns the [`Nucleotide`] represented by this symbol.
+	///
+	/// Returns [`None`] if this symbol is ambiguous or a gap.
+	pub const fn resolve(&self) -> Option<Nucleotide> {
+		match self.expand() {
+			[x] => Some(*x),
+			_ => None,
+		}
+	}
+
+	/// Returns the symbol that pairs with this one on the
+	/// complementary strand. Gaps and symmetric codes
+	/// (S, W, N) are their own complements.
+	pub const fn complement(&self) -> Self {
+		use IupacNucleotide::*;
+		match self {
+			G => C,
+			U => A,
+			A => U,
+			C => G,
+			R => Y,
+			Y => R,
+			S => S,
+			W => W,
+			K => M,
+			M => K,
+			B => V,
+			D => H,
+			H => D,
+			V => B,
+			N => N,
+			Gap => Gap,
+		}
+	}
+
+	/// Returns true if this symbol is an alignment gap.
+	pub const fn is_gap(&self) -> bool {
+		matches!(self, Self::Gap)
+	}
+
+	/// Returns the uppercase letter denoting this symbol,
+	/// or '-' for [`IupacNucleotide::Gap`].
+	pub const fn get_symbol(&self) -> char {
+		match self {
+			Self::G => 'G',
+			Self::U => 'U',
+			Self::A => 'A',
+			Self::C => 'C',
+			Self::R => 'R',
+			Self::Y => 'Y',
+			Self::S => 'S',
+			Self::W => 'W',
+			Self::K => 'K',
+			Self::M => 'M',
+			Self::B => 'B',
+			Self::D => 'D',
+			Self::H => 'H',
+			Self::V => 'V',
+			Self::N => 'N',
+			Self::Gap => '-',
+		}
+	}
+}
+
+impl From<Nucleotide> for IupacNucleotide {
+	fn from(value: Nucleotide) -> Self {
+		match value {
+			Nucleotide::G => Self::G,
+			Nucleotide::U => Self::U,
+			Nucleotide::A => Self::A,
+			Nucleotide::C => Self::C,
+		}
+	}
+}
+
+impl Display for IupacNucleotide {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_char(self.get_symbol())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Codon, GeneticCode};
+
+	/// A helper function that parses a triplet of symbols.
+	fn triplet(text: &str) -> [IupacNucleotide; 3] {
+		let mut symbols = text.chars().map(|x| IupacNucleotide::parse(x).unwrap());
+		[(); 3].map(|_| symbols.next().unwrap())
+	}
+
+	#[test]
+	fn symbols_expand_and_complement() {
+		let text = "GUACRYSWKMBDHVN-";
+		for (symbol, code) in text.chars().zip(0..) {
+			let nucleotide = IupacNucleotide::parse(symbol).unwrap();
+			assert!(nucleotide == IupacNucleotide::from_code(code));
+			assert_eq!(nucleotide.get_symbol(), symbol);
+			assert_eq!(nucleotide.resolve().is_some(), code < 4);
+
+			// The complement stands for the complements of the expansion
+			let complement = nucleotide.complement();
+			assert!(complement.complement() == nucleotide);
+			let mut expected: Vec<_> = nucleotide.expand().iter().map(|x| x.complement()).collect();
+			let mut expansion = complement.expand().to_vec();
+			expected.sort_by_key(|&x| x as u8);
+			expansion.sort_by_key(|&x| x as u8);
+			assert!(expansion == expected);
+		}
+		assert!(IupacNucleotide::parse('t') == Some(IupacNucleotide::U));
+		assert!(IupacNucleotide::parse('.') == Some(IupacNucleotide::Gap));
+		assert!(IupacNucleotide::parse('x').is_none());
+		assert!(IupacNucleotide::Gap.expand().is_empty());
+	}
+
+	#[test]
+	fn ambiguous_triplets_translate_if_expansions_agree() {
+		let code = GeneticCode::STANDARD;
+		let translate = |x| {
+			let [a, b, c] = triplet(x);
+			code.translate_ambiguous(a, b, c)
+		};
+		assert!(translate("GCN") == Codon::from_shorthand('A').unwrap());
+		assert!(translate("UUY") == Codon::from_shorthand('F').unwrap());
+		assert!(translate("URA") == Codon::STOP);
+		assert!(translate("UUN") == Codon::X);
+		assert!(translate("GC-") == Codon::X);
+
+		let bacterial = GeneticCode::BACTERIAL_PLASTID;
+		let [a, b, c] = triplet("DUG");
+		assert!(bacterial.is_start_ambiguous(a, b, c));
+		assert!(!code.is_start_ambiguous(a, b, c));
+		let [a, b, c] = triplet("RUU");
+		assert!(!bacterial.is_start_ambiguous(a, b, c));
+	}
+}
@@ -6,14 +6,50 @@
 mod acid;
 mod amino_string;
 mod codon;
+mod decompressor;
+mod fasta;
+mod fastq;
+mod flat_file;
+mod frame;
+mod genetic_code;
+mod import_config;
+mod iupac_nucleotide;
+mod load_error;
+mod load_event;
+mod location;
 mod nucleotide;
+mod nucleotide_string;
+mod orf_options;
+mod preprocessor;
 mod protein;
 mod protein_table;
+mod record_header;
+mod sequence_format;
+mod source_position;
+mod triplet;
 
 pub use acid::*;
 pub use amino_string::*;
 pub use codon::*;
+pub use decompressor::*;
+pub use fasta::*;
+pub use fastq::*;
+pub use flat_file::*;
+pub use frame::*;
+pub use genetic_code::*;
+pub use import_config::*;
+pub use iupac_nucleotide::*;
+pub use load_error::*;
+pub use load_event::*;
+pub use location::*;
 pub use nucleotide::*;
+pub use nucleotide_string::*;
+pub use orf_options::*;
+pub use preprocessor::*;
 pub use protein::*;
 pub use protein_table::loader::*;
 pub use protein_table::*;
+pub use record_header::*;
+pub use sequence_format::*;
+pub use source_position::*;
+pub use triplet::*;
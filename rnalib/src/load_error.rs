@@ -0,0 +1,71 @@
+//! The module that implements [`LoadError`]
+
+use std::{error::Error, fmt::Display, io};
+
+/// An error that occurred while loading proteins
+/// from a nucleotide sequence.
+#[derive(Debug)]
+pub enum LoadError {
+	/// The source contains a character that is neither
+	/// an [`IupacNucleotide`](crate::IupacNucleotide)
+	/// nor whitespace.
+	InvalidCharacter {
+		/// The offending character
+		character: char,
+		/// 0-based offset of the character in the source, in bytes
+		offset: u64,
+		/// 1-based number of the line containing the character
+		line: u64,
+		/// 1-based column of the character, in characters
+		column: u64,
+	},
+	/// A record of the source is not structured as its
+	/// format requires, e.g. a FASTQ read with fewer
+	/// quality scores than nucleotides.
+	InvalidRecord {
+		/// What is wrong with the record
+		reason: &'static str,
+		/// 0-based offset of the line the problem was
+		/// found at, in bytes
+		offset: u64,
+		/// 1-based number of the line
+		line: u64,
+	},
+	/// Reading the source failed.
+	Io(io::Error),
+}
+
+impl Display for LoadError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			LoadError::InvalidCharacter {
+				character,
+				line,
+				column,
+				..
+			} => write!(
+				f,
+				"Niepoprawny znak '{character}' w linii {line}, kolumnie {column}."
+			),
+			LoadError::InvalidRecord { reason, line, .. } => {
+				write!(f, "Niepoprawny rekord w linii {line}: {reason}.")
+			}
+			LoadError::Io(err) => write!(f, "Błąd odczytu danych wejściowych: {err}."),
+		}
+	}
+}
+
+impl Error for LoadError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			LoadError::InvalidCharacter { .. } | LoadError::InvalidRecord { .. } => None,
+			LoadError::Io(err) => Some(err),
+		}
+	}
+}
+
+impl From<io::Error> for LoadError {
+	fn from(value: io::Error) -> Self {
+		Self::Io(value)
+	}
+}
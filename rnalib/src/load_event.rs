@@ -0,0 +1,27 @@
+//! The module that implements [`LoadEvent`]
+
+use crate::Frame;
+
+/// An event reported by a [`ThreadedProteinLoader`](crate::ThreadedProteinLoader)
+/// to its subscribers while loading.
+#[derive(Clone, Copy, PartialEq)]
+pub enum LoadEvent {
+	/// A given reading frame has been scanned up
+	/// to a part of the source.
+	Progress {
+		frame: Frame,
+		/// Amount of bytes of the source processed so far, or
+		/// nucleotides if the source was an already parsed
+		/// [`NucleotideString`](crate::NucleotideString)
+		bytes: u64,
+		/// The processed part of the source, normalized to
+		/// the range from 0 to 1, or 0 if the size of the
+		/// source is not known
+		progress: f32,
+	},
+	/// A given reading frame has been scanned to the end.
+	FrameFinished(Frame),
+	/// All the threads have exited, the result of the load
+	/// can be taken.
+	Finished,
+}
@@ -0,0 +1,220 @@
+//! The module that implements [`Location`]
+
+use std::fmt::Display;
+
+use crate::{Frame, IupacNucleotide, Strand};
+
+/// Describes where a protein was found in the source
+/// nucleotide sequence.
+///
+/// All offsets are 0-based indices of nucleotides in the
+/// forward strand of the source record, not counting
+/// whitespace and alignment gaps.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+	/// Index of the record of the source the ORF was found in
+	record: usize,
+	/// Offset of the first nucleotide covered by the ORF
+	start: usize,
+	/// Offset one past the last nucleotide covered by the ORF
+	end: usize,
+	/// The reading frame of the ORF
+	frame: Frame,
+	/// The codon that initiated translation
+	start_codon: Option<[IupacNucleotide; 3]>,
+	/// The codon that terminated translation
+	stop_codon: Option<[IupacNucleotide; 3]>,
+	/// Index of the annotated feature the protein was
+	/// translated from, if it was not predicted
+	feature: Option<usize>,
+}
+
+impl Location {
+	/// Constructs a [`Location`] covering the nucleotides in
+	/// range `start..end` of the forward strand, including
+	/// both the start and the stop codon.
+	///
+	/// The start codon is [`None`] for ORFs that were not opened
+	/// by a start codon, and the stop codon is [`None`] for ORFs
+	/// that reached the end of the sequence.
+	pub const fn new(
+		start: usize,
+		end: usize,
+		frame: Frame,
+		start_codon: Option<[IupacNucleotide; 3]>,
+		stop_codon: Option<[IupacNucleotide; 3]>,
+	) -> Self {
+		Self {
+			record: 0,
+			start,
+			end,
+			frame,
+			start_codon,
+			stop_codon,
+			feature: None,
+		}
+	}
+
+	/// Returns the index of the record of the source the
+	/// ORF was found in, see [`ProteinMap::get_record`](crate::ProteinMap::get_record).
+	///
+	/// Sources without multiple records consist of
+	/// a single record, with index 0.
+	pub const fn get_record(&self) -> usize {
+		self.record
+	}
+
+	/// Returns a copy of this location, moved to another
+	/// record of the source.
+	pub(crate) const fn with_record(self, record: usize) -> Self {
+		Self { record, ..self }
+	}
+
+	/// Returns the index of the annotated feature, such as a
+	/// GenBank CDS, the protein was translated from, see
+	/// [`ProteinMap::get_feature`](crate::ProteinMap::get_feature).
+	///
+	/// Returns [`None`] for ORFs predicted from the sequence.
+	pub const fn get_feature(&self) -> Option<usize> {
+		self.feature
+	}
+
+	/// Returns true if the protein was translated from an
+	/// annotated feature, rather than predicted.
+	pub const fn is_annotated(&self) -> bool {
+		self.feature.is_some()
+	}
+
+	/// Returns a copy of this location, marked as annotated
+	/// by a given feature of the source.
+	pub(crate) const fn with_feature(self, feature: usize) -> Self {
+		Self {
+			feature: Some(feature),
+			..self
+		}
+	}
+
+	/// Returns the offset of the first nucleotide covered
+	/// by the ORF, on the forward strand.
+	///
+	/// Annotated features joined from multiple segments
+	/// cover everything between their outermost segments.
+	///
+	/// For ORFs on the reverse strand this is the last
+	/// nucleotide of the stop codon.
+	pub const fn get_start(&self) -> usize {
+		self.start
+	}
+
+	/// Returns the offset one past the last nucleotide
+	/// covered by the ORF, on the forward strand.
+	///
+	/// For ORFs on the reverse strand this is one past the
+	/// first nucleotide of the start codon.
+	pub const fn get_end(&self) -> usize {
+		self.end
+	}
+
+	/// Returns the amount of nucleotides covered by the ORF.
+	pub const fn len(&self) -> usize {
+		self.end - self.start
+	}
+
+	/// Returns true if the ORF covers no nucleotides.
+	pub const fn is_empty(&self) -> bool {
+		self.start == self.end
+	}
+
+	/// Returns the reading [`Frame`] of the ORF.
+	pub const fn get_frame(&self) -> Frame {
+		self.frame
+	}
+
+	/// Returns a copy of this location, moved to
+	/// another reading [`Frame`].
+	pub(crate) const fn with_frame(self, frame: Frame) -> Self {
+		Self { frame, ..self }
+	}
+
+	/// Returns the [`Strand`] the ORF was read from.
+	pub const fn get_strand(&self) -> Strand {
+		self.frame.get_strand()
+	}
+
+	/// Returns the codon that initiated translation,
+	/// as read on the ORF's strand.
+	///
+	/// Returns [`None`] if the ORF was not opened by a
+	/// start codon.
+	pub const fn get_start_codon(&self) -> Option<[IupacNucleotide; 3]> {
+		self.start_codon
+	}
+
+	/// Returns the codon that terminated translation,
+	/// as read on the ORF's strand.
+	///
+	/// Returns [`None`] if the ORF reached the end of the
+	/// sequence without a stop codon.
+	pub const fn get_stop_codon(&self) -> Option<[IupacNucleotide; 3]> {
+		self.stop_codon
+	}
+}
+
+impl Display for Location {
+	/// Formats the location using 1-based, inclusive
+	/// coordinates, as found in GenBank feature tables,
+	/// e.g. `11..40` or `complement(11..40)`.
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.get_strand() {
+			Strand::Forward => write!(f, "{}..{}", self.start + 1, self.end),
+			Strand::Reverse => write!(f, "complement({}..{})", self.start + 1, self.end),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{OrfOptions, ThreadedProteinLoader};
+
+	/// A helper function that parses a codon.
+	fn codon(text: &str) -> Option<[IupacNucleotide; 3]> {
+		let mut symbols = text.chars().map(|x| IupacNucleotide::parse(x).unwrap());
+		Some([(); 3].map(|_| symbols.next().unwrap()))
+	}
+
+	#[test]
+	fn orfs_are_located_on_both_strands() {
+		// AUG GCU UUU UAA on the forward strand, then AUG AAA CCC UGA
+		// on the reverse one, which starts at the last G
+		let source = "CCAUGGCUUUUUAACCUCAGGGUUUCAUG";
+		let options = OrfOptions {
+			both_strands: true,
+			..Default::default()
+		};
+		let mut loader = ThreadedProteinLoader::new(options);
+		loader.start(source.to_owned());
+		let map = loader.wait().unwrap().unwrap();
+		let found = map.occurrences().map(|(x, y)| (x.to_string(), *y));
+		let mut found: Vec<_> = found.collect();
+		found.sort_by_key(|x| x.1.get_start());
+		assert_eq!(found.len(), 2);
+
+		let (protein, forward) = &found[0];
+		assert_eq!(protein, "AF");
+		assert_eq!((forward.get_start(), forward.get_end()), (2, 14));
+		assert!(forward.get_frame() == Frame::new(Strand::Forward, 2));
+		assert!(forward.get_start_codon() == codon("AUG"));
+		assert!(forward.get_stop_codon() == codon("UAA"));
+		assert_eq!(forward.to_string(), "3..14");
+
+		let (protein, reverse) = &found[1];
+		assert_eq!(protein, "KP");
+		assert_eq!((reverse.get_start(), reverse.get_end()), (16, 28));
+		assert!(reverse.get_frame() == Frame::new(Strand::Reverse, 1));
+		assert_eq!(reverse.get_frame().to_string(), "-2");
+		assert!(reverse.get_start_codon() == codon("AUG"));
+		assert!(reverse.get_stop_codon() == codon("UGA"));
+		assert_eq!(reverse.to_string(), "complement(17..28)");
+	}
+}
@@ -2,9 +2,11 @@
 
 use std::fmt::{Display, Write};
 
+use serde::{Deserialize, Serialize};
+
 /// Represents one of the four nucleotides
 /// commonly found in RNA sequences.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Nucleotide {
 	/// Guanine
 	G,
@@ -47,6 +49,18 @@ impl Nucleotide {
 			_ => None,
 		}
 	}
+
+	/// Returns the [`Nucleotide`] that pairs with this one
+	/// on the complementary strand.
+	pub const fn complement(&self) -> Self {
+		use Nucleotide::*;
+		match self {
+			G => C,
+			U => A,
+			A => U,
+			C => G,
+		}
+	}
 }
 
 impl Display for Nucleotide {
@@ -0,0 +1,146 @@
+//! The module that implements [`Iter`] and [`Codons`]
+
+use std::iter::FusedIterator;
+
+use crate::{Frame, IupacNucleotide, Strand};
+
+use super::NucleotideString;
+
+/// An iterator over the nucleotides of a [`NucleotideString`],
+/// created by [`NucleotideString::iter`].
+#[derive(Clone)]
+pub struct Iter<'a> {
+	string: &'a NucleotideString,
+	/// Index of the next nucleotide from the front
+	front: usize,
+	/// Index past the next nucleotide from the back
+	back: usize,
+	/// Index of the first ambiguity code not before `front`
+	exception_front: usize,
+	/// Index past the last ambiguity code before `back`
+	exception_back: usize,
+}
+
+impl<'a> Iter<'a> {
+	/// Constructs an iterator over the nucleotides
+	/// from `start` up to, but excluding, `end`.
+	pub(crate) fn new(string: &'a NucleotideString, start: usize, end: usize) -> Self {
+		let exceptions = &string.exceptions;
+		Self {
+			string,
+			front: start,
+			back: end,
+			exception_front: exceptions.partition_point(|x| x.0 < start),
+			exception_back: exceptions.partition_point(|x| x.0 < end),
+		}
+	}
+
+	/// A helper function that returns the ambiguity
+	/// codes among the remaining nucleotides.
+	fn exceptions(&self) -> &'a [(usize, IupacNucleotide)] {
+		&self.string.exceptions[self.exception_front..self.exception_back]
+	}
+}
+
+impl Iterator for Iter<'_> {
+	type Item = IupacNucleotide;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.front == self.back {
+			return None;
+		}
+		let index = self.front;
+		self.front += 1;
+
+		if let Some(&(at, x)) = self.exceptions().first() {
+			if at == index {
+				self.exception_front += 1;
+				return Some(x);
+			}
+		}
+		Some(self.string.get_packed(index).into())
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let len = self.back - self.front;
+		(len, Some(len))
+	}
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		if self.front == self.back {
+			return None;
+		}
+		self.back -= 1;
+		let index = self.back;
+
+		if let Some(&(at, x)) = self.exceptions().last() {
+			if at == index {
+				self.exception_back -= 1;
+				return Some(x);
+			}
+		}
+		Some(self.string.get_packed(index).into())
+	}
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl FusedIterator for Iter<'_> {}
+
+/// An iterator over the codons of a single reading frame
+/// of a [`NucleotideString`], created by [`NucleotideString::codons`].
+///
+/// Codons of the reverse strand frames are complemented,
+/// and yielded starting at the end of the string.
+#[derive(Clone)]
+pub struct Codons<'a> {
+	nucleotides: Iter<'a>,
+	strand: Strand,
+}
+
+impl<'a> Codons<'a> {
+	/// Constructs an iterator over the codons of a given frame
+	/// of the nucleotides from `start` up to, but excluding, `end`.
+	pub(crate) fn new(
+		string: &'a NucleotideString,
+		start: usize,
+		end: usize,
+		frame: Frame,
+	) -> Self {
+		let offset = (frame.get_offset() as usize).min(end - start);
+		let nucleotides = match frame.get_strand() {
+			Strand::Forward => Iter::new(string, start + offset, end),
+			Strand::Reverse => Iter::new(string, start, end - offset),
+		};
+		Self {
+			nucleotides,
+			strand: frame.get_strand(),
+		}
+	}
+}
+
+impl Iterator for Codons<'_> {
+	type Item = [IupacNucleotide; 3];
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.nucleotides.len() < 3 {
+			return None;
+		}
+		let iter = &mut self.nucleotides;
+		Some(match self.strand {
+			Strand::Forward => [(); 3].map(|_| iter.next().unwrap()),
+			Strand::Reverse => [(); 3].map(|_| iter.next_back().unwrap().complement()),
+		})
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let len = self.nucleotides.len() / 3;
+		(len, Some(len))
+	}
+}
+
+impl ExactSizeIterator for Codons<'_> {}
+
+impl FusedIterator for Codons<'_> {}
@@ -0,0 +1,358 @@
+//! The module that implements [`NucleotideString`]
+
+use std::fmt::{Display, Write};
+
+mod iter;
+pub use iter::*;
+mod slice;
+pub use slice::*;
+pub(crate) mod parser;
+use parser::SequenceParser;
+
+use crate::{Frame, IupacNucleotide, LoadError, Nucleotide};
+
+/// Describes whether a nucleotide sequence is DNA or RNA,
+/// which only affects how thymine / uracil is displayed.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Molecule {
+	/// Deoxyribonucleic acid, displayed with T
+	Dna,
+	/// Ribonucleic acid, displayed with U
+	#[default]
+	Rna,
+}
+
+/// Represents a sequence of nucleotides.
+///
+/// Internally, the four nucleotides are packed into two bits
+/// each, four per byte. Ambiguity codes (see [`IupacNucleotide`])
+/// are kept in a separate, sorted list, so that they cost more
+/// memory only where they are present. Alignment gaps are never
+/// stored.
+///
+/// The packed form is what a [`ThreadedProteinLoader`](crate::ThreadedProteinLoader)
+/// keeps of its source. While a chunk of it is being scanned,
+/// its nucleotides take a byte each, so the memory used by the
+/// scan itself depends only on the chunk size and the amount
+/// of threads.
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct NucleotideString {
+	/// Packed nucleotides, first one in the lowest bits
+	data: Vec<u8>,
+	/// Amount of nucleotides
+	len: usize,
+	/// Ambiguity codes, sorted by their index. The packed
+	/// slots at these indices hold an arbitrary nucleotide
+	/// other than G or C.
+	exceptions: Vec<(usize, IupacNucleotide)>,
+	molecule: Molecule,
+}
+
+impl NucleotideString {
+	/// Constructs an empty [`NucleotideString`] of a given kind.
+	pub fn new(molecule: Molecule) -> Self {
+		Self {
+			molecule,
+			..Default::default()
+		}
+	}
+
+	/// Parses a [`NucleotideString`] from text.
+	///
+	/// Letters are read as in [`IupacNucleotide::parse`], while
+	/// whitespace and alignment gaps are skipped. The string is
+	/// considered DNA if it contains the letter 'T', and RNA
+	/// otherwise.
+	///
+	/// Returns [`Err`] pointing at the first character that
+	/// is not a nucleotide.
+	pub fn parse(source: &str) -> Result<Self, LoadError> {
+		let mut result = Self::default();
+		SequenceParser::default().parse(source.as_bytes(), &mut result)?;
+		Ok(result)
+	}
+
+	/// Appends a single nucleotide to the end of this string.
+	/// Alignment gaps are ignored.
+	pub fn push(&mut self, nucleotide: IupacNucleotide) {
+		let bits = match nucleotide.resolve() {
+			Some(x) => Self::pack(x),
+			None if nucleotide.is_gap() => return,
+			None => {
+				self.exceptions.push((self.len, nucleotide));
+				0
+			}
+		};
+
+		if self.len.is_multiple_of(4) {
+			self.data.push(bits);
+		} else {
+			self.data[self.len / 4] |= bits << (self.len % 4 * 2);
+		}
+		self.len += 1;
+	}
+
+	/// Returns the amount of nucleotides in this string.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Returns true if this string contains no nucleotides.
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns the kind of this string.
+	pub fn get_molecule(&self) -> Molecule {
+		self.molecule
+	}
+
+	/// Returns the nucleotide at a given index.
+	///
+	/// Returns [`None`] if the index is out of bounds.
+	pub fn get(&self, index: usize) -> Option<IupacNucleotide> {
+		if index >= self.len {
+			return None;
+		}
+		if !self.exceptions.is_empty() {
+			if let Ok(x) = self.exceptions.binary_search_by_key(&index, |x| x.0) {
+				return Some(self.exceptions[x].1);
+			}
+		}
+		Some(self.get_packed(index).into())
+	}
+
+	/// Appends the codes (see [`IupacNucleotide::from_code`]) of
+	/// the nucleotides from `start` up to, but excluding, `end`
+	/// to `target`, reading the packed data directly.
+	pub(crate) fn extend_codes(&self, start: usize, end: usize, target: &mut Vec<u8>) {
+		// Codes of the packed nucleotides A, C, G and U
+		const CODES: [u8; 4] = [2, 3, 0, 1];
+		let offset = target.len();
+		let packed = (start..end).map(|x| CODES[(self.data[x / 4] >> (x % 4 * 2) & 3) as usize]);
+		target.extend(packed);
+
+		let first = self.exceptions.partition_point(|x| x.0 < start);
+		for &(index, nucleotide) in &self.exceptions[first..] {
+			if index >= end {
+				break;
+			}
+			target[offset + index - start] = nucleotide as u8;
+		}
+	}
+
+	/// Returns an iterator over the nucleotides of this string.
+	pub fn iter(&self) -> Iter<'_> {
+		Iter::new(self, 0, self.len)
+	}
+
+	/// Returns an iterator over the codons of a given reading
+	/// [`Frame`]. Codons of the reverse strand frames are read
+	/// from the reverse complement of this string.
+	pub fn codons(&self, frame: Frame) -> Codons<'_> {
+		Codons::new(self, 0, self.len, frame)
+	}
+
+	/// Returns a [`NucleotideSlice`] viewing the whole string.
+	pub fn as_slice(&self) -> NucleotideSlice<'_> {
+		NucleotideSlice::new(self, 0, self.len)
+	}
+
+	/// Returns a [`NucleotideSlice`] viewing a part of this
+	/// string, without copying the underlying data.
+	///
+	/// The bounds are clamped as in [`NucleotideString::slice`].
+	pub fn view(&self, start: usize, length: usize) -> NucleotideSlice<'_> {
+		let start = start.min(self.len);
+		let end = start.saturating_add(length).min(self.len);
+		NucleotideSlice::new(self, start, end)
+	}
+
+	/// Returns a sub [`NucleotideString`] contained within
+	/// this instance.
+	///
+	/// The returned data is an owned [`NucleotideString`]
+	/// created by copying the underlying data.
+	pub fn slice(&self, start: usize, length: usize) -> Self {
+		let start = start.min(self.len);
+		let end = start.saturating_add(length).min(self.len);
+		let mut result = Self::new(self.molecule);
+		result.extend(Iter::new(self, start, end));
+		result
+	}
+
+	/// Returns the complementary string, i.e. the string
+	/// that pairs with this one, read in the same direction.
+	pub fn complement(&self) -> Self {
+		let mut data: Vec<u8> = self.data.iter().map(|x| !x).collect();
+		if let Some(last) = data.last_mut() {
+			// Unused bits of the last byte are kept clear
+			if !self.len.is_multiple_of(4) {
+				*last &= (1 << (self.len % 4 * 2)) - 1;
+			}
+		}
+		Self {
+			data,
+			len: self.len,
+			exceptions: self
+				.exceptions
+				.iter()
+				.map(|&(index, x)| (index, x.complement()))
+				.collect(),
+			molecule: self.molecule,
+		}
+	}
+
+	/// Returns the reverse complement of this string, i.e. the
+	/// complementary strand read in its own 5' to 3' direction.
+	pub fn reverse_complement(&self) -> Self {
+		let mut result = Self::new(self.molecule);
+		result.extend(self.iter().rev().map(|x| x.complement()));
+		result
+	}
+
+	/// Returns the fraction of guanine and cytosine among
+	/// the nucleotides of this string, from 0 to 1.
+	///
+	/// The ambiguity code S (G or C) is counted as GC and W
+	/// (A or U) as AU, other ambiguity codes are ignored.
+	/// Returns 0 for strings with no unambiguous nucleotides.
+	pub fn gc_content(&self) -> f32 {
+		// With C = 01 and G = 10, a slot holds G or C
+		// exactly when its two bits differ.
+		let mut gc: usize = self
+			.data
+			.iter()
+			.map(|x| ((x ^ (x >> 1)) & 0x55).count_ones() as usize)
+			.sum();
+		let mut au = self.len - self.exceptions.len() - gc;
+		for (_, x) in &self.exceptions {
+			match x {
+				IupacNucleotide::S => gc += 1,
+				IupacNucleotide::W => au += 1,
+				_ => {}
+			}
+		}
+
+		match gc + au {
+			0 => 0.0,
+			total => gc as f32 / total as f32,
+		}
+	}
+
+	/// Returns the RNA transcript of this string, which
+	/// holds the same nucleotides with thymine replaced
+	/// by uracil.
+	pub fn transcribe(&self) -> Self {
+		Self {
+			molecule: Molecule::Rna,
+			..self.clone()
+		}
+	}
+
+	/// Returns the DNA this string could be transcribed from,
+	/// which holds the same nucleotides with uracil replaced
+	/// by thymine.
+	pub fn back_transcribe(&self) -> Self {
+		Self {
+			molecule: Molecule::Dna,
+			..self.clone()
+		}
+	}
+
+	/// A helper function that returns the packed nucleotide
+	/// at a given index, ignoring ambiguity codes.
+	fn get_packed(&self, index: usize) -> Nucleotide {
+		Self::unpack(self.data[index / 4] >> (index % 4 * 2))
+	}
+
+	/// A helper function that returns the 2-bit representation
+	/// of a [`Nucleotide`].
+	const fn pack(nucleotide: Nucleotide) -> u8 {
+		match nucleotide {
+			Nucleotide::A => 0,
+			Nucleotide::C => 1,
+			Nucleotide::G => 2,
+			Nucleotide::U => 3,
+		}
+	}
+
+	/// A helper function that reads a [`Nucleotide`] from
+	/// the lowest two bits of a byte.
+	const fn unpack(bits: u8) -> Nucleotide {
+		match bits & 3 {
+			0 => Nucleotide::A,
+			1 => Nucleotide::C,
+			2 => Nucleotide::G,
+			_ => Nucleotide::U,
+		}
+	}
+}
+
+impl Extend<IupacNucleotide> for NucleotideString {
+	fn extend<T: IntoIterator<Item = IupacNucleotide>>(&mut self, iter: T) {
+		for nucleotide in iter {
+			self.push(nucleotide);
+		}
+	}
+}
+
+impl FromIterator<IupacNucleotide> for NucleotideString {
+	fn from_iter<T: IntoIterator<Item = IupacNucleotide>>(iter: T) -> Self {
+		let mut result = Self::default();
+		result.extend(iter);
+		result
+	}
+}
+
+impl Display for NucleotideString {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for nucleotide in self.iter() {
+			match (nucleotide, self.molecule) {
+				(IupacNucleotide::U, Molecule::Dna) => f.write_char('T')?,
+				_ => write!(f, "{nucleotide}")?,
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trips_with_exceptions() {
+		let text = "ACGUNRYACGUWSKMBDHVACGUUN";
+		let string = NucleotideString::parse(text).unwrap();
+		assert_eq!(string.len(), text.len());
+		assert_eq!(string.to_string(), text);
+		assert!(string.iter().collect::<NucleotideString>() == string);
+		assert!(string.complement().complement() == string);
+		assert!(string.reverse_complement().reverse_complement() == string);
+		assert_eq!(string.get(4).map(|x| x.get_symbol()), Some('N'));
+		assert!(string.get(text.len()).is_none());
+
+		// Gaps and whitespace are skipped, T makes the string DNA
+		let dna = NucleotideString::parse("ac-gt\nNR").unwrap();
+		assert!(dna.get_molecule() == Molecule::Dna);
+		assert_eq!(dna.to_string(), "ACGTNR");
+		assert_eq!(dna.transcribe().to_string(), "ACGUNR");
+		assert_eq!(dna.slice(3, 2).to_string(), "TN");
+		assert_eq!(dna.complement().to_string(), "TGCANY");
+	}
+
+	#[test]
+	fn codes_match_nucleotides() {
+		let string = NucleotideString::parse("ACGUNRYAcgu-WSKMBDHV\nACGUU").unwrap();
+		for start in 0..string.len() {
+			for end in start..=string.len() {
+				let mut codes = vec![7];
+				string.extend_codes(start, end, &mut codes);
+				let expected = Iter::new(&string, start, end).map(|x| x as u8);
+				assert!(codes[1..].iter().copied().eq(expected));
+				assert_eq!(codes[0], 7);
+			}
+		}
+	}
+}
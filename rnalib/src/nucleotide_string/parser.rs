@@ -0,0 +1,156 @@
+//! The module that implements [`SequenceParser`]
+
+use std::ops::Range;
+
+use crate::{IupacNucleotide, LoadError, RecordHeader, SourcePosition};
+
+use super::{Molecule, NucleotideString};
+
+/// A parser of nucleotide sequences stored as text, which
+/// may be fed in any number of parts. Keeps track of the
+/// position in the source, so that errors can point at
+/// the offending character.
+///
+/// Optionally, lines starting with '>' are read as FASTA
+/// headers, which split the source into records, or digits
+/// are skipped, as in numbered GenBank and EMBL sequences.
+#[derive(Default)]
+pub(crate) struct SequenceParser {
+	/// Position of the next byte of the source
+	position: SourcePosition,
+	/// Whether FASTA headers are recognized
+	headers: bool,
+	/// Whether digits are skipped
+	numbered: bool,
+	/// The header line being read, if any
+	header: Option<Vec<u8>>,
+	/// Amount of nucleotides read since the last header
+	length: usize,
+	/// Soft-masked (lowercase) ranges of nucleotides read
+	/// since the last header, if tracked
+	masked: Option<Vec<Range<usize>>>,
+}
+
+impl SequenceParser {
+	/// Constructs a parser that splits the source into FASTA
+	/// records, optionally tracking soft-masked ranges.
+	pub fn fasta(mask: bool) -> Self {
+		Self {
+			headers: true,
+			masked: mask.then(Vec::new),
+			..Default::default()
+		}
+	}
+
+	/// Constructs a parser that skips the digits numbering
+	/// the sequence lines of GenBank and EMBL files.
+	pub fn numbered() -> Self {
+		Self {
+			numbered: true,
+			..Default::default()
+		}
+	}
+
+	/// Parses the next part of the source, appending the
+	/// nucleotides to `target`. Whitespace and alignment
+	/// gaps are skipped.
+	///
+	/// Returns the amount of bytes consumed, along with the
+	/// header read, if any. Parsing stops right after every
+	/// header, so that the caller can start a new record.
+	///
+	/// Returns [`Err`] at the first unknown character.
+	pub fn parse(
+		&mut self,
+		bytes: &[u8],
+		target: &mut NucleotideString,
+	) -> Result<(usize, Option<RecordHeader>), LoadError> {
+		for (index, &byte) in bytes.iter().enumerate() {
+			if let Some(header) = &mut self.header {
+				self.position.advance(byte);
+				if byte != b'\n' {
+					header.push(byte);
+					continue;
+				}
+				let header = self.finish();
+				return Ok((index + 1, header));
+			}
+
+			match byte {
+				b'>' if self.headers && self.position.column == 1 => {
+					self.header = Some(Vec::new());
+				}
+				b' ' | b'\t' | b'\r' | b'\n' => {}
+				b'0'..=b'9' if self.numbered => {}
+				_ => match IupacNucleotide::parse_raw(byte) {
+					Some(x) => self.push(x, byte, target),
+					None => {
+						return Err(LoadError::InvalidCharacter {
+							character: Self::decode_char(&bytes[index..]),
+							offset: self.position.offset,
+							line: self.position.line,
+							column: self.position.column,
+						});
+					}
+				},
+			}
+			self.position.advance(byte);
+		}
+		Ok((bytes.len(), None))
+	}
+
+	/// Moves past a part of the source that does not hold
+	/// nucleotides, so that positions of the errors found
+	/// later still point at the right character.
+	pub fn skip(&mut self, bytes: &[u8]) {
+		for &byte in bytes {
+			self.position.advance(byte);
+		}
+	}
+
+	/// Finishes reading the header line that was not
+	/// terminated by a newline, if any.
+	pub fn finish(&mut self) -> Option<RecordHeader> {
+		let header = self.header.take()?;
+		self.length = 0;
+		Some(RecordHeader::parse(&String::from_utf8_lossy(&header)))
+	}
+
+	/// Takes the soft-masked ranges of the record read
+	/// since the last header.
+	pub fn take_masked(&mut self) -> Vec<Range<usize>> {
+		self.masked.as_mut().map(std::mem::take).unwrap_or_default()
+	}
+
+	/// A helper function that appends a single parsed
+	/// nucleotide to `target`.
+	fn push(&mut self, nucleotide: IupacNucleotide, byte: u8, target: &mut NucleotideString) {
+		if nucleotide.is_gap() {
+			return;
+		}
+		if byte.eq_ignore_ascii_case(&b'T') {
+			target.molecule = Molecule::Dna;
+		}
+		if let Some(masked) = &mut self.masked {
+			if byte.is_ascii_lowercase() {
+				match masked.last_mut() {
+					Some(range) if range.end == self.length => range.end += 1,
+					_ => masked.push(self.length..self.length + 1),
+				}
+			}
+		}
+		target.push(nucleotide);
+		self.length += 1;
+	}
+
+	/// Decodes the UTF-8 character at the beginning of given
+	/// bytes, falling back to [`char::REPLACEMENT_CHARACTER`].
+	pub fn decode_char(bytes: &[u8]) -> char {
+		let bytes = &bytes[..bytes.len().min(4)];
+		let valid = match std::str::from_utf8(bytes) {
+			Ok(x) => x,
+			Err(err) => std::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap_or_default(),
+		};
+		valid.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER)
+	}
+}
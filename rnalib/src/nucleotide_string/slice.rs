@@ -0,0 +1,71 @@
+//! The module that implements [`NucleotideSlice`]
+
+use crate::{Frame, IupacNucleotide};
+
+use super::{Codons, Iter, NucleotideString};
+
+/// A borrowed view into a part of a [`NucleotideString`],
+/// created by [`NucleotideString::as_slice`] or
+/// [`NucleotideString::view`].
+///
+/// Indices passed to and returned by the slice are
+/// counted from its own start.
+#[derive(Clone, Copy)]
+pub struct NucleotideSlice<'a> {
+	string: &'a NucleotideString,
+	/// Index of the first nucleotide in the string
+	start: usize,
+	/// Index past the last nucleotide in the string
+	end: usize,
+}
+
+impl<'a> NucleotideSlice<'a> {
+	/// Constructs a slice viewing the nucleotides
+	/// from `start` up to, but excluding, `end`.
+	pub(crate) fn new(string: &'a NucleotideString, start: usize, end: usize) -> Self {
+		Self { string, start, end }
+	}
+
+	/// Returns the amount of nucleotides in this slice.
+	pub fn len(&self) -> usize {
+		self.end - self.start
+	}
+
+	/// Returns true if this slice contains no nucleotides.
+	pub fn is_empty(&self) -> bool {
+		self.start == self.end
+	}
+
+	/// Returns the nucleotide at a given index.
+	///
+	/// Returns [`None`] if the index is out of bounds.
+	pub fn get(&self, index: usize) -> Option<IupacNucleotide> {
+		match index < self.len() {
+			true => self.string.get(self.start + index),
+			false => None,
+		}
+	}
+
+	/// Returns an iterator over the nucleotides of this slice.
+	pub fn iter(&self) -> Iter<'a> {
+		Iter::new(self.string, self.start, self.end)
+	}
+
+	/// Returns an iterator over the codons of a given reading
+	/// [`Frame`] of this slice, see [`NucleotideString::codons`].
+	pub fn codons(&self, frame: Frame) -> Codons<'a> {
+		Codons::new(self.string, self.start, self.end, frame)
+	}
+
+	/// Copies the nucleotides of this slice into
+	/// an owned [`NucleotideString`].
+	pub fn to_nucleotide_string(&self) -> NucleotideString {
+		self.string.slice(self.start, self.len())
+	}
+}
+
+impl<'a> From<&'a NucleotideString> for NucleotideSlice<'a> {
+	fn from(value: &'a NucleotideString) -> Self {
+		value.as_slice()
+	}
+}
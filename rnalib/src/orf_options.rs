@@ -0,0 +1,145 @@
+//! The module that implements [`OrfOptions`]
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Codon, GeneticCode, IupacNucleotide, Nucleotide};
+
+/// Describes which codons may open an open reading frame.
+#[derive(Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StartCodons {
+	/// Only the canonical AUG codon.
+	#[default]
+	Aug,
+	/// Every start codon listed by the [`GeneticCode`] in use,
+	/// e.g. AUG, GUG and UUG for bacteria.
+	Table,
+	/// A custom list of codons.
+	Custom(Vec<[Nucleotide; 3]>),
+}
+
+/// Describes how the boundaries of an open reading
+/// frame are determined.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OrfDefinition {
+	/// An ORF spans from a start codon up to the
+	/// nearest in-frame stop codon.
+	#[default]
+	StartToStop,
+	/// An ORF spans the whole region between two
+	/// consecutive in-frame stop codons, regardless
+	/// of start codons.
+	StopToStop,
+}
+
+/// A bucket type holding the settings that define
+/// what is considered an open reading frame.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrfOptions {
+	/// The translation table used to read codons.
+	pub code: GeneticCode,
+	/// If true, the three frames of the reverse
+	/// complement strand are scanned as well.
+	pub both_strands: bool,
+	/// The codons that may open an ORF.
+	pub starts: StartCodons,
+	/// The minimal length of a protein, in amino acids.
+	pub min_length: usize,
+	/// The maximal length of a protein, in amino acids.
+	/// [`None`] means no limit.
+	pub max_length: Option<usize>,
+	/// If true, every start codon found inside an already
+	/// open ORF opens another, nested ORF.
+	pub nested: bool,
+	/// If true, ORFs that reach an end of the sequence
+	/// without a stop codon are kept.
+	pub partial: bool,
+	/// How the boundaries of an ORF are determined.
+	pub definition: OrfDefinition,
+}
+
+impl OrfOptions {
+	/// Returns true if the given triplet may open an ORF
+	/// according to these options.
+	///
+	/// Ambiguous triplets are accepted only if every possible
+	/// expansion of them is accepted.
+	pub fn is_start(&self, a: IupacNucleotide, b: IupacNucleotide, c: IupacNucleotide) -> bool {
+		match &self.starts {
+			StartCodons::Aug => {
+				(a, b, c) == (IupacNucleotide::A, IupacNucleotide::U, IupacNucleotide::G)
+			}
+			StartCodons::Table => self.code.is_start_ambiguous(a, b, c),
+			StartCodons::Custom(codons) => match (a.resolve(), b.resolve(), c.resolve()) {
+				(Some(a), Some(b), Some(c)) => codons.contains(&[a, b, c]),
+				_ => false,
+			},
+		}
+	}
+
+	/// Returns true if a protein of a given length, in amino
+	/// acids, should be reported according to these options.
+	pub fn accepts_length(&self, length: usize) -> bool {
+		length > 0 && length >= self.min_length && self.max_length.is_none_or(|x| length <= x)
+	}
+
+	/// Translates a triplet using the [`GeneticCode`]
+	/// set in these options.
+	pub fn translate(&self, a: IupacNucleotide, b: IupacNucleotide, c: IupacNucleotide) -> Codon {
+		self.code.translate_ambiguous(a, b, c)
+	}
+}
+
+impl Default for OrfOptions {
+	fn default() -> Self {
+		Self {
+			code: GeneticCode::default(),
+			both_strands: false,
+			starts: StartCodons::default(),
+			min_length: 1,
+			max_length: None,
+			nested: false,
+			partial: false,
+			definition: OrfDefinition::default(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lengths_are_accepted_within_limits() {
+		let options = OrfOptions {
+			min_length: 2,
+			max_length: Some(4),
+			..Default::default()
+		};
+		let accepted: Vec<_> = (0..6).filter(|&x| options.accepts_length(x)).collect();
+		assert_eq!(accepted, [2, 3, 4]);
+		assert!(!OrfOptions::default().accepts_length(0));
+		assert!(OrfOptions::default().accepts_length(usize::MAX));
+	}
+
+	#[test]
+	fn starts_are_accepted_for_every_expansion() {
+		use IupacNucleotide::*;
+		let custom = OrfOptions {
+			starts: StartCodons::Custom(vec![[Nucleotide::G, Nucleotide::U, Nucleotide::G]]),
+			..Default::default()
+		};
+		assert!(custom.is_start(G, U, G) && !custom.is_start(A, U, G));
+		assert!(!custom.is_start(R, U, G));
+		assert!(OrfOptions::default().is_start(A, U, G));
+		assert!(!OrfOptions::default().is_start(G, U, G));
+
+		let table = OrfOptions {
+			code: GeneticCode::BACTERIAL_PLASTID,
+			starts: StartCodons::Table,
+			..Default::default()
+		};
+		assert!(table.is_start(R, U, G) && table.is_start(A, U, H));
+		assert!(table.is_start(A, U, N) && !table.is_start(G, U, N));
+	}
+}
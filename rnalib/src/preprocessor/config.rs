@@ -0,0 +1,42 @@
+//! The module that implements [`PreprocessorConfig`]
+
+use serde::{Deserialize, Serialize};
+
+/// Describes how a raw sequence or FASTA records are cleaned
+/// up by a [`Preprocessor`](super::Preprocessor) before they
+/// are loaded.
+///
+/// The configuration is stored along with other import
+/// settings in an [`ImportConfig`](crate::ImportConfig).
+#[derive(Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreprocessorConfig {
+	/// Text removed from the sequence wherever it occurs,
+	/// e.g. "," for comma separated nucleotides. Empty if
+	/// nothing is removed.
+	pub separator: String,
+	/// Whether the characters that are neither nucleotides
+	/// nor whitespace are removed, instead of being reported
+	/// by the loader
+	pub delete_wrong_chars: bool,
+	/// Whether the first lines of the source are skipped
+	pub delete_header: bool,
+	/// Amount of lines skipped if [`Self::delete_header`] is set
+	pub header_len: u32,
+}
+
+impl PreprocessorConfig {
+	/// Returns the amount of leading lines skipped.
+	pub fn get_header_len(&self) -> u32 {
+		match self.delete_header {
+			true => self.header_len,
+			false => 0,
+		}
+	}
+
+	/// Checks whether the configuration leaves
+	/// every source untouched.
+	pub fn is_identity(&self) -> bool {
+		self.separator.is_empty() && !self.delete_wrong_chars && self.get_header_len() == 0
+	}
+}
@@ -0,0 +1,418 @@
+//! The module that implements [`Preprocessor`]
+
+mod config;
+pub use config::*;
+
+use std::io::{self, BufRead, BufReader, Read};
+
+use crate::{Decompressor, IupacNucleotide, LoadError, SourcePosition, TrackedRead};
+
+/// The way a byte of the source is treated by the [`Preprocessor`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ByteFate {
+	/// Passed on to the loader
+	Kept,
+	/// Passed on to the loader, which will reject it
+	Invalid,
+	/// Skipped along with the header
+	Header,
+	/// Removed as a part of a separator
+	Separator,
+	/// Removed as an invalid character
+	Deleted,
+}
+
+impl ByteFate {
+	/// Finds out what a [`Preprocessor`] configured in a given
+	/// way does with each of given bytes, which are the beginning
+	/// of a source, after decompression.
+	///
+	/// The last few bytes may turn out to be a part of a separator
+	/// once the source continues, but are reported as kept.
+	pub fn classify(bytes: &[u8], config: &PreprocessorConfig) -> Vec<Self> {
+		let mut header_len = 0;
+		for _ in 0..config.get_header_len() {
+			let line = bytes[header_len..].iter().position(|&x| x == b'\n');
+			header_len = line.map_or(bytes.len(), |x| header_len + x + 1);
+		}
+
+		let mut filter = Filter::new(config);
+		filter.positions = Some(Vec::new());
+		for &byte in &bytes[..header_len] {
+			filter.position.advance(byte);
+		}
+		let mut position = filter.position;
+		filter.push(&bytes[header_len..]);
+		let kept = filter.positions.unwrap_or_default();
+		let mut kept = kept.iter().map(|x| x.offset as usize).peekable();
+
+		let mut result = vec![ByteFate::Header; header_len];
+		let mut header = false;
+		for (offset, &byte) in bytes.iter().enumerate().skip(header_len) {
+			header |= byte == b'>' && position.column == 1;
+			let valid = header || is_valid(byte);
+			result.push(match kept.next_if_eq(&offset) {
+				Some(_) if valid => ByteFate::Kept,
+				Some(_) => ByteFate::Invalid,
+				None if !valid && config.delete_wrong_chars => ByteFate::Deleted,
+				None => ByteFate::Separator,
+			});
+			header &= byte != b'\n';
+			position.advance(byte);
+		}
+		result
+	}
+}
+
+/// A stream that filters through a raw sequence or FASTA
+/// records and pre-processes them according to a given
+/// [`PreprocessorConfig`], without loading the whole
+/// source into memory.
+///
+/// Compressed sources are decompressed before they are
+/// filtered, see [`Decompressor`], and FASTA header lines
+/// are passed through untouched.
+pub struct Preprocessor<R> {
+	reader: BufReader<Decompressor<BufReader<R>>>,
+	filter: Filter,
+}
+
+impl<R: Read> Preprocessor<R> {
+	/// Constructs a [`Preprocessor`] reading from a given
+	/// source, and skips its header, if requested.
+	pub fn new(source: R, config: &PreprocessorConfig) -> io::Result<Self> {
+		let source = Decompressor::new(BufReader::new(source))?;
+		let mut result = Self {
+			reader: BufReader::new(source),
+			filter: Filter::new(config),
+		};
+
+		let mut header = Vec::new();
+		for _ in 0..config.get_header_len() {
+			result.reader.read_until(b'\n', &mut header)?;
+		}
+		for &byte in &header {
+			result.filter.position.advance(byte);
+		}
+		Ok(result)
+	}
+
+	/// Finds the position in the source of the byte at a
+	/// given offset of the pre-processed stream, reading
+	/// the stream from its current position.
+	///
+	/// Returns [`None`] if the stream is shorter than that.
+	pub fn locate(mut self, offset: u64) -> io::Result<Option<SourcePosition>> {
+		self.filter.positions = Some(Vec::new());
+		let mut emitted = 0;
+		loop {
+			let available = self.fill()?;
+			if available == 0 {
+				return Ok(None);
+			}
+			if offset < emitted + available as u64 {
+				let index = (offset - emitted) as usize;
+				return Ok(self.filter.positions.as_ref().map(|x| x[index]));
+			}
+			self.filter.consume(available);
+			emitted += available as u64;
+		}
+	}
+
+	/// Maps the position of an invalid character reported by
+	/// the loader, which only sees the pre-processed stream,
+	/// back onto the source.
+	///
+	/// The stream should not have been read from yet. Other
+	/// errors, and errors that cannot be located, are returned
+	/// as they are.
+	pub fn locate_error(self, err: LoadError) -> LoadError {
+		let LoadError::InvalidCharacter { character, offset, .. } = err else { return err };
+		match self.locate(offset) {
+			Ok(Some(position)) => LoadError::InvalidCharacter {
+				character,
+				offset: position.offset,
+				line: position.line,
+				column: position.column,
+			},
+			_ => err,
+		}
+	}
+
+	/// A helper function that reads the source until some
+	/// bytes are ready to be read from the stream.
+	///
+	/// Returns the amount of bytes ready, 0 at the end
+	/// of the source.
+	fn fill(&mut self) -> io::Result<usize> {
+		let mut byte_buff = [0u8; 4096];
+
+		let mut available = self.filter.get_available();
+		while available == 0 {
+			let len = self.reader.read(&mut byte_buff)?;
+			if len == 0 {
+				return Ok(self.filter.pending.len());
+			}
+			self.filter.push(&byte_buff[..len]);
+			available = self.filter.get_available();
+		}
+		Ok(available)
+	}
+}
+
+impl<R: Read> Read for Preprocessor<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let len = self.fill()?.min(buf.len());
+		buf[..len].copy_from_slice(&self.filter.pending[..len]);
+		self.filter.consume(len);
+		Ok(len)
+	}
+}
+
+impl<R: Read> TrackedRead for Preprocessor<R> {
+	/// Returns the amount of bytes consumed from the source,
+	/// before decompression.
+	fn get_consumed(&self) -> u64 {
+		self.reader.get_consumed()
+	}
+}
+
+/// The filtering state of a [`Preprocessor`],
+/// independent of its source.
+struct Filter {
+	separator: Vec<u8>,
+	delete_wrong_chars: bool,
+	/// Filtered bytes that were not read yet. The last
+	/// few of them may still turn out to be a separator.
+	pending: Vec<u8>,
+	/// Source positions of the pending bytes, if tracked
+	positions: Option<Vec<SourcePosition>>,
+	/// Position of the next byte of the source
+	position: SourcePosition,
+	/// True while inside of a FASTA header line
+	header: bool,
+}
+
+impl Filter {
+	/// Constructs an empty [`Filter`], positioned
+	/// at the start of the source.
+	fn new(config: &PreprocessorConfig) -> Self {
+		Self {
+			separator: config.separator.as_bytes().to_vec(),
+			delete_wrong_chars: config.delete_wrong_chars,
+			pending: Vec::new(),
+			positions: None,
+			position: SourcePosition::default(),
+			header: false,
+		}
+	}
+
+	/// Returns the amount of pending bytes that can
+	/// no longer turn out to be a separator.
+	fn get_available(&self) -> usize {
+		let held = self.separator.len().saturating_sub(1);
+		self.pending.len().saturating_sub(held)
+	}
+
+	/// Drops a given amount of pending bytes.
+	fn consume(&mut self, len: usize) {
+		self.pending.drain(..len);
+		if let Some(positions) = &mut self.positions {
+			positions.drain(..len);
+		}
+	}
+
+	/// Appends the bytes that pass the filters to
+	/// the pending bytes, removing separators.
+	fn push(&mut self, bytes: &[u8]) {
+		let separator_len = self.separator.len();
+		for &ch in bytes {
+			self.header |= ch == b'>' && self.position.column == 1;
+			if self.header || is_valid(ch) || !self.delete_wrong_chars {
+				self.pending.push(ch);
+				if let Some(positions) = &mut self.positions {
+					positions.push(self.position);
+				}
+				if !self.header && separator_len != 0 && self.pending.ends_with(&self.separator) {
+					let len = self.pending.len() - separator_len;
+					self.pending.truncate(len);
+					if let Some(positions) = &mut self.positions {
+						positions.truncate(len);
+					}
+				}
+			}
+			self.header &= ch != b'\n';
+			self.position.advance(ch);
+		}
+	}
+}
+
+/// A helper function that checks whether a byte
+/// is a nucleotide or whitespace.
+fn is_valid(byte: u8) -> bool {
+	byte.is_ascii_whitespace() || IupacNucleotide::parse_raw(byte).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A source that yields at most a given amount
+	/// of bytes on every read.
+	struct Trickle<'a>(&'a [u8], usize);
+
+	impl Read for Trickle<'_> {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			let len = self.0.len().min(self.1).min(buf.len());
+			buf[..len].copy_from_slice(&self.0[..len]);
+			self.0 = &self.0[len..];
+			Ok(len)
+		}
+	}
+
+	/// A helper function that constructs a configuration.
+	fn config(
+		separator: &str,
+		delete_wrong_chars: bool,
+		header_len: Option<u32>,
+	) -> PreprocessorConfig {
+		PreprocessorConfig {
+			separator: separator.to_owned(),
+			delete_wrong_chars,
+			delete_header: header_len.is_some(),
+			header_len: header_len.unwrap_or_default(),
+		}
+	}
+
+	/// A helper function that pre-processes a given text,
+	/// read a given amount of bytes at a time.
+	fn process(text: &str, config: &PreprocessorConfig, chunk: usize) -> String {
+		let mut result = String::new();
+		let source = Trickle(text.as_bytes(), chunk);
+		let mut preprocessor = Preprocessor::new(source, config).unwrap();
+		preprocessor.read_to_string(&mut result).unwrap();
+		result
+	}
+
+	/// A helper function that returns the fates of the
+	/// bytes of a given text, written as single letters.
+	fn classify(text: &str, config: &PreprocessorConfig) -> String {
+		let fates = ByteFate::classify(text.as_bytes(), config);
+		let fates = fates.iter().map(|x| match x {
+			ByteFate::Kept => 'K',
+			ByteFate::Invalid => 'I',
+			ByteFate::Header => 'H',
+			ByteFate::Separator => 'S',
+			ByteFate::Deleted => 'D',
+		});
+		fates.collect()
+	}
+
+	#[test]
+	fn separators_are_removed_across_reads() {
+		let config = config(", ", false, None);
+		for chunk in [1, 2, 3, 4096] {
+			assert_eq!(process("AC, GU, ,  A,", &config, chunk), "ACGU A,");
+		}
+		assert_eq!(classify("AC, GU, ,  A,", &config), "KKSSKKSSSSKKI");
+		// The end of a source may turn out to be a part of a separator
+		assert_eq!(classify("AC,", &config), "KKI");
+
+		// Overlapping occurrences are removed from the left
+		let config = self::config("::", false, None);
+		for chunk in [1, 2, 4096] {
+			assert_eq!(process("A:::C::::G", &config, chunk), "A:CG");
+		}
+		assert_eq!(classify("A:::C::::G", &config), "KSSIKSSSSK");
+		// Separators are never looked for in FASTA headers
+		assert_eq!(process(">a::b\nA::C", &config, 1), ">a::b\nAC");
+	}
+
+	#[test]
+	fn headers_are_skipped() {
+		let text = "first\nsecond\n>seq\nACGU\n";
+		assert_eq!(process(text, &config("", false, Some(0)), 1), text);
+		let second = "second\n>seq\nACGU\n";
+		assert_eq!(process(text, &config("", false, Some(1)), 1), second);
+		assert_eq!(process(text, &config("", false, Some(10)), 1), "");
+		assert_eq!(process(text, &config("", false, None), 1), text);
+		assert!(config("", false, Some(0)).is_identity());
+		assert!(!config("", false, Some(1)).is_identity());
+
+		let fates = classify(text, &config("", false, Some(1)));
+		assert_eq!(fates, "HHHHHHKIKIKKKKKKKKKKKKK");
+		let fates = classify(text, &config("", false, Some(10)));
+		assert_eq!(fates, "H".repeat(text.len()));
+
+		let crlf = "first\r\n>seq\r\nAC\r\n";
+		assert_eq!(
+			process(crlf, &config("", true, Some(1)), 1),
+			">seq\r\nAC\r\n"
+		);
+		assert_eq!(
+			classify(crlf, &config("", true, Some(1))),
+			"HHHHHHHKKKKKKKKKK"
+		);
+	}
+
+	#[test]
+	fn wrong_characters_are_optionally_deleted() {
+		let text = ">seq 1\nAXC-G\nŻU\n";
+		assert_eq!(process(text, &config("", false, None), 1), text);
+		assert_eq!(
+			process(text, &config("", true, None), 1),
+			">seq 1\nAC-G\nU\n"
+		);
+		assert_eq!(
+			classify(text, &config("", false, None)),
+			"KKKKKKKKIKKKKIIKK"
+		);
+		assert_eq!(classify(text, &config("", true, None)), "KKKKKKKKDKKKKDDKK");
+	}
+
+	#[test]
+	fn positions_are_located_in_the_source() {
+		let text = "title\r\nA--C\nGXŻU\n";
+		let config = config("--", true, Some(1));
+		assert_eq!(process(text, &config, 4096), "AC\nGU\n");
+
+		let locate = |offset| {
+			let preprocessor = Preprocessor::new(text.as_bytes(), &config).unwrap();
+			let position = preprocessor.locate(offset).unwrap()?;
+			Some((position.offset, position.line, position.column))
+		};
+		assert_eq!(locate(0), Some((7, 2, 1)));
+		assert_eq!(locate(1), Some((10, 2, 4)));
+		assert_eq!(locate(3), Some((12, 3, 1)));
+		assert_eq!(locate(4), Some((16, 3, 4)));
+		assert_eq!(locate(6), None);
+
+		let error = LoadError::InvalidCharacter {
+			character: 'U',
+			offset: 4,
+			line: 2,
+			column: 2,
+		};
+		let preprocessor = Preprocessor::new(text.as_bytes(), &config).unwrap();
+		match preprocessor.locate_error(error) {
+			LoadError::InvalidCharacter {
+				character,
+				offset,
+				line,
+				column,
+			} => assert_eq!((character, offset, line, column), ('U', 16, 3, 4)),
+			err => panic!("expected an invalid character, got: {err}"),
+		}
+
+		let error = LoadError::InvalidRecord {
+			reason: "test",
+			offset: 4,
+			line: 2,
+		};
+		let preprocessor = Preprocessor::new(text.as_bytes(), &config).unwrap();
+		match preprocessor.locate_error(error) {
+			LoadError::InvalidRecord { offset, line, .. } => assert_eq!((offset, line), (4, 2)),
+			err => panic!("expected an invalid record, got: {err}"),
+		}
+	}
+}
@@ -5,13 +5,18 @@ use std::{
 	ops::{Deref, DerefMut},
 };
 
-use crate::{AminoString, Codon};
+use crate::{AminoString, Codon, Frame, Location};
 
 /// An abstraction over [`AminoString`] that is
 /// guaranteed to be a proper protein sequence.
+///
+/// The same sequence may be coded at many loci of the
+/// source, hence a [`Protein`] keeps the [`Location`] of
+/// every occurrence it was found at.
 #[derive(Clone)]
 pub struct Protein {
 	string: AminoString,
+	locations: Vec<Location>,
 }
 
 impl Protein {
@@ -20,8 +25,79 @@ impl Protein {
 	pub fn from(codons: Vec<Codon>) -> Self {
 		Self {
 			string: AminoString::from(codons),
+			locations: Vec::new(),
 		}
 	}
+
+	/// Constructs a [`Protein`] from an [`AminoString`]
+	/// found at a given [`Location`] of the source
+	/// nucleotide sequence.
+	pub fn with_location(string: AminoString, location: Location) -> Self {
+		Self {
+			string,
+			locations: vec![location],
+		}
+	}
+
+	/// Records another occurrence of this protein.
+	pub fn add_location(&mut self, location: Location) {
+		self.locations.push(location);
+	}
+
+	/// Moves all the occurrences of another instance of
+	/// the same protein into this one.
+	///
+	/// The merged protein keeps the originating triplets
+	/// (see [`AminoString::get_triplets`]) of its earliest
+	/// occurrence, regardless of the order of merging.
+	pub fn merge(&mut self, mut other: Protein) {
+		let first = |x: &Protein| x.locations.iter().map(Self::order).min();
+		if first(&other) < first(self) {
+			std::mem::swap(&mut self.string, &mut other.string);
+		}
+		self.locations.append(&mut other.locations);
+	}
+
+	/// Sorts the occurrences of this protein by their
+	/// record and position in the source sequence.
+	pub fn sort_locations(&mut self) {
+		self.locations.sort_unstable_by_key(Self::order);
+	}
+
+	/// Replaces the [`Location`] of every occurrence of this
+	/// protein with the one returned by `f`.
+	pub(crate) fn map_locations(&mut self, f: impl Fn(Location) -> Location) {
+		for location in &mut self.locations {
+			*location = f(*location);
+		}
+	}
+
+	/// Returns the [`Location`] of every occurrence of this
+	/// protein.
+	///
+	/// Returns an empty slice if the protein was not read
+	/// from a nucleotide sequence.
+	pub fn get_locations(&self) -> &[Location] {
+		&self.locations
+	}
+
+	/// Returns the amount of times this protein was found
+	/// in the source sequence.
+	pub fn get_count(&self) -> usize {
+		self.locations.len()
+	}
+
+	/// A helper function that returns the key occurrences
+	/// are ordered by.
+	fn order(location: &Location) -> (usize, usize, usize, Frame, Option<usize>) {
+		(
+			location.get_record(),
+			location.get_start(),
+			location.get_end(),
+			location.get_frame(),
+			location.get_feature(),
+		)
+	}
 }
 
 impl Deref for Protein {
@@ -0,0 +1,506 @@
+//! The module that implements [`ChunkScan`] and [`FrameCarry`]
+
+use std::ops::Range;
+
+use crate::{
+	AminoString, Codon, Frame, IupacNucleotide, Location, OrfDefinition, OrfOptions, Strand,
+};
+
+use super::{
+	hits::{CodonHits, CodonSets},
+	key::Key,
+	scanner::OrfScanner,
+};
+
+/// A codon, as read on the strand of its frame, paired
+/// with the offset of its first nucleotide on the
+/// forward strand.
+type Positioned = ([IupacNucleotide; 3], usize);
+
+/// Consecutive codons of a single reading frame, as read
+/// on the strand of the frame, in the order of the forward
+/// strand.
+#[derive(Default)]
+struct Region {
+	/// Offset of the first codon on the forward strand
+	start: usize,
+	triplets: Vec<[IupacNucleotide; 3]>,
+}
+
+impl Region {
+	/// Collects the codons of a given strand found at positions
+	/// `range.start`, `range.start + 3` and so on, up to
+	/// `range.end`, of given nucleotide codes, which start at
+	/// offset `start` of the record.
+	fn collect(codes: &[u8], range: Range<usize>, strand: Strand, start: usize) -> Self {
+		let positions = range.clone().step_by(3);
+		let triplets = positions.map(|x| read_codon(codes, x, strand));
+		Self {
+			start: start + range.start,
+			triplets: triplets.collect(),
+		}
+	}
+
+	/// Appends the codons of the region that directly
+	/// follows this one on the forward strand.
+	fn append(&mut self, other: Region) {
+		match self.triplets.is_empty() {
+			true => *self = other,
+			false => self.triplets.extend(other.triplets),
+		}
+	}
+
+	/// Drops the codons read before the first codon that may
+	/// start translation, as they can not belong to any ORF.
+	///
+	/// Regions of the reverse strand are left as they are, as
+	/// their ORFs are read from the stop codon that precedes
+	/// them, so whether a codon belongs to one is only known
+	/// once a start codon follows it. So are the regions
+	/// scanned for ORFs spanning between stop codons.
+	fn trim(&mut self, options: &OrfOptions, strand: Strand) {
+		if strand == Strand::Reverse || options.definition == OrfDefinition::StopToStop {
+			return;
+		}
+		let mut triplets = self.triplets.iter();
+		let first = triplets.position(|&[a, b, c]| options.is_start(a, b, c));
+		let first = first.unwrap_or(self.triplets.len());
+		self.triplets.drain(..first);
+		self.start += 3 * first;
+	}
+
+	/// Scans the codons of the region, along with the stop
+	/// codons around it, see [`scan_codons`].
+	fn scan(
+		&self,
+		options: &OrfOptions,
+		frame: Frame,
+		before: Option<Positioned>,
+		after: Option<Positioned>,
+		emit: &mut impl FnMut(Key, AminoString, Location),
+	) {
+		let codons = self.triplets.iter().enumerate();
+		let codons = codons.map(|(index, &x)| (x, self.start + 3 * index));
+		scan_codons(options, frame, codons, before, after, emit);
+	}
+}
+
+/// The codons of a single reading frame of a chunk,
+/// split at the stop codons.
+#[derive(Default)]
+struct FrameScan {
+	/// Codons before the first stop codon, or all the
+	/// codons if there is none
+	head: Region,
+	/// The first and the last stop codon, which may be
+	/// the same one
+	stops: Option<(Positioned, Positioned)>,
+	/// Codons after the last stop codon
+	tail: Region,
+}
+
+/// The result of scanning a chunk of a record, which holds
+/// the codons of every scanned frame that may belong to ORFs
+/// spanning the neighbouring chunks.
+///
+/// The ORFs between two stop codons of the same chunk are
+/// found by the scan itself, so the chunks can be scanned
+/// independently, in any order.
+#[derive(Default)]
+pub(crate) struct ChunkScan {
+	frames: Vec<FrameScan>,
+}
+
+impl ChunkScan {
+	/// Scans the codons of given reading frames which start
+	/// within the first `len` of given nucleotide codes (see
+	/// [`IupacNucleotide::from_code`]), found at offset `start`
+	/// of the record. The remaining nucleotides complete the
+	/// codons cut off by the end of the chunk.
+	///
+	/// Every protein found is passed to `emit`. The frames
+	/// of the reverse strand are numbered from the start
+	/// of the record, see [`FrameCarry::fix_frame`].
+	///
+	/// Only the codons found in bulk to possibly stop or start
+	/// translation are translated one by one, see [`CodonHits`],
+	/// until an ORF is opened.
+	pub fn run(
+		codes: &[u8],
+		len: usize,
+		start: usize,
+		options: &OrfOptions,
+		frames: &[Frame],
+		emit: &mut impl FnMut(Key, AminoString, Location),
+	) -> Self {
+		let sets = CodonSets::new(options);
+		let strands = [Strand::Forward, Strand::Reverse].map(|strand| {
+			frames.iter().any(|x| x.get_strand() == strand).then(|| {
+				let stops = CodonHits::find(codes, sets.get_stops(strand));
+				let starts = sets.get_starts(strand).map(|x| CodonHits::find(codes, x));
+				(stops, starts)
+			})
+		});
+
+		let frames = frames.iter().map(|&frame| {
+			let strand = frame.get_strand();
+			let Some((stops, starts)) = &strands[strand as usize] else {
+				return FrameScan::default();
+			};
+			let offset = frame.get_offset() as usize;
+			let skip = (offset + 3 - start % 3) % 3;
+			let end = len.min(codes.len().saturating_sub(2));
+
+			let mut result = FrameScan::default();
+			let mut from = skip;
+			for position in stops.iter_frame(skip, end) {
+				let triplet = read_codon(codes, position, strand);
+				let [a, b, c] = triplet;
+				if options.translate(a, b, c) != Codon::STOP {
+					continue;
+				}
+
+				let stop = (triplet, start + position);
+				let region = from..position;
+				result.stops = match result.stops {
+					Some((first, last)) => {
+						let region = Self::narrow(region, starts.as_ref(), strand);
+						let codons = region.step_by(3);
+						let read = |x| (read_codon(codes, x, strand), start + x);
+						let codons = codons.map(read);
+						scan_codons(options, frame, codons, Some(last), Some(stop), emit);
+						Some((first, stop))
+					}
+					None => {
+						result.head = Region::collect(codes, region, strand, start);
+						Some((stop, stop))
+					}
+				};
+				from = position + 3;
+			}
+
+			let rest = Region::collect(codes, from..end.max(from), strand, start);
+			match result.stops {
+				Some(_) => result.tail = rest,
+				None => result.head = rest,
+			}
+			result
+		});
+
+		Self {
+			frames: frames.collect(),
+		}
+	}
+
+	/// A helper function that narrows down a region between two
+	/// stop codons of a chunk to the codons read since the first
+	/// codon that may start translation, in the order of the
+	/// strand, as the ones before it can not belong to any ORF.
+	///
+	/// The region is left as it is if ORFs are not opened
+	/// by start codons.
+	fn narrow(region: Range<usize>, starts: Option<&CodonHits>, strand: Strand) -> Range<usize> {
+		let Some(starts) = starts else { return region };
+		let mut positions = starts.iter_frame(region.start, region.end);
+		let first = match strand {
+			Strand::Forward => positions.next(),
+			Strand::Reverse => positions.last(),
+		};
+		match (first, strand) {
+			(Some(x), Strand::Forward) => x..region.end,
+			(Some(x), Strand::Reverse) => region.start..x + 3,
+			(None, _) => region.start..region.start,
+		}
+	}
+}
+
+/// The codons of a single reading frame of a record which
+/// have been read, but could not be scanned yet, as no stop
+/// codon has followed them.
+///
+/// On the forward strand, only the codons since the first
+/// start codon are kept, so the memory used depends on the
+/// length of the longest ORF rather than the longest region
+/// free of stop codons, see [`Region::trim`].
+#[derive(Default)]
+pub(crate) struct FrameCarry {
+	/// The stop codon preceding the codons, if any
+	stop: Option<Positioned>,
+	region: Region,
+}
+
+impl FrameCarry {
+	/// Constructs a [`FrameCarry`] for every frame
+	/// of a new record.
+	pub fn start(frames: usize) -> Vec<Self> {
+		(0..frames).map(|_| Self::default()).collect()
+	}
+
+	/// Appends the codons of the chunk that follows the ones
+	/// read so far, scanning the ORFs completed by the chunk,
+	/// then drops the ones that can not belong to any ORF.
+	///
+	/// The carries are given in the order of the frames
+	/// the chunk was scanned in.
+	pub fn push_all(
+		carries: &mut [Self],
+		scan: ChunkScan,
+		options: &OrfOptions,
+		frames: &[Frame],
+		mut emit: impl FnMut(Key, AminoString, Location),
+	) {
+		for ((carry, frame), scan) in carries.iter_mut().zip(frames).zip(scan.frames) {
+			let Self { stop, region } = carry;
+			region.append(scan.head);
+			if let Some((first, last)) = scan.stops {
+				region.scan(options, *frame, *stop, Some(first), &mut emit);
+				*stop = Some(last);
+				*region = scan.tail;
+			}
+			region.trim(options, frame.get_strand());
+		}
+	}
+
+	/// Scans the codons that were not followed by a stop
+	/// codon before the end of the record.
+	pub fn finish(
+		self,
+		options: &OrfOptions,
+		frame: Frame,
+		emit: &mut impl FnMut(Key, AminoString, Location),
+	) {
+		self.region.scan(options, frame, self.stop, None, emit);
+	}
+
+	/// Moves a [`Location`] found in a record of a given length
+	/// to its actual [`Frame`]. Frames of the reverse strand are
+	/// numbered from the end of the record, which is only known
+	/// once it has been read, so until then their offsets are
+	/// counted from its start instead.
+	pub fn fix_frame(location: Location, length: usize) -> Location {
+		let frame = location.get_frame();
+		if frame.get_strand() == Strand::Forward {
+			return location;
+		}
+		let offset = frame.get_offset() as usize;
+		location.with_frame(Frame::new(Strand::Reverse, (length % 3 + 3 - offset) as u8))
+	}
+}
+
+/// A helper function that reads the codon of a given strand
+/// found at a given position of given nucleotide codes, as
+/// read on that strand.
+fn read_codon(codes: &[u8], position: usize, strand: Strand) -> [IupacNucleotide; 3] {
+	let [a, b, c] = [0, 1, 2].map(|x| IupacNucleotide::from_code(codes[position + x]));
+	match strand {
+		Strand::Forward => [a, b, c],
+		Strand::Reverse => [c, b, a].map(|x| x.complement()),
+	}
+}
+
+/// A helper function that passes codons of a region, given in
+/// the order of the forward strand, to an [`OrfScanner`] in the
+/// order of translation, along with the stop codons that precede
+/// and follow the region on the forward strand, if any. A missing
+/// stop codon stands for an end of the record.
+fn scan_codons(
+	options: &OrfOptions,
+	frame: Frame,
+	codons: impl DoubleEndedIterator<Item = Positioned>,
+	before: Option<Positioned>,
+	after: Option<Positioned>,
+	emit: &mut impl FnMut(Key, AminoString, Location),
+) {
+	let (first, last) = match frame.get_strand() {
+		Strand::Forward => (before, after),
+		Strand::Reverse => (after, before),
+	};
+
+	let mut scanner = match first {
+		Some(_) => OrfScanner::after_stop(options, frame),
+		None => OrfScanner::new(options, frame),
+	};
+	match frame.get_strand() {
+		Strand::Forward => codons.for_each(|(x, at)| scanner.push(x, at, emit)),
+		Strand::Reverse => codons.rev().for_each(|(x, at)| scanner.push(x, at, emit)),
+	}
+	match last {
+		Some((triplet, at)) => scanner.push(triplet, at, emit),
+		None => scanner.finish(emit),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{GeneticCode, Nucleotide, StartCodons};
+
+	/// Proteins found, along with their locations.
+	type Found = Vec<(String, Location)>;
+
+	/// A helper function that generates pseudo-random numbers.
+	fn next(state: &mut u64) -> u64 {
+		*state ^= *state << 13;
+		*state ^= *state >> 7;
+		*state ^= *state << 17;
+		*state
+	}
+
+	/// A helper function that generates a pseudo-random sequence
+	/// of nucleotide codes, with ambiguity codes among them.
+	fn random_codes(len: usize, state: &mut u64) -> Vec<u8> {
+		let codes = (0..len).map(|_| match next(state) % 30 {
+			0 => 4 + (*state / 30 % 11) as u8,
+			x => (x % 4) as u8,
+		});
+		codes.collect()
+	}
+
+	/// A helper function that sorts the proteins found
+	/// in the order of their locations.
+	fn sorted(mut found: Found) -> Found {
+		found.sort_by_key(|(string, x)| {
+			let frame = (x.get_strand() as u8, x.get_frame().get_offset());
+			(x.get_start(), x.get_end(), frame, string.clone())
+		});
+		found
+	}
+
+	/// A helper function that scans every codon of given
+	/// frames, one by one, with a single [`OrfScanner`].
+	fn scan_plain(codes: &[u8], options: &OrfOptions, frames: &[Frame]) -> Found {
+		let mut found = Vec::new();
+		let mut emit = |_, string: AminoString, x| found.push((string.to_string(), x));
+		for &frame in frames {
+			let mut scanner = OrfScanner::new(options, frame);
+			let offset = frame.get_offset() as usize;
+			let count = codes.len().saturating_sub(offset) / 3;
+			for index in 0..count {
+				let position = match frame.get_strand() {
+					Strand::Forward => offset + 3 * index,
+					Strand::Reverse => codes.len() - offset - 3 * index - 3,
+				};
+				let triplet = read_codon(codes, position, frame.get_strand());
+				scanner.push(triplet, position, &mut emit);
+			}
+			scanner.finish(&mut emit);
+		}
+		sorted(found)
+	}
+
+	/// A helper function that splits given codes into chunks of
+	/// pseudo-random lengths, scans them in given frames, then
+	/// puts the results together, as the loader does.
+	fn scan_chunked(
+		codes: &[u8],
+		options: &OrfOptions,
+		frames: &[Frame],
+		state: &mut u64,
+	) -> Found {
+		let mut found = Vec::new();
+		let mut emit = |_, string: AminoString, x| found.push((string.to_string(), x));
+		let mut carries = FrameCarry::start(frames.len());
+		let mut start = 0;
+		while start < codes.len() {
+			let len = (1 + next(state) % 200) as usize;
+			let len = len.min(codes.len() - start);
+			let chunk = &codes[start..codes.len().min(start + len + 2)];
+			let scan = ChunkScan::run(chunk, len, start, options, frames, &mut emit);
+			FrameCarry::push_all(&mut carries, scan, options, frames, &mut emit);
+			start += len;
+		}
+		for (carry, &frame) in carries.into_iter().zip(frames) {
+			carry.finish(options, frame, &mut emit);
+		}
+
+		let fix = |(string, x)| (string, FrameCarry::fix_frame(x, codes.len()));
+		sorted(found.into_iter().map(fix).collect())
+	}
+
+	#[test]
+	fn chunks_match_a_plain_scan() {
+		let codes = [
+			GeneticCode::STANDARD,
+			GeneticCode::VERTEBRATE_MITOCHONDRIAL,
+			GeneticCode::CILIATE_NUCLEAR,
+			GeneticCode::from_id(11).unwrap(),
+		];
+		let starts = [
+			StartCodons::Aug,
+			StartCodons::Table,
+			StartCodons::Custom(vec![[Nucleotide::C, Nucleotide::U, Nucleotide::G]]),
+		];
+		let mut options = Vec::new();
+		for (index, code) in codes.into_iter().enumerate() {
+			for (nested, starts) in starts.iter().enumerate() {
+				options.push(OrfOptions {
+					code,
+					both_strands: true,
+					starts: starts.clone(),
+					nested: nested % 2 == 1,
+					partial: index % 2 == 0,
+					..Default::default()
+				});
+			}
+			options.push(OrfOptions {
+				code,
+				both_strands: true,
+				min_length: 3,
+				max_length: Some(20),
+				definition: OrfDefinition::StopToStop,
+				partial: index % 2 == 1,
+				..Default::default()
+			});
+		}
+
+		let mut state = 0x9E37_79B9_7F4A_7C15;
+		let mut total = 0;
+		for options in &options {
+			for _ in 0..20 {
+				let len = (next(&mut state) % 1500) as usize;
+				let codes = random_codes(len, &mut state);
+				let expected = scan_plain(&codes, options, &Frame::ALL);
+				let found = scan_chunked(&codes, options, &Frame::ALL, &mut state);
+				assert_eq!(found.len(), expected.len());
+				total += found.len();
+				for ((string, location), expected) in found.iter().zip(&expected) {
+					assert_eq!(string, &expected.0);
+					assert!(*location == expected.1);
+				}
+			}
+		}
+		assert!(total > 1000);
+	}
+
+	#[test]
+	fn carries_keep_open_orfs_only() {
+		// Alanines with no stop codon in any frame of either strand,
+		// and a single start codon on the forward strand
+		let text = format!("{}AUG{}", "GCU".repeat(1000), "GCU".repeat(1000));
+		let codes = text.bytes().map(IupacNucleotide::parse_raw);
+		let codes: Vec<_> = codes.map(|x| x.unwrap() as u8).collect();
+
+		let options = OrfOptions {
+			both_strands: true,
+			partial: true,
+			..Default::default()
+		};
+		let mut emit = |_, _, _| {};
+		let mut carries = FrameCarry::start(6);
+		let mut longest = [0; 6];
+		for start in (0..codes.len()).step_by(300) {
+			let chunk = &codes[start..codes.len().min(start + 302)];
+			let len = chunk.len().min(300);
+			let scan = ChunkScan::run(chunk, len, start, &options, &Frame::ALL, &mut emit);
+			FrameCarry::push_all(&mut carries, scan, &options, &Frame::ALL, &mut emit);
+			for (longest, carry) in longest.iter_mut().zip(&carries) {
+				*longest = carry.region.triplets.len().max(*longest);
+			}
+		}
+
+		// Only the codons since the start codon are kept on the
+		// forward strand, while whole regions are on the reverse one
+		assert_eq!(longest, [1001, 0, 0, 2001, 1000, 2000]);
+		assert_eq!(carries[0].region.start, 3000);
+	}
+}
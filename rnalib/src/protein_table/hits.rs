@@ -0,0 +1,345 @@
+//! The module that implements [`CodonHits`] and [`CodonSets`]
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+use crate::{Codon, IupacNucleotide, OrfDefinition, OrfOptions, Strand};
+
+/// The lowest code of an ambiguity code, see
+/// [`IupacNucleotide::from_code`]; the four nucleotides
+/// have lower ones.
+const AMBIGUOUS: u8 = 4;
+
+/// The codons that may stop or start translation on each
+/// strand, as read on the forward strand, encoded as in
+/// [`IupacNucleotide::from_code`].
+pub(crate) struct CodonSets {
+	/// Stop codons of the forward and the reverse strand
+	stops: [Vec<[u8; 3]>; 2],
+	/// Start codons of the forward and the reverse strand,
+	/// empty if start codons do not matter
+	starts: [Vec<[u8; 3]>; 2],
+}
+
+impl CodonSets {
+	/// Collects the unambiguous codons that stop or start
+	/// translation according to given [`OrfOptions`].
+	pub fn new(options: &OrfOptions) -> Self {
+		let mut result = Self {
+			stops: Default::default(),
+			starts: Default::default(),
+		};
+		let starts = options.definition == OrfDefinition::StartToStop;
+
+		for code in 0..64u8 {
+			let codon = [code >> 4, code >> 2 & 3, code & 3];
+			let [a, b, c] = codon.map(IupacNucleotide::from_code);
+			let reverse = [c, b, a].map(|x| x.complement());
+			for (index, [a, b, c]) in [[a, b, c], reverse].into_iter().enumerate() {
+				if options.translate(a, b, c) == Codon::STOP {
+					result.stops[index].push(codon);
+				}
+				if starts && options.is_start(a, b, c) {
+					result.starts[index].push(codon);
+				}
+			}
+		}
+		result
+	}
+
+	/// Returns the stop codons of a given strand.
+	pub fn get_stops(&self, strand: Strand) -> &[[u8; 3]] {
+		&self.stops[strand as usize]
+	}
+
+	/// Returns the start codons of a given strand, or
+	/// [`None`] if ORFs are not opened by start codons.
+	pub fn get_starts(&self, strand: Strand) -> Option<&[[u8; 3]]> {
+		let starts = &self.starts[strand as usize];
+		(!starts.is_empty()).then_some(starts)
+	}
+}
+
+/// The positions of a sequence of nucleotides, encoded as in
+/// [`IupacNucleotide::from_code`], at which a codon from a
+/// given set may start.
+///
+/// Besides the codons of the set, every codon holding an
+/// ambiguity code is a hit, as it may stand for one of them.
+/// Codons cut off by the end of the sequence are never hits.
+///
+/// The sequence is searched in bulk, with AVX2 or SSE2 where
+/// available, and the results are exactly the same as those
+/// of searching one position at a time.
+#[derive(PartialEq, Eq)]
+pub(crate) struct CodonHits {
+	/// One bit per position, the first one in
+	/// the lowest bit of the first word
+	words: Vec<u64>,
+}
+
+impl CodonHits {
+	/// Finds the hits of given codons in a given
+	/// sequence, using SIMD instructions if possible.
+	pub fn find(codes: &[u8], codons: &[[u8; 3]]) -> Self {
+		let mut result = Self::new(codes.len());
+		let done = result.fill_simd(codes, codons);
+		result.fill_scalar(codes, codons, done);
+		result
+	}
+
+	/// Finds the hits of given codons in a given sequence,
+	/// one position at a time.
+	#[cfg(test)]
+	fn find_scalar(codes: &[u8], codons: &[[u8; 3]]) -> Self {
+		let mut result = Self::new(codes.len());
+		result.fill_scalar(codes, codons, 0);
+		result
+	}
+
+	/// Returns an iterator over the hits at positions `from`,
+	/// `from + 3`, `from + 6` and so on, up to `to`, exclusive,
+	/// i.e. the hits within a single reading frame.
+	pub fn iter_frame(&self, from: usize, to: usize) -> impl Iterator<Item = usize> + '_ {
+		let to = to.min(self.words.len() * 64);
+		let words = from / 64..to.div_ceil(64);
+		let positions = words.flat_map(move |index| {
+			let mut word = self.words[index];
+			std::iter::from_fn(move || {
+				let bit = word.trailing_zeros();
+				word &= word.wrapping_sub(1);
+				(bit < 64).then_some(index * 64 + bit as usize)
+			})
+		});
+		positions.filter(move |&x| x >= from && x < to && (x - from).is_multiple_of(3))
+	}
+
+	/// A helper function that constructs a [`CodonHits`]
+	/// without hits for a sequence of a given length.
+	fn new(len: usize) -> Self {
+		Self {
+			words: vec![0; len.div_ceil(64)],
+		}
+	}
+
+	/// A helper function that marks the hits in the lowest
+	/// bits of `mask`, starting at a given position, which
+	/// is a multiple of the width of the mask.
+	fn set(&mut self, position: usize, mask: u64) {
+		self.words[position / 64] |= mask << (position % 64);
+	}
+
+	/// A helper function that finds the hits starting at
+	/// the positions from `from` on, one at a time.
+	fn fill_scalar(&mut self, codes: &[u8], codons: &[[u8; 3]], from: usize) {
+		for (offset, codon) in codes.windows(3).enumerate().skip(from) {
+			if codon.iter().any(|&x| x >= AMBIGUOUS) || codons.iter().any(|x| x == codon) {
+				self.set(offset, 1);
+			}
+		}
+	}
+
+	/// A helper function that finds the hits starting at
+	/// the first positions of the sequence with the widest
+	/// SIMD instructions available.
+	///
+	/// Returns the amount of positions searched.
+	#[cfg(target_arch = "x86_64")]
+	fn fill_simd(&mut self, codes: &[u8], codons: &[[u8; 3]]) -> usize {
+		if is_x86_feature_detected!("avx2") {
+			// SAFETY: The CPU has just been checked to support AVX2.
+			return unsafe { self.fill_avx2(codes, codons) };
+		}
+		self.fill_sse2(codes, codons)
+	}
+
+	/// A helper function that leaves all the positions
+	/// to the scalar search, as there are no supported
+	/// SIMD instructions on this architecture.
+	#[cfg(not(target_arch = "x86_64"))]
+	fn fill_simd(&mut self, _codes: &[u8], _codons: &[[u8; 3]]) -> usize {
+		0
+	}
+
+	/// A helper function that finds the hits starting at the
+	/// first positions of the sequence, 16 at a time, with
+	/// SSE2 instructions, which every x86-64 CPU supports.
+	///
+	/// Returns the amount of positions searched.
+	#[cfg(target_arch = "x86_64")]
+	fn fill_sse2(&mut self, codes: &[u8], codons: &[[u8; 3]]) -> usize {
+		const WIDTH: usize = 16;
+		let mut position = 0;
+		// SAFETY: SSE2 is a part of the x86-64 baseline, and every load
+		// reads the WIDTH bytes at an offset of at most 2 from a position
+		// for which `position + WIDTH + 2 <= codes.len()` holds.
+		unsafe {
+			let max_unambiguous = _mm_set1_epi8(AMBIGUOUS as i8 - 1);
+			let codons: Vec<_> = codons
+				.iter()
+				.map(|x| x.map(|x| _mm_set1_epi8(x as i8)))
+				.collect();
+			while position + WIDTH + 2 <= codes.len() {
+				let pointer = codes.as_ptr().add(position);
+				let first = _mm_loadu_si128(pointer.cast());
+				let second = _mm_loadu_si128(pointer.add(1).cast());
+				let third = _mm_loadu_si128(pointer.add(2).cast());
+
+				let max = _mm_max_epu8(_mm_max_epu8(first, second), third);
+				let mut hits = _mm_cmpgt_epi8(max, max_unambiguous);
+				for [a, b, c] in &codons {
+					let a = _mm_cmpeq_epi8(first, *a);
+					let ab = _mm_and_si128(a, _mm_cmpeq_epi8(second, *b));
+					let abc = _mm_and_si128(ab, _mm_cmpeq_epi8(third, *c));
+					hits = _mm_or_si128(hits, abc);
+				}
+				self.set(position, _mm_movemask_epi8(hits) as u16 as u64);
+				position += WIDTH;
+			}
+		}
+		position
+	}
+
+	/// A helper function that finds the hits starting at the
+	/// first positions of the sequence, 32 at a time, with
+	/// AVX2 instructions.
+	///
+	/// Returns the amount of positions searched.
+	///
+	/// # Safety
+	///
+	/// The CPU has to support AVX2.
+	#[cfg(target_arch = "x86_64")]
+	#[target_feature(enable = "avx2")]
+	unsafe fn fill_avx2(&mut self, codes: &[u8], codons: &[[u8; 3]]) -> usize {
+		const WIDTH: usize = 32;
+		let max_unambiguous = _mm256_set1_epi8(AMBIGUOUS as i8 - 1);
+		let codons: Vec<_> = codons
+			.iter()
+			.map(|x| x.map(|x| _mm256_set1_epi8(x as i8)))
+			.collect();
+
+		let mut position = 0;
+		while position + WIDTH + 2 <= codes.len() {
+			// SAFETY: Every load reads the WIDTH bytes at an offset of at most 2
+			// from a position for which `position + WIDTH + 2 <= codes.len()` holds.
+			let (first, second, third) = unsafe {
+				let pointer = codes.as_ptr().add(position);
+				(
+					_mm256_loadu_si256(pointer.cast()),
+					_mm256_loadu_si256(pointer.add(1).cast()),
+					_mm256_loadu_si256(pointer.add(2).cast()),
+				)
+			};
+
+			let max = _mm256_max_epu8(_mm256_max_epu8(first, second), third);
+			let mut hits = _mm256_cmpgt_epi8(max, max_unambiguous);
+			for [a, b, c] in &codons {
+				let a = _mm256_cmpeq_epi8(first, *a);
+				let ab = _mm256_and_si256(a, _mm256_cmpeq_epi8(second, *b));
+				let abc = _mm256_and_si256(ab, _mm256_cmpeq_epi8(third, *c));
+				hits = _mm256_or_si256(hits, abc);
+			}
+			self.set(position, _mm256_movemask_epi8(hits) as u32 as u64);
+			position += WIDTH;
+		}
+		position
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{GeneticCode, StartCodons};
+
+	/// A helper function that generates a pseudo-random sequence
+	/// of nucleotide codes, with ambiguity codes among them.
+	fn random_codes(len: usize, seed: u64) -> Vec<u8> {
+		let mut state = seed | 1;
+		(0..len)
+			.map(|_| {
+				state ^= state << 13;
+				state ^= state >> 7;
+				state ^= state << 17;
+				match state % 50 {
+					0 => AMBIGUOUS + (state / 50 % 11) as u8,
+					x => (x % 4) as u8,
+				}
+			})
+			.collect()
+	}
+
+	/// A helper function that returns the codon sets
+	/// searched for by the tests.
+	fn codon_sets() -> Vec<Vec<[u8; 3]>> {
+		let mut result = vec![
+			Vec::new(),
+			(0..64).map(|x| [x >> 4, x >> 2 & 3, x & 3]).collect(),
+		];
+		for &code in GeneticCode::ALL {
+			let options = OrfOptions {
+				code,
+				starts: StartCodons::Table,
+				..Default::default()
+			};
+			let sets = CodonSets::new(&options);
+			for strand in [Strand::Forward, Strand::Reverse] {
+				result.push(sets.get_stops(strand).to_vec());
+				result.extend(sets.get_starts(strand).map(|x| x.to_vec()));
+			}
+		}
+		result
+	}
+
+	#[test]
+	fn simd_matches_scalar() {
+		for set in codon_sets() {
+			for len in (0..300).chain([4096, 65536 + 7]) {
+				let codes = random_codes(len, len as u64 + set.len() as u64);
+				let simd = CodonHits::find(&codes, &set);
+				let scalar = CodonHits::find_scalar(&codes, &set);
+				assert!(simd == scalar, "length {len}, {} codons", set.len());
+			}
+		}
+	}
+
+	#[cfg(target_arch = "x86_64")]
+	#[test]
+	fn every_instruction_set_matches_scalar() {
+		for set in codon_sets() {
+			for len in [0, 1, 17, 18, 19, 33, 34, 35, 100, 1000] {
+				let codes = random_codes(len, 7 * len as u64 + 1);
+				let scalar = CodonHits::find_scalar(&codes, &set);
+
+				let mut sse2 = CodonHits::new(len);
+				let done = sse2.fill_sse2(&codes, &set);
+				sse2.fill_scalar(&codes, &set, done);
+				assert!(sse2 == scalar, "SSE2, length {len}");
+
+				if is_x86_feature_detected!("avx2") {
+					let mut avx2 = CodonHits::new(len);
+					// SAFETY: The CPU has just been checked to support AVX2.
+					let done = unsafe { avx2.fill_avx2(&codes, &set) };
+					avx2.fill_scalar(&codes, &set, done);
+					assert!(avx2 == scalar, "AVX2, length {len}");
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn finds_stops_and_ambiguous_codons() {
+		use IupacNucleotide::*;
+		let sequence = [A, U, G, U, A, A, N, C, C, U, G, A, U];
+		let codes: Vec<_> = sequence.into_iter().map(|x| x as u8).collect();
+		let sets = CodonSets::new(&OrfOptions::default());
+
+		let stops = CodonHits::find(&codes, sets.get_stops(Strand::Forward));
+		let hits: Vec<_> = stops.iter_frame(0, codes.len()).collect();
+		assert_eq!(hits, [3, 6, 9]);
+		let hits: Vec<_> = (0..3)
+			.flat_map(|x| stops.iter_frame(x, codes.len()))
+			.collect();
+		assert_eq!(hits, [3, 6, 9, 4, 5]);
+	}
+}
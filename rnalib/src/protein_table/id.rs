@@ -0,0 +1,25 @@
+//! The module that implements [`ProteinId`] used by [`ProteinMap`](crate::ProteinMap)
+
+use std::fmt::Display;
+
+/// A numeric identifier of an entry of a [`ProteinMap`](crate::ProteinMap).
+///
+/// Identifiers are assigned in the order the proteins are first
+/// inserted into the map, and do not change as the map grows,
+/// so they may be kept to look the proteins up in constant time.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProteinId(pub(crate) usize);
+
+impl ProteinId {
+	/// Returns the index of the entry in the order
+	/// of insertion into its map.
+	pub const fn get_index(&self) -> usize {
+		self.0
+	}
+}
+
+impl Display for ProteinId {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "#{}", self.0)
+	}
+}
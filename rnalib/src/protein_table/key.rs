@@ -13,15 +13,7 @@ impl Eq for Key {}
 
 impl PartialOrd for Key {
 	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-		use std::cmp::Ordering::*;
-		match self.0.len().partial_cmp(&other.0.len()) {
-			None => None,
-			Some(ord) => match ord {
-				Less => Some(Less),
-				Greater => Some(Greater),
-				Equal => self.0.partial_cmp(&other.0),
-			},
-		}
+		Some(self.cmp(other))
 	}
 }
 
@@ -1,206 +1,1376 @@
 //! The module that implements [`ThreadedProteinLoader`]
 
 use std::{
-	collections::BTreeMap,
+	collections::{BTreeMap, VecDeque},
+	io::{self, BufRead, BufReader, ErrorKind, Read},
 	sync::{
-		atomic::{AtomicBool, AtomicU32, Ordering},
+		atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+		mpsc::{channel, sync_channel, Receiver, Sender, SyncSender},
 		Arc, Mutex,
 	},
-	thread,
+	thread::{self, JoinHandle},
 };
 
-use crate::{Codon, Nucleotide, Protein, ProteinMap};
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
 
-use super::key::Key;
+use crate::{
+	nucleotide_string::parser::SequenceParser, AminoString, Decompressor, FastqReader, Feature,
+	FlatFileFormat, FlatFileReader, FlatFileRecord, Frame, GeneticCode, LoadError, LoadEvent,
+	Location, NucleotideString, OrfOptions, Protein, ProteinMap, QualityEncoding, QualityFilter,
+	RecordHeader, Strand, TrackedRead,
+};
+
+use super::{
+	chunk::{ChunkScan, FrameCarry},
+	key::Key,
+};
+
+/// A record read as a whole, along with the amount
+/// of the source read up to its end.
+type WholeRecord = Result<(RecordHeader, NucleotideString, u64), LoadError>;
+
+/// A message passed from the reading thread to
+/// the dispatching thread.
+enum Message {
+	/// A chunk of parsed nucleotides, along with the
+	/// amount of the source read up to its end.
+	Chunk(Arc<NucleotideString>, u64),
+	/// The end of the current record; the nucleotides
+	/// that follow belong to the next one.
+	EndOfRecord,
+}
+
+/// The state of a single load, shared between the
+/// loader and its threads.
+#[derive(Default)]
+struct SharedState {
+	result: Mutex<Option<BTreeMap<Key, Protein>>>,
+	error: Mutex<Option<LoadError>>,
+	/// True once an error has been reported, even if
+	/// it has already been taken
+	failed: AtomicBool,
+	/// Cancellation token checked by the threads
+	cancelled: AtomicBool,
+	/// Amount of bytes scanned in each frame
+	progress: [AtomicU64; 6],
+	/// Amount of dispatching threads still running
+	running: AtomicUsize,
+	/// Expected size of the source, in bytes
+	size: u64,
+	/// Headers of the records read so far
+	records: Mutex<Vec<RecordHeader>>,
+	/// Annotated features the proteins were translated from
+	features: Mutex<Vec<Feature>>,
+	subscribers: Vec<Sender<LoadEvent>>,
+}
+
+impl SharedState {
+	/// Returns true if an error has been reported.
+	fn has_error(&self) -> bool {
+		self.failed.load(Ordering::Acquire)
+	}
+
+	/// Reports an error, unless one has been reported already,
+	/// and drops the proteins found so far, so that a partial
+	/// result is never taken.
+	fn report(&self, err: LoadError) {
+		if let Ok(mut lock) = self.error.lock() {
+			lock.get_or_insert(err);
+		}
+		self.failed.store(true, Ordering::Release);
+		if let Ok(mut lock) = self.result.lock() {
+			*lock = None;
+		}
+	}
+
+	/// Passes an event to every subscriber.
+	fn emit(&self, event: LoadEvent) {
+		for subscriber in &self.subscribers {
+			subscriber.send(event).ok();
+		}
+	}
+
+	/// Returns the progress of scanning a frame, normalized
+	/// to the range from 0 to 1.
+	fn get_progress(&self, index: usize) -> f32 {
+		if self.size == 0 {
+			return 0.0;
+		}
+		let bytes = self.progress[index].load(Ordering::Relaxed);
+		(bytes as f64 / self.size as f64).min(1.0) as f32
+	}
+}
 
 /// A parser that uses system's multithreading capabilities
-/// in order to efficiently load a [`ProteinMap`] from String,
-/// while reporting the progress.
+/// in order to efficiently load a [`ProteinMap`] from any
+/// [`Read`] source, while reporting the progress.
+///
+/// The source is read in chunks by a dedicated thread, and
+/// the chunks are scanned in all three ribosome offsets, or
+/// six if the reverse complement strand is scanned as well,
+/// by a pool of threads, see [`ThreadedProteinLoader::with_threads`].
+/// Every chunk overlaps the next one by the two nucleotides
+/// that complete its last codons, so the chunks are scanned
+/// independently, then the ORFs spanning several chunks are
+/// put together in the order of the source. The results do
+/// not depend on the amount of threads.
+///
+/// The source is never held in memory as a whole; apart
+/// from the results, the memory used depends on the length
+/// of the longest ORF, as the codons read before the first
+/// start codon of a region free of stop codons are dropped.
+/// ORFs of the reverse strand, however, are read from their
+/// stop codons on, so whether a codon belongs to one is only
+/// known once a start codon follows it. There, as with
+/// [`OrfDefinition::StopToStop`](crate::OrfDefinition::StopToStop),
+/// whole regions since the last stop codon are kept.
+///
+/// Sources already held in memory, e.g. memory-mapped files,
+/// are parsed without a reading thread, see
+/// [`ThreadedProteinLoader::start_bytes`].
 ///
-/// Uses three threads under the hood, one for each ribosome
-/// offset.
+/// The result can be awaited with [`ThreadedProteinLoader::wait`],
+/// or polled with [`ThreadedProteinLoader::try_take`]. Progress
+/// is reported as [`LoadEvent`]s to every receiver obtained from
+/// [`ThreadedProteinLoader::subscribe`].
+///
+/// A running load can be stopped with [`ThreadedProteinLoader::cancel`],
+/// which makes all the threads exit as soon as possible. Dropping
+/// the loader cancels the load as well.
+///
+/// What is considered a protein is defined by the [`OrfOptions`]
+/// the loader was constructed with. The input may contain
+/// IUPAC ambiguity codes, see [`IupacNucleotide`](crate::IupacNucleotide), and whitespace,
+/// which is skipped. Any other character stops the load with a
+/// [`LoadError`] pointing at it.
+///
+/// The input may also consist of multiple FASTA records, each
+/// starting with a '>' header line. Every record is scanned
+/// separately, so ORFs never span two records. GenBank and
+/// EMBL files are read by [`ThreadedProteinLoader::start_flat_file`],
+/// which adds the annotated coding sequences to the results, and
+/// FASTQ files by [`ThreadedProteinLoader::start_fastq`].
+///
+/// Sources compressed with gzip, bgzip or Zstandard are
+/// decompressed on the fly, see [`Decompressor`]. Their
+/// progress is measured in compressed bytes.
 pub struct ThreadedProteinLoader {
-	result: Arc<Mutex<Option<BTreeMap<Key, Protein>>>>,
-	flags: [Arc<AtomicBool>; 3],
-	error: Arc<AtomicBool>,
-	progress: [Arc<AtomicU32>; 3],
-	stride_len: u32,
+	options: OrfOptions,
+	/// Amount of threads of the pool, 0 for one per core
+	threads: usize,
+	state: Arc<SharedState>,
+	handles: Vec<JoinHandle<()>>,
+	subscribers: Vec<Sender<LoadEvent>>,
 }
 
 impl ThreadedProteinLoader {
+	/// Size of a single chunk read from the source, in bytes.
+	const CHUNK_LEN: usize = 1 << 16;
+	/// Amount of chunks that may wait to be scanned, per
+	/// thread of the pool, before the reading thread is blocked.
+	const QUEUE_LEN: usize = 4;
+
+	/// Constructs a [`ThreadedProteinLoader`] that reads
+	/// proteins according to given [`OrfOptions`], using
+	/// a thread for every available core.
+	pub fn new(options: OrfOptions) -> Self {
+		Self::with_threads(options, 0)
+	}
+
+	/// Constructs a [`ThreadedProteinLoader`] that reads
+	/// proteins according to given [`OrfOptions`], scanning
+	/// the source with a given amount of threads, or a thread
+	/// for every available core if it is 0.
+	///
+	/// The threads are spawned anew by every load, apart from
+	/// the thread reading the source and the one dispatching
+	/// the chunks.
+	pub fn with_threads(options: OrfOptions, threads: usize) -> Self {
+		Self {
+			options,
+			threads,
+			state: Default::default(),
+			handles: Vec::new(),
+			subscribers: Vec::new(),
+		}
+	}
+
+	/// Returns the amount of threads the source is scanned with.
+	pub fn get_threads(&self) -> usize {
+		match self.threads {
+			0 => thread::available_parallelism().map_or(1, |x| x.get()),
+			threads => threads,
+		}
+	}
+
+	/// Returns a receiver of the [`LoadEvent`]s reported by
+	/// the loads started after this call.
+	pub fn subscribe(&mut self) -> Receiver<LoadEvent> {
+		let (sender, receiver) = channel();
+		self.subscribers.push(sender);
+		receiver
+	}
+
 	/// Starts parsing from a given [`String`]
 	pub fn start(&mut self, source: String) {
-		self.reset();
+		self.start_bytes(source);
+	}
 
-		self.stride_len = source.len() as u32;
-		let source = Arc::new(source);
-		let target = self.result.clone();
+	/// Starts parsing from a given byte slice, e.g. a memory-mapped
+	/// file, cancelling the previous load if it is still running.
+	///
+	/// Unlike with other sources, there is no reading thread, and the
+	/// text is not copied into read buffers; the dispatching thread
+	/// parses the slice on its own. This is not a zero-copy load,
+	/// though: as with any other source, the nucleotides are packed
+	/// into [`NucleotideString`] chunks before they are scanned.
+	/// The slice is not decompressed.
+	pub fn start_bytes<S>(&mut self, source: S)
+	where
+		S: AsRef<[u8]> + Send + 'static,
+	{
+		let size = source.as_ref().len() as u64;
+		self.spawn_dispatcher(size, |state| SliceReader::new(source, state.clone()));
+	}
 
-		let s1 = source.clone();
-		let s2 = source.clone();
-		let s3 = source;
+	/// Starts parsing from a given [`Read`] source, cancelling
+	/// the previous load if it is still running.
+	///
+	/// The `size` of the source, in bytes, is only used to
+	/// report the progress, and may be 0 if it is not known.
+	/// For compressed sources it is the compressed size.
+	pub fn start_reader(&mut self, source: impl Read + Send + 'static, size: u64) {
+		let (sender, state) = self.spawn_channel(size);
+		let handle = thread::spawn(move || match Decompressor::new(BufReader::new(source)) {
+			Ok(source) => Self::read_source(source, sender, &state),
+			Err(err) => state.report(LoadError::Io(err)),
+		});
+		self.handles.push(handle);
+	}
 
-		let t1 = target.clone();
-		let t2 = target.clone();
-		let t3 = target;
+	/// Starts parsing from a given [`TrackedRead`] source,
+	/// cancelling the previous load if it is still running.
+	///
+	/// Unlike [`ThreadedProteinLoader::start_reader`], the source
+	/// is not decompressed, and the progress is measured in the
+	/// bytes of its underlying data rather than the bytes read,
+	/// so the `size` is the size of the underlying data.
+	pub fn start_tracked(&mut self, source: impl TrackedRead + Send + 'static, size: u64) {
+		let (sender, state) = self.spawn_channel(size);
+		let handle = thread::spawn(move || Self::read_source(source, sender, &state));
+		self.handles.push(handle);
+	}
 
-		let f1 = self.flags[0].clone();
-		let f2 = self.flags[1].clone();
-		let f3 = self.flags[2].clone();
+	/// Starts searching an already parsed [`NucleotideString`],
+	/// cancelling the previous load if it is still running.
+	///
+	/// The progress is reported in nucleotides rather than bytes.
+	pub fn start_sequence(&mut self, sequence: NucleotideString) {
+		let (sender, state) = self.spawn_channel(sequence.len() as u64);
+		let handle = thread::spawn(move || {
+			if let Ok(mut records) = state.records.lock() {
+				records.push(RecordHeader::default());
+			}
+			Self::send_sequence(&sequence, &sender, &state, |x| x as u64);
+		});
+		self.handles.push(handle);
+	}
 
-		let e1 = self.error.clone();
-		let e2 = self.error.clone();
-		let e3 = self.error.clone();
+	/// Starts parsing a GenBank or EMBL file from a given
+	/// [`BufRead`] source, cancelling the previous load if it
+	/// is still running.
+	///
+	/// Besides the ORFs predicted in the sequences, the results
+	/// hold the proteins encoded by the annotated coding sequences,
+	/// translated as described in [`Feature::translate`] regardless
+	/// of the [`OrfOptions`]. Their locations refer to the features
+	/// kept by the map, see [`ProteinMap::get_feature`].
+	///
+	/// Unlike with other sources, every record is held in
+	/// memory as a whole while it is scanned.
+	pub fn start_flat_file(
+		&mut self,
+		source: impl BufRead + Send + 'static,
+		format: FlatFileFormat,
+		size: u64,
+	) {
+		let (sender, state) = self.spawn_channel(size);
+		let code = self.options.code;
+		let handle = thread::spawn(move || {
+			let source = match Decompressor::new(source) {
+				Ok(x) => BufReader::new(x),
+				Err(err) => return state.report(LoadError::Io(err)),
+			};
+			let mut reader = FlatFileReader::new(source, format);
+			let next = |index| {
+				let record = match reader.next()? {
+					Ok(x) => x,
+					Err(err) => return Some(Err(err)),
+				};
+				Self::store_annotations(&record, index, code, &state);
+				let header = record.get_header().clone();
+				let consumed = reader.get_ref().get_consumed();
+				Some(Ok((header, record.into_sequence(), consumed)))
+			};
+			Self::send_records(next, sender, &state);
+		});
+		self.handles.push(handle);
+	}
+
+	/// Starts parsing a FASTQ file from a given [`BufRead`]
+	/// source, cancelling the previous load if it is still
+	/// running.
+	///
+	/// Every read is trimmed and filtered by a given
+	/// [`QualityFilter`], then scanned as a separate record,
+	/// so ORFs never span two reads. The reads discarded by
+	/// the filter are not among the records of the map.
+	pub fn start_fastq(
+		&mut self,
+		source: impl BufRead + Send + 'static,
+		encoding: QualityEncoding,
+		filter: QualityFilter,
+		size: u64,
+	) {
+		let (sender, state) = self.spawn_channel(size);
+		let handle = thread::spawn(move || {
+			let source = match Decompressor::new(source) {
+				Ok(x) => BufReader::new(x),
+				Err(err) => return state.report(LoadError::Io(err)),
+			};
+			let mut reader = FastqReader::new(source, encoding);
+			let next = |_| loop {
+				let record = match reader.next()? {
+					Ok(x) => x,
+					Err(err) => return Some(Err(err)),
+				};
+				if let Some(record) = filter.apply(record) {
+					let header = record.get_header().clone();
+					let consumed = reader.get_ref().get_consumed();
+					return Some(Ok((header, record.into_sequence(), consumed)));
+				}
+			};
+			Self::send_records(next, sender, &state);
+		});
+		self.handles.push(handle);
+	}
+
+	/// Cancels the current load. The underlying threads
+	/// exit as soon as possible, dropping the source and
+	/// all the proteins found so far.
+	///
+	/// Afterwards the loader is ready, but there is no
+	/// [`ProteinMap`] to be taken.
+	pub fn cancel(&mut self) {
+		self.state.cancelled.store(true, Ordering::Relaxed);
+		if let Ok(mut lock) = self.state.result.lock() {
+			*lock = None;
+		}
+	}
 
-		let p1 = self.progress[0].clone();
-		let p2 = self.progress[1].clone();
-		let p3 = self.progress[2].clone();
+	/// Returns true if the current load was cancelled.
+	pub fn is_cancelled(&self) -> bool {
+		self.state.cancelled.load(Ordering::Relaxed)
+	}
 
-		thread::spawn(move || Self::load_skip(s1, t1, f1, e1, p1, 0));
-		thread::spawn(move || Self::load_skip(s2, t2, f2, e2, p2, 1));
-		thread::spawn(move || Self::load_skip(s3, t3, f3, e3, p3, 2));
+	/// Blocks until the current load finishes, then takes
+	/// the loaded [`ProteinMap`], or the [`LoadError`] that
+	/// stopped the load.
+	///
+	/// Returns [`None`] if the load was cancelled, or its
+	/// result has already been taken.
+	pub fn wait(&mut self) -> Option<Result<ProteinMap, LoadError>> {
+		for handle in self.handles.drain(..) {
+			handle.join().ok();
+		}
+		self.try_take()
 	}
 
-	/// Takes the loaded [`ProteinMap`].
+	/// Takes the loaded [`ProteinMap`], or the [`LoadError`]
+	/// that stopped the load, without blocking.
 	///
-	/// Returns [`None`] if there is no [`ProteinMap`] to be taken.
-	pub fn take(&mut self) -> Option<ProteinMap> {
-		if !self.is_ready() || self.error.load(Ordering::Relaxed) {
+	/// Returns [`None`] if the load is still running, was
+	/// cancelled, or its result has already been taken. Once
+	/// a load has failed, it never yields a [`ProteinMap`].
+	pub fn try_take(&mut self) -> Option<Result<ProteinMap, LoadError>> {
+		if !self.is_ready() || self.is_cancelled() {
 			return None;
 		}
 
-		let lock = self.result.try_lock();
-		match lock {
-			Ok(mut guard) => {
-				let map = guard.replace(BTreeMap::new());
-				Some(ProteinMap::from(map.unwrap()))
-			}
-			Err(_) => None,
+		if self.state.has_error() {
+			return self.state.error.lock().ok()?.take().map(Err);
 		}
+
+		let mut map = ProteinMap::from(self.state.result.lock().ok()?.take()?);
+		map.records = std::mem::take(&mut *self.state.records.lock().ok()?);
+		map.features = std::mem::take(&mut *self.state.features.lock().ok()?);
+		Some(Ok(map))
 	}
 
 	/// Returns true if the parser finished loading the
-	/// [`ProteinMap`], or encountered an error and exited
-	/// early.
+	/// [`ProteinMap`], encountered an error and exited
+	/// early, or was cancelled.
 	pub fn is_ready(&self) -> bool {
-		(self.flags[0].load(Ordering::Relaxed)
-			&& self.flags[1].load(Ordering::Relaxed)
-			&& self.flags[2].load(Ordering::Relaxed))
-			|| self.error.load(Ordering::Relaxed)
+		self.state.running.load(Ordering::Acquire) == 0
+			|| self.state.has_error()
+			|| self.is_cancelled()
 	}
 
 	/// Returns the normalized progress made by the
-	/// underlying threads.
+	/// underlying threads, based on the amount of bytes
+	/// of the source scanned.
+	///
+	/// Returns 0 if the size of the source is not known.
 	pub fn get_progress(&self) -> f32 {
-		let total = self.stride_len as f32 / 3.0;
-		let p1 = self.progress[0].load(Ordering::Relaxed) / 3;
-		let p2 = self.progress[0].load(Ordering::Relaxed) / 3;
-		let p3 = self.progress[0].load(Ordering::Relaxed) / 3;
-		(p1 + p2 + p3) as f32 / total
+		let frames = match self.options.both_strands {
+			true => 6,
+			false => 3,
+		};
+		let total: f32 = (0..frames).map(|x| self.state.get_progress(x)).sum();
+		total / frames as f32
+	}
+
+	/// Returns the normalized progress of scanning
+	/// a given reading [`Frame`].
+	///
+	/// Returns 0 if the size of the source is not known.
+	pub fn get_frame_progress(&self, frame: Frame) -> f32 {
+		let index = Frame::ALL.iter().position(|&x| x == frame);
+		index.map_or(0.0, |x| self.state.get_progress(x))
+	}
+
+	/// A helper function that cancels the previous load, then
+	/// spawns a thread that dispatches the chunks received
+	/// from a channel to the threads scanning them.
+	///
+	/// Returns the sender of the chunks to be scanned,
+	/// along with the state of the new load.
+	fn spawn_channel(&mut self, size: u64) -> (SyncSender<Message>, Arc<SharedState>) {
+		let (sender, receiver) = sync_channel(Self::QUEUE_LEN);
+		let state = self.spawn_dispatcher(size, |_| receiver.into_iter());
+		(sender, state)
+	}
+
+	/// A helper function that cancels the previous load, then
+	/// spawns a thread that dispatches the chunks returned by
+	/// the iterator `messages` constructs, given the state of
+	/// the new load, to a pool of threads scanning them.
+	///
+	/// Returns the state of the new load.
+	fn spawn_dispatcher<M>(
+		&mut self,
+		size: u64,
+		messages: impl FnOnce(&Arc<SharedState>) -> M,
+	) -> Arc<SharedState>
+	where
+		M: Iterator<Item = Message> + Send + 'static,
+	{
+		self.cancel();
+		self.handles.clear();
+
+		let frames: Vec<_> = Frame::ALL
+			.into_iter()
+			.enumerate()
+			.filter(|(_, x)| x.get_strand() == Strand::Forward || self.options.both_strands)
+			.collect();
+
+		// The state shared with the threads is replaced rather than
+		// cleared, so that threads of a previous, cancelled load can
+		// not affect the new one.
+		let state = Arc::new(SharedState {
+			result: Mutex::new(Some(BTreeMap::new())),
+			running: AtomicUsize::new(1),
+			size,
+			subscribers: self.subscribers.clone(),
+			..Default::default()
+		});
+		self.state = state.clone();
+
+		let messages = messages(&state);
+		let options = Arc::new(self.options.clone());
+		let threads = self.get_threads();
+		let shared = state.clone();
+		let handle = thread::spawn(move || {
+			let state = shared;
+			match Dispatcher::new(state.clone(), options, &frames, threads) {
+				Ok(dispatcher) => dispatcher.run(messages),
+				Err(err) => state.report(LoadError::Io(io::Error::other(err))),
+			}
+			for (_, frame) in frames {
+				state.emit(LoadEvent::FrameFinished(frame));
+			}
+			state.running.fetch_sub(1, Ordering::AcqRel);
+			state.emit(LoadEvent::Finished);
+		});
+		self.handles.push(handle);
+
+		state
+	}
+
+	/// A helper function that reads the source in chunks,
+	/// parses them, and passes them to the dispatching thread.
+	///
+	/// Whitespace and alignment gaps are skipped, FASTA headers
+	/// end the current record. Stops at the first unknown
+	/// character or I/O error, reporting it, or once the load
+	/// is cancelled.
+	fn read_source(mut source: impl TrackedRead, sender: SyncSender<Message>, state: &SharedState) {
+		let mut buffer = vec![0u8; Self::CHUNK_LEN];
+		let mut parser = SequenceParser::fasta(false);
+		let mut after = 0;
+		let mut empty = true;
+		while !state.cancelled.load(Ordering::Relaxed) {
+			let len = match source.read(&mut buffer) {
+				Ok(0) => break,
+				Ok(len) => len,
+				Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+				Err(err) => return state.report(LoadError::Io(err)),
+			};
+			let before = after;
+			after = source.get_consumed();
+
+			let mut bytes = &buffer[..len];
+			while !bytes.is_empty() {
+				let mut chunk = NucleotideString::default();
+				let (consumed, header) = match parser.parse(bytes, &mut chunk) {
+					Ok(x) => x,
+					Err(err) => return state.report(err),
+				};
+				bytes = &bytes[consumed..];
+				empty &= chunk.is_empty();
+
+				// Only the amount of the underlying data consumed by the
+				// whole read is known, so the progress in between is
+				// interpolated, which is exact for plain sources.
+				let chunk = Arc::new(chunk);
+				let end = before + (after - before) * (len - bytes.len()) as u64 / len as u64;
+				sender.send(Message::Chunk(chunk, end)).ok();
+				if let Some(header) = header {
+					Self::start_record(header, empty, &sender, state);
+				}
+			}
+		}
+
+		// The trailers of compressed sources are only consumed
+		// once the data runs out, so the progress catches up.
+		let end = source.get_consumed();
+		if end > after {
+			sender.send(Message::Chunk(Arc::default(), end)).ok();
+		}
+		if let Some(header) = parser.finish() {
+			Self::start_record(header, empty, &sender, state);
+		}
+		if let Ok(mut records) = state.records.lock() {
+			if records.is_empty() {
+				records.push(RecordHeader::default());
+			}
+		}
 	}
 
-	/// Restores all internal state to default.
-	fn reset(&mut self) {
-		self.result = Arc::new(Mutex::new(Some(BTreeMap::new())));
+	/// A helper function that passes the records returned one
+	/// by one by `next`, given the index of each, to the
+	/// dispatching thread.
+	///
+	/// Stops at the first error, reporting it, or once the
+	/// load is cancelled.
+	fn send_records(
+		mut next: impl FnMut(usize) -> Option<WholeRecord>,
+		sender: SyncSender<Message>,
+		state: &SharedState,
+	) {
+		let mut index = 0;
+		let mut start = 0;
+		while !state.cancelled.load(Ordering::Relaxed) {
+			let (header, sequence, end) = match next(index) {
+				Some(Ok(x)) => x,
+				Some(Err(err)) => return state.report(err),
+				None => break,
+			};
 
-		self.flags[0].store(false, Ordering::Relaxed);
-		self.flags[1].store(false, Ordering::Relaxed);
-		self.flags[2].store(false, Ordering::Relaxed);
+			if index > 0 {
+				sender.send(Message::EndOfRecord).ok();
+			}
+			if let Ok(mut records) = state.records.lock() {
+				records.push(header);
+			}
 
-		self.progress[0].store(0, Ordering::Relaxed);
-		self.progress[1].store(0, Ordering::Relaxed);
-		self.progress[2].store(0, Ordering::Relaxed);
+			// The progress within a record is interpolated, as the
+			// whole record has been read from the source already.
+			let length = sequence.len().max(1) as u64;
+			let progress = |x| start + (end - start) * x as u64 / length;
+			Self::send_sequence(&sequence, &sender, state, progress);
+			start = end;
+			index += 1;
+		}
 
-		self.error.store(false, Ordering::Relaxed)
+		if let Ok(mut records) = state.records.lock() {
+			if records.is_empty() {
+				records.push(RecordHeader::default());
+			}
+		}
 	}
 
-	/// A helper function used to iterate over a [`String`]
-	/// and read all the proteins encoded in it.
-	fn load_skip(
-		source: Arc<String>,
-		target: Arc<Mutex<Option<BTreeMap<Key, Protein>>>>,
-		flag: Arc<AtomicBool>,
-		error: Arc<AtomicBool>,
-		progress: Arc<AtomicU32>,
-		skip: usize,
+	/// A helper function that translates the annotated coding
+	/// sequences of a record with a given index, and stores
+	/// them in the results along with their features.
+	fn store_annotations(
+		record: &FlatFileRecord,
+		index: usize,
+		code: GeneticCode,
+		state: &SharedState,
 	) {
-		const SPACE: u8 = b' ';
-
-		let mut result = BTreeMap::new();
-		let mut current = Vec::with_capacity(30000);
-		let mut current_str = String::with_capacity(30000);
-		let mut protein = false;
-
-		let mut iter =
-			source.as_bytes().iter().filter(|&&x| x != SPACE).map(
-				|&x| match Nucleotide::parse_raw(x) {
-					Some(x) => x,
-					None => {
-						error.store(true, Ordering::Relaxed);
-						Nucleotide::A
-					}
-				},
-			);
-		if error.load(Ordering::Relaxed) {
+		let Ok(mut features) = state.features.lock() else { return };
+		let Ok(mut result) = state.result.lock() else { return };
+		let Some(result) = result.as_mut() else { return };
+
+		for (feature, string, location) in record.translate(code) {
+			let location = location.with_record(index).with_feature(features.len());
+			features.push(feature.clone());
+			let key = Key(string
+				.get_codons()
+				.iter()
+				.map(|x| x.get_acid_shorthand())
+				.collect());
+			ProteinMap::merge_entry(result, key, Protein::with_location(string, location));
+		}
+	}
+
+	/// A helper function that passes a parsed sequence to the
+	/// dispatching thread in chunks. The amount of the source read
+	/// up to the end of a chunk is given by `progress`, based on
+	/// the amount of nucleotides sent.
+	fn send_sequence(
+		sequence: &NucleotideString,
+		sender: &SyncSender<Message>,
+		state: &SharedState,
+		progress: impl Fn(usize) -> u64,
+	) {
+		for start in (0..sequence.len()).step_by(Self::CHUNK_LEN) {
+			if state.cancelled.load(Ordering::Relaxed) {
+				break;
+			}
+			let chunk = sequence.slice(start, Self::CHUNK_LEN);
+			let bytes = progress(start + chunk.len());
+			sender.send(Message::Chunk(Arc::new(chunk), bytes)).ok();
+		}
+	}
+
+	/// A helper function that starts a new record with a
+	/// given header, ending the current one.
+	///
+	/// The first header does not end a record if no
+	/// nucleotides preceded it.
+	fn start_record(
+		header: RecordHeader,
+		empty: bool,
+		sender: &SyncSender<Message>,
+		state: &SharedState,
+	) {
+		let Ok(mut records) = state.records.lock() else { return };
+		if !records.is_empty() || !empty {
+			if records.is_empty() {
+				records.push(RecordHeader::default());
+			}
+			sender.send(Message::EndOfRecord).ok();
+		}
+		records.push(header);
+	}
+}
+
+/// A chunk of a record, scanned by the thread pool.
+#[derive(Default)]
+struct Task {
+	/// Index of the record the chunk belongs to
+	record: usize,
+	/// Offset of the chunk in its record
+	start: usize,
+	/// The nucleotides of the chunk, followed by the ones
+	/// of the next chunks that complete its last codons
+	pieces: Vec<Arc<NucleotideString>>,
+	/// Amount of nucleotides of the chunk itself
+	len: usize,
+	/// Amount of nucleotides of the next chunks
+	overlap: usize,
+	/// Amount of the source read up to the end of the chunk
+	progress: u64,
+	/// Index of the step awaiting the result of the task
+	step: usize,
+}
+
+impl Task {
+	/// Scans the chunk in given reading frames, storing the
+	/// proteins found in the map of the current thread.
+	fn scan(
+		self,
+		state: &SharedState,
+		options: &OrfOptions,
+		frames: &[Frame],
+		found: &[Mutex<BTreeMap<Key, Protein>>],
+	) -> ChunkScan {
+		let index = rayon::current_thread_index().unwrap_or_default();
+		let proteins = found.get(index).and_then(|x| x.lock().ok());
+		let Some(mut proteins) = proteins else { return ChunkScan::default() };
+		if state.cancelled.load(Ordering::Relaxed) {
+			return ChunkScan::default();
+		}
+
+		let (start, len) = (self.start, self.len);
+		let mut codes = Vec::with_capacity(len + 2);
+		for piece in &self.pieces {
+			let end = piece.len().min(len + 2 - codes.len());
+			piece.extend_codes(0, end, &mut codes);
+		}
+		let mut emit = collector(&mut proteins, self.record);
+		ChunkScan::run(&codes, len, start, options, frames, &mut emit)
+	}
+}
+
+/// A step of a load, completed in the order of the source.
+enum Step {
+	/// Scanning a chunk, along with the amount of the source
+	/// read up to its end. The result is [`None`] until the
+	/// task scanning the chunk finishes.
+	Scan(Option<ChunkScan>, u64),
+	/// The end of a record of a given length
+	EndOfRecord(usize),
+}
+
+/// Splits the chunks of a single load into tasks scanned by a
+/// pool of threads, then puts their results together in the
+/// order of the source.
+///
+/// The proteins are kept in a separate map for every thread,
+/// and merged into the results once the source runs out.
+struct Dispatcher {
+	state: Arc<SharedState>,
+	options: Arc<OrfOptions>,
+	/// The scanned frames, in the order of [`Frame::ALL`]
+	frames: Arc<[Frame]>,
+	/// Indices of the scanned frames in [`Frame::ALL`]
+	indices: Vec<usize>,
+	pool: ThreadPool,
+	/// Proteins found by every thread of the pool
+	found: Arc<Vec<Mutex<BTreeMap<Key, Protein>>>>,
+	/// Proteins found by the dispatcher itself
+	proteins: BTreeMap<Key, Protein>,
+	sender: Sender<(usize, ChunkScan)>,
+	receiver: Receiver<(usize, ChunkScan)>,
+	/// The task the received chunks are added to
+	current: Task,
+	/// Tasks waiting for the nucleotides that
+	/// complete their last codons
+	sealed: VecDeque<Task>,
+	/// Steps not completed yet, in order
+	steps: VecDeque<Step>,
+	/// Amount of steps completed so far
+	completed: usize,
+	/// Amount of tasks being scanned
+	running: usize,
+	/// Maximal amount of tasks being scanned
+	max_running: usize,
+	/// Codons of the current record not scanned yet
+	carries: Vec<FrameCarry>,
+	/// Lengths of the records completed so far
+	lengths: Vec<usize>,
+	/// Amount of the source read so far
+	progress: u64,
+}
+
+impl Dispatcher {
+	/// Constructs a [`Dispatcher`] scanning given reading
+	/// frames, paired with their indices in [`Frame::ALL`],
+	/// with a given amount of threads.
+	fn new(
+		state: Arc<SharedState>,
+		options: Arc<OrfOptions>,
+		frames: &[(usize, Frame)],
+		threads: usize,
+	) -> Result<Self, ThreadPoolBuildError> {
+		let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+		let (sender, receiver) = channel();
+		Ok(Self {
+			state,
+			options,
+			frames: frames.iter().map(|x| x.1).collect(),
+			indices: frames.iter().map(|x| x.0).collect(),
+			found: Arc::new((0..threads).map(|_| Default::default()).collect()),
+			proteins: BTreeMap::new(),
+			pool,
+			sender,
+			receiver,
+			current: Task::default(),
+			sealed: VecDeque::new(),
+			steps: VecDeque::new(),
+			completed: 0,
+			running: 0,
+			max_running: threads * ThreadedProteinLoader::QUEUE_LEN,
+			carries: FrameCarry::start(frames.len()),
+			lengths: Vec::new(),
+			progress: 0,
+		})
+	}
+
+	/// Scans the chunks returned by `messages`, then stores
+	/// the proteins found in the results.
+	///
+	/// Nothing is stored if the load was cancelled or failed.
+	fn run(mut self, messages: impl Iterator<Item = Message>) {
+		for message in messages {
+			if self.state.cancelled.load(Ordering::Relaxed) {
+				return;
+			}
+			match message {
+				Message::Chunk(chunk, progress) => self.push(chunk, progress),
+				Message::EndOfRecord => self.end_record(),
+			}
+			while let Ok(result) = self.receiver.try_recv() {
+				self.accept(result);
+			}
+		}
+
+		self.end_record();
+		while self.running > 0 && !self.state.cancelled.load(Ordering::Relaxed) {
+			let Ok(result) = self.receiver.recv() else { break };
+			self.accept(result);
+		}
+		self.report(self.progress);
+
+		if self.state.cancelled.load(Ordering::Relaxed) || self.state.has_error() {
 			return;
 		}
+		self.store();
+	}
+
+	/// A helper function that adds a chunk, along with the
+	/// amount of the source read up to its end, to the
+	/// current task, and completes the earlier ones.
+	fn push(&mut self, chunk: Arc<NucleotideString>, progress: u64) {
+		self.progress = progress;
+		self.current.progress = progress;
+		if chunk.is_empty() {
+			return;
+		}
+
+		for task in self.sealed.iter_mut().filter(|x| x.overlap < 2) {
+			task.pieces.push(chunk.clone());
+			task.overlap += chunk.len();
+		}
+		while self.sealed.front().is_some_and(|x| x.overlap >= 2) {
+			if let Some(task) = self.sealed.pop_front() {
+				self.submit(task);
+			}
+		}
+
+		self.current.len += chunk.len();
+		self.current.pieces.push(chunk);
+		if self.current.len >= ThreadedProteinLoader::CHUNK_LEN {
+			self.seal();
+		}
+	}
+
+	/// A helper function that ends the current record,
+	/// scanning the rest of its chunks.
+	fn end_record(&mut self) {
+		if self.current.len > 0 {
+			self.seal();
+		}
+		while let Some(task) = self.sealed.pop_front() {
+			self.submit(task);
+		}
+		self.steps.push_back(Step::EndOfRecord(self.current.start));
+		self.current = Task {
+			record: self.current.record + 1,
+			progress: self.progress,
+			..Default::default()
+		};
+		self.complete();
+	}
+
+	/// A helper function that stops adding chunks to the
+	/// current task, which then waits for the nucleotides
+	/// that complete its last codons.
+	fn seal(&mut self) {
+		let next = Task {
+			record: self.current.record,
+			start: self.current.start + self.current.len,
+			progress: self.progress,
+			..Default::default()
+		};
+		let mut task = std::mem::replace(&mut self.current, next);
+		task.step = self.completed + self.steps.len();
+		self.steps.push_back(Step::Scan(None, task.progress));
+		self.sealed.push_back(task);
+	}
+
+	/// A helper function that passes a task to the pool,
+	/// blocking while too many tasks are being scanned.
+	fn submit(&mut self, task: Task) {
+		let state = self.state.clone();
+		let options = self.options.clone();
+		let frames = self.frames.clone();
+		let found = self.found.clone();
+		let sender = self.sender.clone();
+		self.pool.spawn(move || {
+			let step = task.step;
+			let scan = task.scan(&state, &options, &frames, &found);
+			sender.send((step, scan)).ok();
+		});
 
-		for _ in 0..skip {
-			iter.next();
+		self.running += 1;
+		while self.running >= self.max_running {
+			let Ok(result) = self.receiver.recv() else { return };
+			self.accept(result);
 		}
+	}
 
-		while let (Some(a), Some(b), Some(c)) = (iter.next(), iter.next(), iter.next()) {
-			let codon = Codon::new(a, b, c);
+	/// A helper function that accepts the result of a task
+	/// awaited by the step with a given index.
+	fn accept(&mut self, (step, scan): (usize, ChunkScan)) {
+		self.running -= 1;
+		if let Some(Step::Scan(result, _)) = self.steps.get_mut(step - self.completed) {
+			*result = Some(scan);
+		}
+		self.complete();
+	}
 
-			if protein && codon == Codon::STOP {
-				if !current.is_empty() {
-					current.shrink_to_fit();
-					current_str.shrink_to_fit();
-					result.insert(Key(current_str), Protein::from(current));
-					current = Vec::with_capacity(30000);
-					current_str = String::with_capacity(30000);
+	/// A helper function that completes the steps which
+	/// are ready, in order.
+	fn complete(&mut self) {
+		while matches!(
+			self.steps.front(),
+			Some(Step::Scan(Some(_), _) | Step::EndOfRecord(_))
+		) {
+			self.completed += 1;
+			let record = self.lengths.len();
+			match self.steps.pop_front() {
+				Some(Step::Scan(Some(scan), progress)) => {
+					let emit = collector(&mut self.proteins, record);
+					let (options, frames) = (&self.options, &self.frames);
+					FrameCarry::push_all(&mut self.carries, scan, options, frames, emit);
+					self.report(progress);
+				}
+				Some(Step::EndOfRecord(length)) => {
+					let mut emit = collector(&mut self.proteins, record);
+					let carries = FrameCarry::start(self.frames.len());
+					let carries = std::mem::replace(&mut self.carries, carries);
+					for (carry, frame) in carries.into_iter().zip(self.frames.iter()) {
+						carry.finish(&self.options, *frame, &mut emit);
+					}
+					self.lengths.push(length);
 				}
-				protein = false;
+				_ => {}
 			}
+		}
+	}
 
-			if protein {
-				current_str.push(codon.get_acid_shorthand());
-				current.push(codon);
+	/// A helper function that reports the amount of
+	/// the source scanned in every frame.
+	fn report(&self, bytes: u64) {
+		for (&index, &frame) in self.indices.iter().zip(self.frames.iter()) {
+			self.state.progress[index].store(bytes, Ordering::Relaxed);
+			let progress = self.state.get_progress(index);
+			self.state.emit(LoadEvent::Progress {
+				frame,
+				bytes,
+				progress,
+			});
+		}
+	}
+
+	/// A helper function that merges the proteins found by
+	/// all the threads into the results, moving them to
+	/// their actual frames.
+	fn store(mut self) {
+		let Ok(mut result) = self.state.result.lock() else { return };
+		let Some(result) = result.as_mut() else { return };
+
+		let mut found = vec![std::mem::take(&mut self.proteins)];
+		for proteins in self.found.iter() {
+			if let Ok(mut proteins) = proteins.lock() {
+				found.push(std::mem::take(&mut *proteins));
 			}
+		}
+
+		let length = |x: usize| self.lengths.get(x).copied().unwrap_or_default();
+		for (key, mut protein) in found.into_iter().flatten() {
+			protein.map_locations(|x| FrameCarry::fix_frame(x, length(x.get_record())));
+			ProteinMap::merge_entry(result, key, protein);
+		}
+	}
+}
+
+/// A helper function that returns a callback storing the
+/// proteins found in a record with a given index in a map.
+fn collector(
+	target: &mut BTreeMap<Key, Protein>,
+	record: usize,
+) -> impl FnMut(Key, AminoString, Location) + '_ {
+	move |key, string, location| {
+		let protein = Protein::with_location(string, location.with_record(record));
+		ProteinMap::merge_entry(target, key, protein);
+	}
+}
+
+/// A source of the chunks scanned by the dispatching
+/// thread, which parses a byte slice.
+struct SliceReader<S> {
+	source: S,
+	state: Arc<SharedState>,
+	parser: SequenceParser,
+	/// Offset of the first byte not parsed yet
+	position: usize,
+	/// Amount of records started so far
+	records: usize,
+	/// Whether no nucleotides have been parsed so far
+	empty: bool,
+	/// The end of the record parsed along with the last chunk
+	pending: Option<Message>,
+	finished: bool,
+}
+
+impl<S: AsRef<[u8]>> SliceReader<S> {
+	/// Constructs a [`SliceReader`] parsing a given
+	/// source from its start.
+	fn new(source: S, state: Arc<SharedState>) -> Self {
+		Self {
+			source,
+			state,
+			parser: SequenceParser::fasta(false),
+			position: 0,
+			records: 0,
+			empty: true,
+			pending: None,
+			finished: false,
+		}
+	}
 
-			if codon == Codon::start() {
-				protein = true;
+	/// A helper function that starts a new record with a given
+	/// header, as in [`ThreadedProteinLoader::start_record`].
+	///
+	/// Returns the end of the current record, if it is ended.
+	fn start_record(&mut self, header: RecordHeader) -> Option<Message> {
+		let mut result = None;
+		if self.records > 0 || !self.empty {
+			if self.records == 0 {
+				self.push_record(RecordHeader::default());
 			}
+			result = Some(Message::EndOfRecord);
+		}
+		self.push_record(header);
+		result
+	}
+
+	/// A helper function that stores the header
+	/// of a new record.
+	fn push_record(&mut self, header: RecordHeader) {
+		self.records += 1;
+		if let Ok(mut records) = self.state.records.lock() {
+			records.push(header);
+		}
+	}
+}
+
+impl<S: AsRef<[u8]>> Iterator for SliceReader<S> {
+	type Item = Message;
 
-			progress.fetch_add(1, Ordering::Relaxed);
+	/// Parses the next chunk of the source. Stops at the first
+	/// unknown character, reporting it, or once the load is
+	/// cancelled.
+	fn next(&mut self) -> Option<Message> {
+		if let Some(message) = self.pending.take() {
+			return Some(message);
+		}
+		if self.finished || self.state.cancelled.load(Ordering::Relaxed) {
+			return None;
 		}
 
-		if let Ok(mut target) = target.lock() {
-			if let Some(x) = target.as_mut() {
-				x.append(&mut result)
+		let bytes = self.source.as_ref();
+		if self.position == bytes.len() {
+			self.finished = true;
+			let end = self.parser.finish().and_then(|x| self.start_record(x));
+			if self.records == 0 {
+				self.push_record(RecordHeader::default());
 			}
+			return end;
 		}
 
-		flag.store(true, Ordering::Relaxed);
+		let len = ThreadedProteinLoader::CHUNK_LEN;
+		let end = bytes.len().min(self.position + len);
+		let mut chunk = NucleotideString::default();
+		let (consumed, header) = match self.parser.parse(&bytes[self.position..end], &mut chunk) {
+			Ok(x) => x,
+			Err(err) => {
+				self.finished = true;
+				self.state.report(err);
+				return None;
+			}
+		};
+		self.position += consumed;
+		self.empty &= chunk.is_empty();
+		self.pending = header.and_then(|x| self.start_record(x));
+		Some(Message::Chunk(Arc::new(chunk), self.position as u64))
 	}
 }
 
 impl Default for ThreadedProteinLoader {
 	fn default() -> Self {
-		Self {
-			result: Arc::new(Mutex::new(Some(BTreeMap::new()))),
-			flags: [
-				Arc::new(AtomicBool::new(false)),
-				Arc::new(AtomicBool::new(false)),
-				Arc::new(AtomicBool::new(false)),
-			],
-			progress: [
-				Arc::new(AtomicU32::new(0)),
-				Arc::new(AtomicU32::new(0)),
-				Arc::new(AtomicU32::new(0)),
-			],
-			stride_len: 0,
-			error: Arc::new(AtomicBool::new(false)),
+		Self::new(OrfOptions::default())
+	}
+}
+
+impl Drop for ThreadedProteinLoader {
+	fn drop(&mut self) {
+		self.cancel();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		io::{self, Cursor},
+		time::{Duration, Instant},
+	};
+
+	use super::*;
+	use crate::OrfDefinition;
+
+	/// An endless source of nucleotides.
+	struct Endless;
+
+	impl Read for Endless {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			for (index, byte) in buf.iter_mut().enumerate() {
+				*byte = b"AUGGCUUAAC"[index % 10];
+			}
+			Ok(buf.len())
+		}
+	}
+
+	/// A helper function that generates a pseudo-random record
+	/// of a given length, with ambiguity codes among its nucleotides.
+	///
+	/// Every multiple of [`ThreadedProteinLoader::CHUNK_LEN`] is
+	/// straddled by a long ORF, read on the forward strand at odd
+	/// multiples and on the reverse strand at even ones.
+	fn random_record(len: usize, seed: u64) -> String {
+		let mut state = seed | 1;
+		let mut result: Vec<u8> = (0..len)
+			.map(|_| {
+				state ^= state << 13;
+				state ^= state >> 7;
+				state ^= state << 17;
+				match state % 50 {
+					0 => b"RYSWKMBDHVN"[(state / 50 % 11) as usize],
+					x => b"GUAC"[(x % 4) as usize],
+				}
+			})
+			.collect();
+
+		let forward = format!("AUG{}UAA", "GCU".repeat(1000));
+		let reverse = format!("UUA{}CAU", "AGC".repeat(1000));
+		let chunk = ThreadedProteinLoader::CHUNK_LEN;
+		for (index, boundary) in (chunk..len).step_by(chunk).enumerate() {
+			let orf = [&forward, &reverse][index % 2].as_bytes();
+			let start = boundary - orf.len() / 2;
+			if let Some(target) = result.get_mut(start..start + orf.len()) {
+				target.copy_from_slice(orf);
+			}
 		}
+		String::from_utf8(result).unwrap()
+	}
+
+	/// A helper function that scans every record as a single chunk,
+	/// then collects the proteins found the way the loader does.
+	fn scan_whole(records: &[String], options: &OrfOptions) -> ProteinMap {
+		let frames = match options.both_strands {
+			true => &Frame::ALL[..],
+			false => &Frame::ALL[..3],
+		};
+		let mut found = BTreeMap::new();
+		for (record, text) in records.iter().enumerate() {
+			let sequence = NucleotideString::parse(text).unwrap();
+			let mut codes = Vec::new();
+			sequence.extend_codes(0, sequence.len(), &mut codes);
+
+			let mut proteins = BTreeMap::new();
+			let mut emit = collector(&mut proteins, record);
+			let scan = ChunkScan::run(&codes, codes.len(), 0, options, frames, &mut emit);
+			let mut carries = FrameCarry::start(frames.len());
+			FrameCarry::push_all(&mut carries, scan, options, frames, &mut emit);
+			for (carry, frame) in carries.into_iter().zip(frames) {
+				carry.finish(options, *frame, &mut emit);
+			}
+			drop(emit);
+			for (key, mut protein) in proteins {
+				protein.map_locations(|x| FrameCarry::fix_frame(x, codes.len()));
+				ProteinMap::merge_entry(&mut found, key, protein);
+			}
+		}
+		ProteinMap::from(found)
+	}
+
+	/// A helper function that checks whether two maps hold the
+	/// same proteins, found at the same locations, in the same
+	/// order and with the same ids.
+	fn assert_same(map: &ProteinMap, expected: &ProteinMap) {
+		assert_eq!(map.len(), expected.len());
+		let entries = map.range(0..map.len());
+		let expected_entries = expected.range(0..expected.len());
+		for ((id, protein), (expected_id, expected)) in entries.zip(expected_entries) {
+			assert!(id == expected_id);
+			assert_eq!(protein.to_string(), expected.to_string());
+			assert!(protein.get_locations() == expected.get_locations());
+		}
+	}
+
+	/// A helper function that checks whether a map holds an
+	/// ORF of a given strand straddling the end of a chunk.
+	fn straddles(map: &ProteinMap, strand: Strand) -> bool {
+		let chunk = ThreadedProteinLoader::CHUNK_LEN;
+		map.occurrences().any(|(_, x)| {
+			x.get_strand() == strand && x.get_start() / chunk != (x.get_end() - 1) / chunk
+		})
+	}
+
+	#[test]
+	fn chunks_are_scanned_as_a_whole() {
+		let chunk = ThreadedProteinLoader::CHUNK_LEN;
+		let lengths = [2 * chunk + 2000, chunk + 1, 500, chunk + 4000];
+		let records: Vec<_> = lengths
+			.iter()
+			.enumerate()
+			.map(|(index, &len)| random_record(len, index as u64 + 1))
+			.collect();
+		let mut text = String::new();
+		for (index, record) in records.iter().enumerate() {
+			text += &format!(">record{index}\n");
+			for line in record.as_bytes().chunks(80) {
+				text += std::str::from_utf8(line).unwrap();
+				text.push('\n');
+			}
+		}
+
+		let options = [
+			OrfOptions {
+				both_strands: true,
+				..Default::default()
+			},
+			OrfOptions {
+				both_strands: true,
+				definition: OrfDefinition::StopToStop,
+				partial: true,
+				..Default::default()
+			},
+		];
+		for options in options {
+			let expected = scan_whole(&records, &options);
+			let first = scan_whole(&records[..1], &options);
+			assert!(straddles(&first, Strand::Forward) && straddles(&first, Strand::Reverse));
+
+			for threads in [1, 4] {
+				let mut loader = ThreadedProteinLoader::with_threads(options.clone(), threads);
+				loader.start(text.clone());
+				let map = loader.wait().unwrap().unwrap();
+				assert_same(&map, &expected);
+				assert_eq!(map.get_records().len(), records.len());
+				assert_eq!(map.get_records()[3].get_id(), "record3");
+
+				loader.start_reader(Cursor::new(text.clone()), 0);
+				assert_same(&loader.wait().unwrap().unwrap(), &expected);
+
+				// Chunks of a parsed sequence end exactly at the
+				// multiples of the chunk length
+				let sequence = NucleotideString::parse(&records[0]).unwrap();
+				loader.start_sequence(sequence);
+				assert_same(&loader.wait().unwrap().unwrap(), &first);
+			}
+		}
+	}
+
+	#[test]
+	fn slices_and_readers_yield_the_same_map() {
+		let chunk = ThreadedProteinLoader::CHUNK_LEN;
+		let first = random_record(chunk + 100, 7);
+		let second = random_record(3 * chunk, 8);
+		// A sequence without a header, then records with
+		// Windows line endings and a header at a chunk boundary
+		let mut text = first.clone() + "\r\n>second record\r\n";
+		let padding = chunk - text.len() % chunk - 3;
+		text += &format!("{}\r\n>third\r\n", &second[..padding]);
+		text += &second[padding..];
+
+		let options = OrfOptions {
+			both_strands: true,
+			partial: true,
+			..Default::default()
+		};
+		let mut loader = ThreadedProteinLoader::new(options);
+		loader.start_bytes(text.clone().into_bytes());
+		let map = loader.wait().unwrap().unwrap();
+		loader.start_reader(Cursor::new(text.clone()), 0);
+		let expected = loader.wait().unwrap().unwrap();
+		assert_same(&map, &expected);
+		let headers = |map: &ProteinMap| {
+			let headers = map.get_records().iter().map(ToString::to_string);
+			headers.collect::<Vec<_>>()
+		};
+		assert_eq!(headers(&map), ["", "second record", "third"]);
+		assert_eq!(headers(&map), headers(&expected));
+
+		// Errors point at the same character
+		text.insert(chunk + 10, '!');
+		let position = |error| match error {
+			Some(Err(LoadError::InvalidCharacter {
+				character,
+				offset,
+				line,
+				column,
+			})) => (character, offset, line, column),
+			_ => panic!("expected an invalid character"),
+		};
+		loader.start_bytes(text.clone().into_bytes());
+		let error = position(loader.wait());
+		loader.start_reader(Cursor::new(text), 0);
+		assert_eq!(error, position(loader.wait()));
+		assert_eq!((error.0, error.1), ('!', chunk as u64 + 10));
+	}
+
+	#[test]
+	fn failed_load_never_yields_a_map() {
+		let mut loader = ThreadedProteinLoader::default();
+		loader.start("AUGUUUUAAx".to_owned());
+		let error = loader.wait().and_then(Result::err);
+		assert!(matches!(error, Some(LoadError::InvalidCharacter { .. })));
+		assert!(loader.is_ready());
+		assert!(loader.try_take().is_none());
+		assert!(loader.wait().is_none());
+	}
+
+	#[test]
+	fn cancelled_load_stops_and_yields_nothing() {
+		let options = OrfOptions {
+			both_strands: true,
+			..Default::default()
+		};
+		let mut loader = ThreadedProteinLoader::with_threads(options, 2);
+		let events = loader.subscribe();
+		loader.start_reader(Endless, 0);
+		let progress = events.recv_timeout(Duration::from_secs(10));
+		assert!(matches!(progress, Ok(LoadEvent::Progress { .. })));
+		assert!(!loader.is_ready());
+
+		let cancelled = Instant::now();
+		loader.cancel();
+		assert!(loader.is_ready() && loader.is_cancelled());
+		assert!(loader.wait().is_none());
+		assert!(cancelled.elapsed() < Duration::from_secs(5));
+		assert!(loader.try_take().is_none());
+		assert!(events.iter().any(|x| matches!(x, LoadEvent::Finished)));
+
+		// The next load is not affected by the cancelled one
+		loader.start("AUGAAAUAA".to_owned());
+		let map = loader.wait().unwrap().unwrap();
+		assert_eq!(map.keys().map(|x| x.0.as_str()).collect::<Vec<_>>(), ["K"]);
+		assert!(!loader.is_cancelled());
 	}
 }
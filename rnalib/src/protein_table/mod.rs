@@ -1,39 +1,115 @@
 //! The module that implements [`ProteinMap`]
 
 use std::{
-	collections::{btree_map::Keys, BTreeMap},
-	rc::Rc,
+	collections::{btree_map::Entry, BTreeMap},
+	io,
+	ops::{Index, Range},
+	sync::{Arc, OnceLock},
 };
 
+mod chunk;
+mod hits;
+mod id;
+pub use id::ProteinId;
 mod key;
 use key::Key;
 
 pub mod loader;
+mod orfs;
+pub use orfs::*;
+mod scanner;
 
 use crate::*;
 
 /// Holds a collection of proteins in optimal
 /// data format.
+///
+/// The [`Location`] of every occurrence refers to a record
+/// of the source, whose header is kept by the map as well,
+/// along with the annotated feature the occurrence was
+/// translated from, if any.
+///
+/// Every entry is given a [`ProteinId`], which allows to
+/// access it in constant time, while the entries are listed
+/// in the order of their keys, see [`ProteinMap::range`].
 #[derive(Default)]
 pub struct ProteinMap {
-	proteins: BTreeMap<Key, Protein>,
+	/// Proteins of the map, indexed by their ids
+	entries: Vec<Arc<Protein>>,
+	/// Ids of the entries, by their keys
+	order: BTreeMap<Key, ProteinId>,
+	/// Ids of the entries, in the order of their keys, listed
+	/// once the map is paged through after it has changed
+	pages: OnceLock<Vec<ProteinId>>,
+	/// Headers of the records of the source
+	records: Vec<RecordHeader>,
+	/// Annotated features of the source that
+	/// proteins were translated from
+	features: Vec<Feature>,
 }
 
 impl ProteinMap {
 	/// Constructs a [`ProteinMap`] from a vector of
 	/// [`Protein`] instances.
+	///
+	/// Occurrences of identical proteins are merged
+	/// into a single entry.
 	pub fn new(source: Vec<Protein>) -> Self {
-		let mut proteins = BTreeMap::new();
+		let mut result = Self::default();
 		for protein in source {
-			proteins.insert(Key(protein.to_string()), protein);
+			result.insert(protein);
 		}
-
-		Self { proteins }
+		result
 	}
 
 	/// Constructs a [`ProteinMap`] from a [`BTreeMap`].
+	///
+	/// The entries are given ids in the order of their keys.
 	pub fn from(proteins: BTreeMap<Key, Protein>) -> Self {
-		Self { proteins }
+		let mut entries = Vec::with_capacity(proteins.len());
+		let order = proteins.into_iter().map(|(key, mut protein)| {
+			protein.sort_locations();
+			entries.push(Arc::new(protein));
+			(key, ProteinId(entries.len() - 1))
+		});
+		// Built in bulk, as the keys are sorted already
+		let order = order.collect();
+		Self {
+			entries,
+			order,
+			..Self::default()
+		}
+	}
+
+	/// Inserts a [`Protein`] into this map. If an identical
+	/// protein is already present, the occurrences of both
+	/// are merged. Otherwise, the protein is given the
+	/// next unused [`ProteinId`].
+	///
+	/// Returns the id of the entry holding the protein.
+	///
+	/// Takes logarithmic time in the amount of entries, apart
+	/// from merging the occurrences. Once a new entry is added,
+	/// the next call to [`ProteinMap::range`] lists the entries
+	/// anew, in linear time.
+	pub fn insert(&mut self, mut protein: Protein) -> ProteinId {
+		match self.order.entry(Key(protein.to_string())) {
+			Entry::Occupied(entry) => {
+				let id = *entry.get();
+				let entry = Arc::make_mut(&mut self.entries[id.0]);
+				entry.merge(protein);
+				entry.sort_locations();
+				id
+			}
+			Entry::Vacant(entry) => {
+				let id = ProteinId(self.entries.len());
+				protein.sort_locations();
+				self.entries.push(Arc::new(protein));
+				entry.insert(id);
+				self.pages.take();
+				id
+			}
+		}
 	}
 
 	/// Parses a string containing an RNA sequence into a [`ProteinMap`].
@@ -41,39 +117,237 @@ impl ProteinMap {
 	///
 	/// Returns [`Err`] if any issue was encountered while
 	/// parsing.
-	pub fn parse(source: String) -> Result<Self, String> {
-		let mut importer = ThreadedProteinLoader::default();
-		importer.start(source);
-
-		while !importer.is_ready() {}
+	pub fn parse(source: String) -> Result<Self, LoadError> {
+		Self::from_sequence(NucleotideString::parse(&source)?)
+	}
 
-		match importer.take() {
-			Some(x) => Ok(x),
-			None => Err(String::from(Self::ERR_MESSAGE)),
-		}
+	/// Finds all the proteins encoded in a [`NucleotideString`].
+	/// Uses a [`ThreadedProteinLoader`] under the hood.
+	///
+	/// Returns [`Err`] if the sequence could not be scanned,
+	/// e.g. the scanning threads could not be spawned.
+	pub fn from_sequence(sequence: NucleotideString) -> Result<Self, LoadError> {
+		let mut importer = ThreadedProteinLoader::default();
+		importer.start_sequence(sequence);
+		let stopped = || Err(LoadError::Io(io::Error::other("load stopped unexpectedly")));
+		importer.wait().unwrap_or_else(stopped)
 	}
 
-	/// Returns an [`Rc`] wrapped [`Protein`] from this map,
-	/// given its key.
+	/// Returns a shared [`Protein`] from this map, given its
+	/// key. The protein is not copied, so it is cheap to keep
+	/// or pass to other threads.
 	///
 	/// Returns [`None`] if such a protein does not exist
 	/// in this map.
-	pub fn get(&self, key: &Key) -> Option<Rc<Protein>> {
-		self.proteins.get(key).map(|x| Rc::new(x.clone()))
+	pub fn get(&self, key: &Key) -> Option<Arc<Protein>> {
+		self.get_by_id(*self.order.get(key)?)
 	}
 
-	/// Returns an [`Rc`] wrapped [`Protein`] from this map,
+	/// Returns a shared [`Protein`] from this map,
 	/// given its textual representation.
 	///
 	/// See [`ProteinMap::get`] for more.
-	pub fn get_by_string(&self, key: String) -> Option<Rc<Protein>> {
-		self.proteins.get(&Key(key)).map(|x| Rc::new(x.clone()))
+	pub fn get_by_string(&self, key: String) -> Option<Arc<Protein>> {
+		self.get_by_id(self.get_id(&key)?)
+	}
+
+	/// Returns a shared [`Protein`] from this map,
+	/// given the [`ProteinId`] of its entry.
+	///
+	/// Returns [`None`] if the id does not belong to
+	/// this map.
+	pub fn get_by_id(&self, id: ProteinId) -> Option<Arc<Protein>> {
+		self.entries.get(id.0).map(Arc::clone)
+	}
+
+	/// Returns the [`ProteinId`] of the entry holding a protein,
+	/// given its textual representation.
+	///
+	/// Returns [`None`] if such a protein does not exist
+	/// in this map.
+	pub fn get_id(&self, key: &str) -> Option<ProteinId> {
+		self.order.get(&Key(key.to_owned())).copied()
+	}
+
+	/// Returns an iterator over the entries found at a given
+	/// range of positions in the order of their keys, such as
+	/// a single page of a list of proteins.
+	///
+	/// Positions past the end of the map are skipped.
+	pub fn range(
+		&self,
+		range: Range<usize>,
+	) -> impl ExactSizeIterator<Item = (ProteinId, &Protein)> + '_ {
+		let ids = || self.order.values().copied().collect();
+		let pages = self.pages.get_or_init(ids);
+		let end = range.end.min(pages.len());
+		let ids = pages[range.start.min(end)..end].iter();
+		ids.map(|&id| (id, &*self.entries[id.0]))
+	}
+
+	/// Returns an iterator over every occurrence of every
+	/// protein present in this map, as opposed to
+	/// [`ProteinMap::iter`], which yields unique sequences.
+	pub fn occurrences(&self) -> impl Iterator<Item = (&Protein, &Location)> {
+		let proteins = self.iter().map(|x| x.1);
+		proteins.flat_map(|x| x.get_locations().iter().map(move |location| (x, location)))
+	}
+
+	/// Returns the headers of all the records of the source,
+	/// in the order they were read.
+	///
+	/// Returns an empty slice if the map was not loaded
+	/// from a nucleotide sequence.
+	pub fn get_records(&self) -> &[RecordHeader] {
+		&self.records
+	}
+
+	/// Returns the header of the record a given
+	/// occurrence was found in.
+	///
+	/// Returns [`None`] if there is no such record.
+	pub fn get_record(&self, location: &Location) -> Option<&RecordHeader> {
+		self.records.get(location.get_record())
+	}
+
+	/// Returns the annotated features of the source, such as
+	/// GenBank coding sequences, that proteins were translated
+	/// from, see [`ThreadedProteinLoader::start_flat_file`].
+	///
+	/// Returns an empty slice if all the proteins were
+	/// predicted from the sequence.
+	pub fn get_features(&self) -> &[Feature] {
+		&self.features
+	}
+
+	/// Returns the annotated feature a given occurrence
+	/// was translated from.
+	///
+	/// Returns [`None`] if the occurrence was predicted.
+	pub fn get_feature(&self, location: &Location) -> Option<&Feature> {
+		self.features.get(location.get_feature()?)
+	}
+
+	/// Returns the amount of unique proteins present
+	/// in this map.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Returns true if this map holds no proteins.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Returns the total amount of occurrences of all
+	/// the proteins present in this map.
+	pub fn count_occurrences(&self) -> usize {
+		self.entries.iter().map(|x| x.get_count()).sum()
+	}
+
+	/// Return a list of all the keys present in this map,
+	/// in their order.
+	pub fn keys(&self) -> impl ExactSizeIterator<Item = &Key> + '_ {
+		self.iter().map(|x| x.0)
+	}
+
+	/// Returns an iterator over all the entries present
+	/// in this map, ordered by their keys.
+	pub fn iter(&self) -> impl ExactSizeIterator<Item = (&Key, &Protein)> + '_ {
+		let entries = self.order.iter();
+		entries.map(|(key, id)| (key, &*self.entries[id.0]))
 	}
 
-	/// Return a list of all the keys present in this map.
-	pub fn keys(&self) -> Keys<Key, Protein> {
-		self.proteins.keys()
+	/// A helper function that inserts a [`Protein`] into a
+	/// [`BTreeMap`], merging its occurrences with an identical
+	/// protein if one is already present.
+	fn merge_entry(target: &mut BTreeMap<Key, Protein>, key: Key, protein: Protein) {
+		match target.entry(key) {
+			Entry::Vacant(entry) => {
+				entry.insert(protein);
+			}
+			Entry::Occupied(mut entry) => entry.get_mut().merge(protein),
+		}
+	}
+}
+
+impl Index<ProteinId> for ProteinMap {
+	type Output = Protein;
+
+	/// Returns the [`Protein`] held by the entry
+	/// with a given [`ProteinId`].
+	///
+	/// Panics if the id does not belong to this map.
+	fn index(&self, id: ProteinId) -> &Self::Output {
+		&self.entries[id.0]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A helper function that constructs a [`Protein`] from
+	/// its one-letter codes, found at a given offset.
+	fn protein(shorthands: &str, start: usize) -> Protein {
+		let codons = shorthands.chars().filter_map(Codon::from_shorthand);
+		let end = start + 3 * shorthands.len();
+		let location = Location::new(start, end, Frame::ALL[0], None, None);
+		Protein::with_location(AminoString::from(codons.collect()), location)
 	}
 
-	pub const ERR_MESSAGE: &str = "Wystąpił problem podczas wczytywania białek. Upewnij się, że dane wejściowe są poprawne, lub skonfiguruj importer do usuwania niepoprawnych znaków.";
+	#[test]
+	fn entries_are_paged_in_the_order_of_keys() {
+		let mut map = ProteinMap::default();
+		let strings = ["MKV", "A", "MK", "WW", "MKV", "C"];
+		let ids: Vec<_> = strings
+			.iter()
+			.enumerate()
+			.map(|(index, x)| map.insert(protein(x, 3 * index)))
+			.collect();
+		let indices: Vec<_> = ids.iter().map(ProteinId::get_index).collect();
+		assert_eq!(indices, [0, 1, 2, 3, 0, 4]);
+		assert_eq!(map.len(), 5);
+		assert_eq!(map.count_occurrences(), 6);
+
+		let entry = |(id, x): (ProteinId, &Protein)| format!("{id} {x}");
+		let page = |range| map.range(range).map(entry).collect::<Vec<_>>();
+		assert_eq!(page(0..2), ["#1 A", "#4 C"]);
+		assert_eq!(page(2..4), ["#2 MK", "#3 WW"]);
+		assert_eq!(page(4..6), ["#0 MKV"]);
+		assert!(page(6..8).is_empty());
+		assert_eq!(map.range(1..10).len(), 4);
+
+		let merged = map.get_by_id(ids[0]).unwrap();
+		assert_eq!(merged.to_string(), "MKV");
+		assert_eq!(merged.get_count(), 2);
+		assert!(map.get_by_id(ProteinId(5)).is_none());
+		assert!(map.get_id("WW") == Some(ids[3]));
+		assert!(map.get_id("W").is_none());
+		assert_eq!(map[ids[2]].to_string(), "MK");
+
+		// Pages are listed anew once an entry is added
+		assert!(map.insert(protein("MK", 30)) == ids[2]);
+		assert_eq!(map.insert(protein("AA", 33)).get_index(), 5);
+		let page = |range| map.range(range).map(entry).collect::<Vec<_>>();
+		assert_eq!(page(2..4), ["#5 AA", "#2 MK"]);
+		assert_eq!(page(5..7), ["#0 MKV"]);
+		assert_eq!(map[ids[2]].get_count(), 2);
+	}
+
+	#[test]
+	fn maps_are_built_from_sorted_entries() {
+		let mut proteins = BTreeMap::new();
+		for (index, x) in ["WW", "A", "MKV", "A"].iter().enumerate() {
+			ProteinMap::merge_entry(&mut proteins, Key(x.to_string()), protein(x, 3 * index));
+		}
+		let mut map = ProteinMap::from(proteins);
+		let keys: Vec<_> = map.keys().map(|x| x.0.as_str()).collect();
+		assert_eq!(keys, ["A", "WW", "MKV"]);
+		assert!(map.get_id("MKV") == Some(ProteinId(2)));
+		assert_eq!(map[ProteinId(0)].get_count(), 2);
+		assert_eq!(map.insert(protein("C", 12)).get_index(), 3);
+		let entries = map.range(0..2).map(|(id, x)| format!("{id} {x}"));
+		assert_eq!(entries.collect::<Vec<_>>(), ["#0 A", "#3 C"]);
+	}
 }
@@ -0,0 +1,169 @@
+//! The module that implements [`orfs`] and [`Orf`]
+
+use std::{collections::VecDeque, iter::FusedIterator};
+
+use crate::{AminoString, Codons, Frame, Location, NucleotideSlice, OrfOptions, Protein, Strand};
+
+use super::scanner::OrfScanner;
+
+/// A single occurrence of an open reading frame,
+/// yielded by [`orfs`].
+#[derive(Clone)]
+pub struct Orf {
+	string: AminoString,
+	location: Location,
+}
+
+impl Orf {
+	/// Returns the protein encoded by the ORF, keeping
+	/// the triplets it was translated from.
+	pub fn get_string(&self) -> &AminoString {
+		&self.string
+	}
+
+	/// Returns the [`Location`] of the ORF.
+	pub fn get_location(&self) -> Location {
+		self.location
+	}
+
+	/// Converts the ORF into a [`Protein`] with
+	/// a single occurrence.
+	pub fn into_protein(self) -> Protein {
+		Protein::with_location(self.string, self.location)
+	}
+}
+
+/// Returns an iterator lazily finding the open reading frames
+/// of a nucleotide sequence, according to given [`OrfOptions`].
+///
+/// Every occurrence is yielded separately, frame by frame, in
+/// the order of [`Frame::ALL`]. Within a frame, ORFs are yielded
+/// as soon as the codon that closes them is read, so nested ORFs
+/// come after the ones that contain them. The ORFs are the same
+/// as the ones a [`ThreadedProteinLoader`](crate::ThreadedProteinLoader)
+/// finds in a single record.
+///
+/// Offsets of the locations are counted from the start of the
+/// slice, which is treated as a record of its own.
+pub fn orfs<'a>(sequence: NucleotideSlice<'a>, options: &'a OrfOptions) -> Orfs<'a> {
+	let frames: &'static [Frame] = &Frame::ALL;
+	let frames = match options.both_strands {
+		true => frames,
+		false => &frames[..3],
+	};
+	Orfs {
+		sequence,
+		options,
+		frames: frames.iter(),
+		current: None,
+		found: VecDeque::new(),
+	}
+}
+
+/// A reading frame which is being scanned by [`Orfs`].
+struct FrameReader<'a> {
+	scanner: OrfScanner<'a>,
+	codons: Codons<'a>,
+	frame: Frame,
+	/// Amount of codons read so far
+	read: usize,
+}
+
+impl FrameReader<'_> {
+	/// A helper function that returns the offset of the next
+	/// codon of the frame, on the forward strand.
+	fn position(&self, len: usize) -> usize {
+		let offset = self.frame.get_offset() as usize + 3 * self.read;
+		match self.frame.get_strand() {
+			Strand::Forward => offset,
+			Strand::Reverse => len - offset - 3,
+		}
+	}
+}
+
+/// A lazy iterator over the open reading frames of a
+/// nucleotide sequence, created by [`orfs`].
+pub struct Orfs<'a> {
+	sequence: NucleotideSlice<'a>,
+	options: &'a OrfOptions,
+	/// Frames that have not been scanned yet
+	frames: std::slice::Iter<'static, Frame>,
+	current: Option<FrameReader<'a>>,
+	/// ORFs that have been found, but not yielded yet
+	found: VecDeque<Orf>,
+}
+
+impl Iterator for Orfs<'_> {
+	type Item = Orf;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let len = self.sequence.len();
+		loop {
+			if let Some(orf) = self.found.pop_front() {
+				return Some(orf);
+			}
+
+			let found = &mut self.found;
+			let mut emit = |_, string, location| found.push_back(Orf { string, location });
+			let Some(reader) = &mut self.current else {
+				let &frame = self.frames.next()?;
+				self.current = Some(FrameReader {
+					scanner: OrfScanner::new(self.options, frame),
+					codons: self.sequence.codons(frame),
+					frame,
+					read: 0,
+				});
+				continue;
+			};
+
+			match reader.codons.next() {
+				Some(triplet) => {
+					let at = reader.position(len);
+					reader.scanner.push(triplet, at, &mut emit);
+					reader.read += 1;
+				}
+				None => {
+					if let Some(reader) = self.current.take() {
+						reader.scanner.finish(&mut emit);
+					}
+				}
+			}
+		}
+	}
+}
+
+impl FusedIterator for Orfs<'_> {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{NucleotideString, ProteinMap};
+
+	#[test]
+	fn orfs_match_the_loader() {
+		let mut state = 0x2545_F491_4F6C_DD1Du64;
+		let text: String = (0..150_000)
+			.map(|_| {
+				state ^= state << 13;
+				state ^= state >> 7;
+				state ^= state << 17;
+				match state % 40 {
+					0 => b"RYSWKMBDHVN"[(state / 40 % 11) as usize] as char,
+					x => b"GUAC"[(x % 4) as usize] as char,
+				}
+			})
+			.collect();
+		let sequence = NucleotideString::parse(&text).unwrap();
+
+		let options = OrfOptions::default();
+		let found = orfs(sequence.as_slice(), &options).map(Orf::into_protein);
+		let expected = ProteinMap::new(found.collect());
+		let map = ProteinMap::from_sequence(sequence).unwrap();
+		assert_eq!(map.len(), expected.len());
+		assert!(map.len() > 100);
+		for ((key, protein), (expected_key, expected)) in map.iter().zip(expected.iter()) {
+			assert!(key == expected_key);
+			assert!(protein.get_locations() == expected.get_locations());
+		}
+	}
+}
@@ -0,0 +1,277 @@
+//! The module that implements [`OrfScanner`]
+
+use crate::{
+	AminoString, Codon, Frame, IupacNucleotide, Location, OrfDefinition, OrfOptions, Strand,
+	Triplet,
+};
+
+use super::key::Key;
+
+/// An ORF that has been opened, but not yet closed
+/// by a stop codon.
+struct OpenOrf {
+	/// Index of the first amino acid of the ORF
+	/// in the shared codon buffer
+	index: usize,
+	/// Offset of the codon that opened the ORF,
+	/// on the forward strand
+	position: usize,
+	/// The codon that opened the ORF
+	start_codon: Option<[IupacNucleotide; 3]>,
+	/// True if the ORF was opened by neither a start
+	/// nor a stop codon
+	partial: bool,
+}
+
+/// A state machine that reads the codons of a single
+/// reading frame one by one, in the order of translation,
+/// and assembles them into proteins according to [`OrfOptions`].
+///
+/// Every protein found is passed to the `emit` callback along
+/// with its [`Key`] and [`Location`], keeping the triplets it was
+/// translated from. The scanner only keeps the amino acids of the
+/// ORFs that are currently open.
+pub(crate) struct OrfScanner<'a> {
+	options: &'a OrfOptions,
+	frame: Frame,
+	/// Offset of the last codon read, on the forward strand
+	last: usize,
+	/// True if the next codon opens a stop-to-stop ORF
+	pending: bool,
+	/// True if a stop codon has already been read
+	bounded: bool,
+	/// Amino acids read since the outermost open ORF started
+	codons: Vec<Codon>,
+	/// Triplets of the amino acids in `codons`
+	triplets: Vec<Option<Triplet>>,
+	/// Currently open ORFs, outermost first
+	open: Vec<OpenOrf>,
+}
+
+impl<'a> OrfScanner<'a> {
+	/// Constructs an [`OrfScanner`] reading a given [`Frame`]
+	/// from the beginning of the sequence.
+	pub fn new(options: &'a OrfOptions, frame: Frame) -> Self {
+		Self {
+			options,
+			frame,
+			last: 0,
+			pending: options.definition == OrfDefinition::StopToStop,
+			bounded: false,
+			codons: Vec::new(),
+			triplets: Vec::new(),
+			open: Vec::new(),
+		}
+	}
+
+	/// Constructs an [`OrfScanner`] reading a given [`Frame`]
+	/// right after a stop codon.
+	pub fn after_stop(options: &'a OrfOptions, frame: Frame) -> Self {
+		Self {
+			bounded: true,
+			..Self::new(options, frame)
+		}
+	}
+
+	/// Reads the next codon of the frame, as read on the frame's
+	/// strand, found at a given offset of the forward strand.
+	pub fn push(
+		&mut self,
+		triplet: [IupacNucleotide; 3],
+		at: usize,
+		emit: &mut impl FnMut(Key, AminoString, Location),
+	) {
+		let [a, b, c] = triplet;
+		let codon = self.options.translate(a, b, c);
+		self.last = at;
+
+		if codon == Codon::STOP {
+			self.close_all(Some(triplet), at, emit);
+			self.pending = self.options.definition == OrfDefinition::StopToStop;
+			self.bounded = true;
+			return;
+		}
+
+		if self.pending {
+			self.pending = false;
+			self.open_orf(None, at);
+		}
+
+		if !self.open.is_empty() {
+			self.codons.push(codon);
+			self.triplets.push(Triplet::resolve(a, b, c));
+		}
+
+		let definition = self.options.definition;
+		let may_open = self.open.is_empty() || self.options.nested;
+		if definition == OrfDefinition::StartToStop && may_open && self.options.is_start(a, b, c) {
+			self.open_orf(Some(triplet), at);
+		}
+	}
+
+	/// Finishes reading the frame, passing the ORFs that
+	/// were never closed to `emit` if partial ORFs are
+	/// to be reported.
+	pub fn finish(mut self, emit: &mut impl FnMut(Key, AminoString, Location)) {
+		if self.options.partial {
+			self.close_all(None, self.last, emit);
+		}
+	}
+
+	/// A helper function that opens a new ORF at a given offset.
+	fn open_orf(&mut self, start_codon: Option<[IupacNucleotide; 3]>, at: usize) {
+		self.open.push(OpenOrf {
+			index: self.codons.len(),
+			position: at,
+			partial: start_codon.is_none() && !self.bounded,
+			start_codon,
+		});
+	}
+
+	/// A helper function that closes all the open ORFs at
+	/// the codon found at a given offset, and emits the ones
+	/// that qualify.
+	fn close_all(
+		&mut self,
+		stop_codon: Option<[IupacNucleotide; 3]>,
+		at: usize,
+		emit: &mut impl FnMut(Key, AminoString, Location),
+	) {
+		for orf in self.open.drain(..) {
+			let partial = orf.partial || stop_codon.is_none();
+			let codons = &self.codons[orf.index..];
+			if (partial && !self.options.partial) || !self.options.accepts_length(codons.len()) {
+				continue;
+			}
+
+			let (from, to) = match self.frame.get_strand() {
+				Strand::Forward => (orf.position, at + 3),
+				Strand::Reverse => (at, orf.position + 3),
+			};
+			let location = Location::new(from, to, self.frame, orf.start_codon, stop_codon);
+			let key = Key(codons.iter().map(|x| x.get_acid_shorthand()).collect());
+			let triplets = self.triplets[orf.index..].to_vec();
+			emit(
+				key,
+				AminoString::with_triplets(codons.to_vec(), triplets),
+				location,
+			);
+		}
+		self.codons.clear();
+		self.triplets.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{GeneticCode, Nucleotide, StartCodons};
+
+	/// A helper function that reads codons separated by spaces
+	/// in the first forward frame, and returns the proteins found
+	/// along with the ranges of nucleotides they cover.
+	fn scan(text: &str, options: &OrfOptions) -> Vec<(String, usize, usize)> {
+		let mut found = Vec::new();
+		let mut emit = |key: Key, _, location: Location| {
+			found.push((key.0, location.get_start(), location.get_end()));
+		};
+		let mut scanner = OrfScanner::new(options, Frame::new(Strand::Forward, 0));
+		for (index, codon) in text.split_whitespace().enumerate() {
+			let mut symbols = codon.chars().map(|x| IupacNucleotide::parse(x).unwrap());
+			let triplet = [(); 3].map(|_| symbols.next().unwrap());
+			scanner.push(triplet, 3 * index, &mut emit);
+		}
+		scanner.finish(&mut emit);
+		found
+	}
+
+	/// A helper function that returns the proteins
+	/// found, without their locations.
+	fn proteins(text: &str, options: &OrfOptions) -> Vec<String> {
+		scan(text, options).into_iter().map(|x| x.0).collect()
+	}
+
+	#[test]
+	fn starts_depend_on_the_options() {
+		let table = OrfOptions {
+			starts: StartCodons::Table,
+			..Default::default()
+		};
+		let bacterial = OrfOptions {
+			code: GeneticCode::BACTERIAL_PLASTID,
+			..table.clone()
+		};
+		assert!(proteins("GUG AAA UAA", &OrfOptions::default()).is_empty());
+		assert!(proteins("GUG AAA UAA", &table).is_empty());
+		assert_eq!(proteins("GUG AAA UAA", &bacterial), ["K"]);
+		assert_eq!(proteins("UUG AAA UAA", &bacterial), ["K"]);
+		assert_eq!(scan("CCC UUG AAA UAA", &bacterial), [("K".into(), 3, 12)]);
+
+		let custom = OrfOptions {
+			starts: StartCodons::Custom(vec![[Nucleotide::C, Nucleotide::U, Nucleotide::G]]),
+			..Default::default()
+		};
+		assert_eq!(proteins("CUG GGG UAA", &custom), ["G"]);
+		assert!(proteins("AUG GGG UAA", &custom).is_empty());
+		// Ambiguous codons open ORFs only under the NCBI tables
+		assert!(proteins("CUN GGG UAA", &custom).is_empty());
+		assert_eq!(proteins("NUG GGG UAA", &bacterial), ["G"]);
+	}
+
+	#[test]
+	fn lengths_are_limited() {
+		let text = "AUG AAA AAA AAA UAA";
+		let limited = |min_length, max_length| OrfOptions {
+			min_length,
+			max_length,
+			..Default::default()
+		};
+		assert_eq!(proteins(text, &limited(3, None)), ["KKK"]);
+		assert!(proteins(text, &limited(4, None)).is_empty());
+		assert_eq!(proteins(text, &limited(1, Some(3))), ["KKK"]);
+		assert!(proteins(text, &limited(1, Some(2))).is_empty());
+		assert_eq!(proteins(text, &limited(3, Some(3))), ["KKK"]);
+
+		// Empty proteins are never reported
+		assert!(proteins("AUG UAA", &limited(0, None)).is_empty());
+	}
+
+	#[test]
+	fn nested_and_partial_orfs_are_optional() {
+		let text = "AUG AAA AUG CCC UAA";
+		let nested = OrfOptions {
+			nested: true,
+			..Default::default()
+		};
+		assert_eq!(scan(text, &OrfOptions::default()), [("KMP".into(), 0, 15)]);
+		let both = [("KMP".into(), 0, 15), ("P".into(), 6, 15)];
+		assert_eq!(scan(text, &nested), both);
+
+		let text = "AUG AAA CCC";
+		let partial = OrfOptions {
+			partial: true,
+			..Default::default()
+		};
+		assert!(proteins(text, &OrfOptions::default()).is_empty());
+		assert_eq!(scan(text, &partial), [("KP".into(), 0, 9)]);
+	}
+
+	#[test]
+	fn orfs_span_between_stops_or_from_starts() {
+		let text = "GGG UAA GGG AUG CCC UAG AAA";
+		let stop_to_stop = OrfOptions {
+			definition: OrfDefinition::StopToStop,
+			..Default::default()
+		};
+		assert_eq!(scan(text, &OrfOptions::default()), [("P".into(), 9, 18)]);
+		assert_eq!(scan(text, &stop_to_stop), [("GMP".into(), 6, 18)]);
+
+		// Regions at the ends of the sequence are not bounded by stops
+		let partial = OrfOptions {
+			partial: true,
+			..stop_to_stop
+		};
+		let found = proteins(text, &partial);
+		assert_eq!(found, ["G", "GMP", "K"]);
+	}
+}
@@ -0,0 +1,51 @@
+//! The module that implements [`RecordHeader`]
+
+use std::fmt::Display;
+
+/// Identifies a single record of a multi-record
+/// source, such as a FASTA file.
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct RecordHeader {
+	id: String,
+	description: String,
+}
+
+impl RecordHeader {
+	/// Constructs a [`RecordHeader`] from an identifier
+	/// and a description, which may be empty.
+	pub fn new(id: String, description: String) -> Self {
+		Self { id, description }
+	}
+
+	/// Parses a FASTA header line, with or without the
+	/// leading '>'. The identifier spans up to the first
+	/// whitespace, the rest of the line is the description.
+	pub fn parse(line: &str) -> Self {
+		let line = line.trim();
+		let line = line.strip_prefix('>').unwrap_or(line).trim_start();
+		match line.split_once(char::is_whitespace) {
+			Some((id, description)) => Self::new(id.into(), description.trim().into()),
+			None => Self::new(line.into(), String::new()),
+		}
+	}
+
+	/// Returns the identifier of the record, which is
+	/// empty for records read from sources without headers.
+	pub fn get_id(&self) -> &str {
+		&self.id
+	}
+
+	/// Returns the description of the record.
+	pub fn get_description(&self) -> &str {
+		&self.description
+	}
+}
+
+impl Display for RecordHeader {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.description.is_empty() {
+			true => write!(f, "{}", self.id),
+			false => write!(f, "{} {}", self.id, self.description),
+		}
+	}
+}
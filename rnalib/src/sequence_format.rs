@@ -0,0 +1,51 @@
+//! The module that implements [`SequenceFormat`]
+
+use crate::FlatFileFormat;
+
+/// The text formats of nucleotide data, which can be
+/// told apart by their content.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SequenceFormat {
+	/// A bare sequence, possibly preceded by
+	/// a few lines of other text
+	Raw,
+	/// Records starting with '>' header lines,
+	/// see [`FastaReader`](crate::FastaReader)
+	Fasta,
+	/// Reads with quality scores,
+	/// see [`FastqReader`](crate::FastqReader)
+	Fastq,
+	/// An annotated GenBank or EMBL file,
+	/// see [`FlatFileReader`](crate::FlatFileReader)
+	FlatFile(FlatFileFormat),
+}
+
+impl SequenceFormat {
+	/// Guesses the format of data starting with given bytes,
+	/// which should be decompressed already, see
+	/// [`Decompressor`](crate::Decompressor).
+	///
+	/// Only the first line that is not blank is inspected,
+	/// apart from FASTQ reads, whose third line has to start
+	/// with '+'. Data that matches no other format is taken
+	/// for a [`SequenceFormat::Raw`] sequence.
+	pub fn detect(sample: &[u8]) -> Self {
+		let sample = sample.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(sample);
+		let mut lines = sample
+			.split(|&x| x == b'\n')
+			.filter(|x| !x.trim_ascii().is_empty());
+		let Some(first) = lines.next() else { return SequenceFormat::Raw };
+
+		if first.starts_with(b"LOCUS ") {
+			SequenceFormat::FlatFile(FlatFileFormat::GenBank)
+		} else if first.starts_with(b"ID   ") {
+			SequenceFormat::FlatFile(FlatFileFormat::Embl)
+		} else if first.starts_with(b">") {
+			SequenceFormat::Fasta
+		} else if first.starts_with(b"@") && lines.nth(1).is_some_and(|x| x.starts_with(b"+")) {
+			SequenceFormat::Fastq
+		} else {
+			SequenceFormat::Raw
+		}
+	}
+}
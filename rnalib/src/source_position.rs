@@ -0,0 +1,36 @@
+//! The module that implements [`SourcePosition`]
+
+/// A position of a byte in the source.
+#[derive(Clone, Copy)]
+pub struct SourcePosition {
+	/// 0-based offset, in bytes
+	pub offset: u64,
+	/// 1-based line number
+	pub line: u64,
+	/// 1-based column, in characters
+	pub column: u64,
+}
+
+impl SourcePosition {
+	/// Moves the position past a given byte.
+	pub(crate) fn advance(&mut self, byte: u8) {
+		self.offset += 1;
+		if byte == b'\n' {
+			self.line += 1;
+			self.column = 1;
+		} else if byte & 0xC0 != 0x80 {
+			// UTF-8 continuation bytes do not start a new character
+			self.column += 1;
+		}
+	}
+}
+
+impl Default for SourcePosition {
+	fn default() -> Self {
+		Self {
+			offset: 0,
+			line: 1,
+			column: 1,
+		}
+	}
+}
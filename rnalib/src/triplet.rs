@@ -0,0 +1,167 @@
+//! The module that implements [`Triplet`]
+
+use std::{fmt::Display, num::NonZeroU8};
+
+use crate::{Codon, GeneticCode, IupacNucleotide, Nucleotide};
+
+/// Represents a single codon as the three nucleotides
+/// it consists of, i.e. one of the 64 possible triplets.
+///
+/// Unlike [`Codon`], which only describes the amino acid
+/// encoded, a [`Triplet`] tells synonymous codons apart.
+/// Translating it is a separate step, see [`Triplet::translate`].
+///
+/// Triplets are ordered as in the NCBI (TCAG) tables.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Triplet {
+	/// Index of the triplet in the NCBI (TCAG) order plus one,
+	/// so that an `Option<Triplet>` takes a single byte.
+	index: NonZeroU8,
+}
+
+impl Triplet {
+	/// Constructs a [`Triplet`] from three sequential
+	/// instances of [`Nucleotide`].
+	pub const fn new(a: Nucleotide, b: Nucleotide, c: Nucleotide) -> Self {
+		const fn order(x: Nucleotide) -> u8 {
+			match x {
+				Nucleotide::U => 0,
+				Nucleotide::C => 1,
+				Nucleotide::A => 2,
+				Nucleotide::G => 3,
+			}
+		}
+		let index = order(a) * 16 + order(b) * 4 + order(c);
+		match NonZeroU8::new(index + 1) {
+			Some(index) => Self { index },
+			None => unreachable!(),
+		}
+	}
+
+	/// Constructs a [`Triplet`] from three sequential
+	/// instances of [`IupacNucleotide`].
+	///
+	/// Returns [`None`] if any of them is ambiguous.
+	pub const fn resolve(
+		a: IupacNucleotide,
+		b: IupacNucleotide,
+		c: IupacNucleotide,
+	) -> Option<Self> {
+		match (a.resolve(), b.resolve(), c.resolve()) {
+			(Some(a), Some(b), Some(c)) => Some(Self::new(a, b, c)),
+			_ => None,
+		}
+	}
+
+	/// Constructs a [`Triplet`] given its index in the
+	/// NCBI (TCAG) order.
+	///
+	/// Returns [`None`] if the index is not below 64.
+	pub const fn from_index(index: usize) -> Option<Self> {
+		if index >= 64 {
+			return None;
+		}
+		match NonZeroU8::new(index as u8 + 1) {
+			Some(index) => Some(Self { index }),
+			None => None,
+		}
+	}
+
+	/// Attempt to parse and return a [`Triplet`] from a string
+	/// of exactly three nucleotides. Ignores letter-case.
+	///
+	/// Returns [`None`] if the string is not such a triplet.
+	pub fn parse(from: &str) -> Option<Self> {
+		let mut chars = from.chars().map(Nucleotide::parse);
+		match (chars.next(), chars.next(), chars.next(), chars.next()) {
+			(Some(Some(a)), Some(Some(b)), Some(Some(c)), None) => Some(Self::new(a, b, c)),
+			_ => None,
+		}
+	}
+
+	/// Returns an iterator over all 64 triplets,
+	/// in the NCBI (TCAG) order.
+	pub fn all() -> impl Iterator<Item = Self> {
+		(0..64).filter_map(Self::from_index)
+	}
+
+	/// Returns the index of this triplet in the
+	/// NCBI (TCAG) order, from 0 to 63.
+	pub const fn get_index(&self) -> usize {
+		self.index.get() as usize - 1
+	}
+
+	/// Returns the nucleotides this triplet consists of.
+	pub const fn get_nucleotides(&self) -> [Nucleotide; 3] {
+		const ORDER: [Nucleotide; 4] = [Nucleotide::U, Nucleotide::C, Nucleotide::A, Nucleotide::G];
+		let index = self.get_index();
+		[ORDER[index / 16], ORDER[index / 4 % 4], ORDER[index % 4]]
+	}
+
+	/// Translates this triplet into a [`Codon`], according
+	/// to a given [`GeneticCode`].
+	pub const fn translate(&self, code: &GeneticCode) -> Codon {
+		let [a, b, c] = self.get_nucleotides();
+		code.translate(a, b, c)
+	}
+
+	/// Returns true if this triplet and another one encode
+	/// the same amino acid, or both terminate translation,
+	/// according to a given [`GeneticCode`].
+	pub fn is_synonymous(&self, other: Triplet, code: &GeneticCode) -> bool {
+		self.translate(code) == other.translate(code)
+	}
+}
+
+impl From<Triplet> for [Nucleotide; 3] {
+	fn from(value: Triplet) -> Self {
+		value.get_nucleotides()
+	}
+}
+
+impl Display for Triplet {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for nucleotide in self.get_nucleotides() {
+			write!(f, "{nucleotide}")?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn triplets_encode_and_decode() {
+		for (index, triplet) in Triplet::all().enumerate() {
+			assert_eq!(triplet.get_index(), index);
+			let [a, b, c] = triplet.get_nucleotides();
+			assert!(Triplet::new(a, b, c) == triplet);
+			assert!(Triplet::parse(&triplet.to_string()) == Some(triplet));
+			assert!(Triplet::resolve(a.into(), b.into(), c.into()) == Some(triplet));
+		}
+		assert_eq!(Triplet::all().count(), 64);
+		assert!(Triplet::from_index(64).is_none());
+		assert_eq!(std::mem::size_of::<Option<Triplet>>(), 1);
+
+		// The NCBI order starts with UUU and ends with GGG
+		assert_eq!(Triplet::from_index(0).unwrap().to_string(), "UUU");
+		assert_eq!(Triplet::parse("tgg").unwrap().get_index(), 15);
+		assert_eq!(Triplet::from_index(63).unwrap().to_string(), "GGG");
+		assert!(Triplet::parse("UGGA").is_none() && Triplet::parse("UG").is_none());
+		let [a, b, c] = [IupacNucleotide::U, IupacNucleotide::R, IupacNucleotide::G];
+		assert!(Triplet::resolve(a, b, c).is_none());
+	}
+
+	#[test]
+	fn synonymous_triplets_depend_on_the_code() {
+		let [uga, ugg, uaa] = ["UGA", "UGG", "UAA"].map(|x| Triplet::parse(x).unwrap());
+		let standard = GeneticCode::STANDARD;
+		let mitochondrial = GeneticCode::VERTEBRATE_MITOCHONDRIAL;
+		assert!(uga.is_synonymous(uaa, &standard));
+		assert!(!uga.is_synonymous(ugg, &standard));
+		assert!(uga.is_synonymous(ugg, &mitochondrial));
+		assert!(ugg.translate(&standard) == Codon::from_shorthand('W').unwrap());
+	}
+}